@@ -0,0 +1,791 @@
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+use walkdir::WalkDir;
+
+use crate::file_metadata::{get_cached_checksums, get_checksum};
+use crate::install::{install_mod, InstallReport, InstallSource};
+use crate::metadata::{
+    installed_layout, is_excluded, load_metadata, psarc_source_directory, replace_game_id,
+    Metadata, ModFileType,
+};
+use crate::rpcs3_directory::Rpcs3Directory;
+
+const BUNDLE_METADATA: &str = "metadata.json";
+// Makes the extraction folder unique even for imports started in the same millisecond
+static IMPORT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Zip(#[from] zip::result::ZipError),
+    #[error("unsupported archive '{0}', expected a .zip or .tar.zst file")]
+    UnsupportedArchive(String),
+    #[error("invalid bundle: {0}")]
+    InvalidBundle(String),
+    #[error("the installation can't be exported: {0}")]
+    InvalidInstallation(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum ArchiveFormat {
+    Zip,
+    TarZst,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &Path) -> Result<ArchiveFormat> {
+        let file_name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if file_name.ends_with(".zip") {
+            Ok(ArchiveFormat::Zip)
+        } else if file_name.ends_with(".tar.zst") || file_name.ends_with(".tzst") {
+            Ok(ArchiveFormat::TarZst)
+        } else {
+            Err(Error::UnsupportedArchive(file_name))
+        }
+    }
+}
+
+// metadata.json of a bundle is a regular metadata.json,
+// with the md5 of every file in the bundle so the base folders can be checked too.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct BundleMetadata {
+    #[serde(flatten)]
+    pub metadata: Metadata,
+    #[serde(default)]
+    pub checksums: BTreeMap<String, String>,
+}
+
+fn extract_zip(archive_path: &Path, destination: &Path) -> Result<()> {
+    let mut archive = zip::ZipArchive::new(File::open(archive_path)?)?;
+
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index)?;
+        // Entries with absolute paths or ".." are rejected instead of written outside the destination
+        let Some(relative_path) = entry.enclosed_name().map(|path| path.to_path_buf()) else {
+            return Err(Error::InvalidBundle(format!(
+                "unsafe path '{}'",
+                entry.name()
+            )));
+        };
+        let output_path = destination.join(relative_path);
+
+        if entry.is_dir() {
+            fs::create_dir_all(&output_path)?;
+            continue;
+        }
+        if let Some(parent) = output_path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        io::copy(&mut entry, &mut File::create(&output_path)?)?;
+    }
+
+    Ok(())
+}
+
+fn extract_tar_zst(archive_path: &Path, destination: &Path) -> Result<()> {
+    let decoder = zstd::Decoder::new(File::open(archive_path)?)?;
+    let mut archive = tar::Archive::new(decoder);
+    // unpack skips entries that would end up outside the destination
+    archive.unpack(destination)?;
+    Ok(())
+}
+
+pub fn extract_bundle(archive_path: &Path, destination: &Path) -> Result<()> {
+    let format = ArchiveFormat::from_path(archive_path)?;
+
+    if destination.exists() {
+        fs::remove_dir_all(destination)?;
+    }
+    fs::create_dir_all(destination)?;
+
+    match format {
+        ArchiveFormat::Zip => extract_zip(archive_path, destination),
+        ArchiveFormat::TarZst => extract_tar_zst(archive_path, destination),
+    }
+}
+
+async fn file_matches(path: &Path, md5: &str) -> bool {
+    path.is_file() && get_checksum(&path.display().to_string()).await == md5
+}
+
+// Everything the install needs for this game version has to be in the bundle and match its md5
+async fn validate_bundle(root: &Path, bundle: &BundleMetadata, game_id: &str) -> Vec<String> {
+    let metadata = &bundle.metadata;
+    let mut errors: Vec<String> = Vec::new();
+
+    if metadata.files_for(game_id).next().is_none() {
+        errors.push(format!("no files for {}", game_id));
+    }
+
+    let base_path = replace_game_id(&metadata.base.remote_path, game_id);
+    if !root.join(&base_path).is_dir() {
+        errors.push(format!("missing {}", base_path));
+    }
+
+    for file in metadata.files_for(game_id) {
+        let remote_path = replace_game_id(&file.remote_path, game_id);
+        let valid = match file.file_type {
+            ModFileType::File => file_matches(&root.join(&remote_path), &file.md5).await,
+            // Packed psarc files are checked against their md5 once installed
+            ModFileType::Psarc => root.join(&remote_path).is_dir(),
+        };
+        if !valid {
+            errors.push(format!("missing or modified {}", remote_path));
+        }
+    }
+
+    let patch_path = &metadata.base.patch_remote_path;
+    if !file_matches(&root.join(patch_path), &metadata.base.patch_md5).await {
+        errors.push(format!("missing or modified {}", patch_path));
+    }
+
+    for (relative_path, md5) in bundle.checksums.iter() {
+        if !file_matches(&root.join(relative_path), md5).await {
+            errors.push(format!("missing or modified {}", relative_path));
+        }
+    }
+
+    errors
+}
+
+// The md5s inside a bundle only prove it wasn't damaged since the export,
+// its mod files and patch also have to be the ones published in the current metadata.json
+fn compare_with_published(bundle: &Metadata, published: &Metadata, game_id: &str) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+
+    for file in published.files_for(game_id) {
+        match bundle
+            .files_for(game_id)
+            .find(|bundled| bundled.name == file.name)
+        {
+            Some(bundled) if bundled.md5 == file.md5 => {}
+            Some(_) => errors.push(format!("{} differs from the published version", file.name)),
+            None => errors.push(format!("{} is missing from the bundle", file.name)),
+        }
+    }
+    for file in bundle.files_for(game_id) {
+        if !published
+            .files_for(game_id)
+            .any(|published_file| published_file.name == file.name)
+        {
+            errors.push(format!("{} is not a published file", file.name));
+        }
+    }
+    if bundle.base.patch_md5 != published.base.patch_md5 {
+        errors.push("the patch differs from the published version".to_string());
+    }
+
+    errors
+}
+
+// One folder per import, removing it can't touch the files of another import
+fn bundle_directory(app: &AppHandle) -> PathBuf {
+    let name = format!(
+        "moddedboost_bundle_{}_{}_{}",
+        std::process::id(),
+        chrono::Utc::now().timestamp_millis(),
+        IMPORT_COUNTER.fetch_add(1, Ordering::Relaxed)
+    );
+    app.path()
+        .resolve(name, BaseDirectory::Temp)
+        .expect("failed to resolve resource")
+}
+
+// Installs a bundle through the same steps as an online update, with the extracted bundle as the source
+#[tauri::command]
+pub async fn import_mod_bundle(
+    app: AppHandle,
+    archive_path: &str,
+    rpcs3_directory: &str,
+    game_id: &str,
+    beta: bool,
+) -> Result<InstallReport> {
+    let published = load_metadata(&app, beta).map_err(Error::InvalidBundle)?;
+    let archive_path = PathBuf::from(archive_path);
+    let root = bundle_directory(&app);
+
+    println!(
+        "Extracting {} to {}",
+        archive_path.display(),
+        root.display()
+    );
+    let extract_root = root.clone();
+    let extracted =
+        tauri::async_runtime::spawn_blocking(move || extract_bundle(&archive_path, &extract_root))
+            .await
+            .map_err(|err| Error::InvalidBundle(err.to_string()))
+            .and_then(|result| result);
+
+    let result = match extracted {
+        Ok(()) => {
+            let rpcs3_directory = Rpcs3Directory::at(Path::new(rpcs3_directory));
+            install_bundle(&app, &root, &rpcs3_directory, game_id, &published).await
+        }
+        Err(err) => Err(err),
+    };
+
+    let _ = fs::remove_dir_all(&root);
+    result
+}
+
+async fn install_bundle(
+    app: &AppHandle,
+    root: &Path,
+    rpcs3_directory: &Rpcs3Directory,
+    game_id: &str,
+    published: &Metadata,
+) -> Result<InstallReport> {
+    let contents = fs::read_to_string(root.join(BUNDLE_METADATA))
+        .map_err(|_| Error::InvalidBundle(format!("missing {}", BUNDLE_METADATA)))?;
+    let bundle: BundleMetadata = serde_json::from_str(&contents)
+        .map_err(|err| Error::InvalidBundle(format!("{}: {}", BUNDLE_METADATA, err)))?;
+
+    let mut errors = compare_with_published(&bundle.metadata, published, game_id);
+    errors.extend(validate_bundle(root, &bundle, game_id).await);
+    if !errors.is_empty() {
+        return Err(Error::InvalidBundle(errors.join(", ")));
+    }
+
+    Ok(install_mod(
+        app,
+        &InstallSource::local(root),
+        rpcs3_directory,
+        game_id,
+        &bundle.metadata,
+        "import_bundle",
+    )
+    .await)
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportProgress {
+    pub processed_bytes: u64,
+    pub total_bytes: u64,
+    pub current_file: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportReport {
+    pub output_path: String,
+    pub mod_version: String,
+    pub file_count: usize,
+    pub total_bytes: u64,
+}
+
+struct ExportFile {
+    archive_path: String,
+    local_path: PathBuf,
+    size: u64,
+}
+
+// Checksums are computed while the file is written, so every file is only read once
+struct HashingReader<R> {
+    inner: R,
+    context: md5::Context,
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.context.consume(&buf[..read]);
+        Ok(read)
+    }
+}
+
+enum ArchiveWriter {
+    Zip(zip::ZipWriter<File>),
+    TarZst(tar::Builder<zstd::stream::AutoFinishEncoder<'static, File>>),
+}
+
+impl ArchiveWriter {
+    fn create(output_path: &Path, format: ArchiveFormat) -> Result<ArchiveWriter> {
+        let file = File::create(output_path)?;
+
+        Ok(match format {
+            ArchiveFormat::Zip => ArchiveWriter::Zip(zip::ZipWriter::new(file)),
+            ArchiveFormat::TarZst => {
+                let encoder = zstd::Encoder::new(file, 0)?.auto_finish();
+                ArchiveWriter::TarZst(tar::Builder::new(encoder))
+            }
+        })
+    }
+
+    fn append(&mut self, archive_path: &str, size: u64, reader: &mut impl Read) -> Result<()> {
+        match self {
+            ArchiveWriter::Zip(writer) => {
+                let options = zip::write::FileOptions::default()
+                    .compression_method(zip::CompressionMethod::Deflated)
+                    .large_file(size >= u32::MAX as u64);
+                writer.start_file(archive_path, options)?;
+                io::copy(reader, writer)?;
+            }
+            ArchiveWriter::TarZst(builder) => {
+                let mut header = tar::Header::new_gnu();
+                header.set_size(size);
+                header.set_mode(0o644);
+                header.set_cksum();
+                builder.append_data(&mut header, archive_path, reader)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> Result<()> {
+        match self {
+            ArchiveWriter::Zip(mut writer) => {
+                writer.finish()?.flush()?;
+            }
+            ArchiveWriter::TarZst(builder) => {
+                builder.into_inner()?.flush()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn collect_export_files(layout_files: Vec<(String, PathBuf, Vec<String>)>) -> Vec<ExportFile> {
+    let mut files: Vec<ExportFile> = Vec::new();
+
+    for (remote_path, local_path, exclude_paths) in layout_files {
+        if local_path.is_file() {
+            let size = fs::metadata(&local_path)
+                .map(|metadata| metadata.len())
+                .unwrap_or(0);
+            files.push(ExportFile {
+                archive_path: remote_path,
+                local_path,
+                size,
+            });
+            continue;
+        }
+
+        for entry in WalkDir::new(&local_path).into_iter().flatten() {
+            if !entry.file_type().is_file() {
+                continue;
+            }
+            let Ok(relative_path) = entry.path().strip_prefix(&local_path) else {
+                continue;
+            };
+            let relative_path = relative_path.to_string_lossy().replace('\\', "/");
+            if is_excluded(&exclude_paths, &format!("/{}", relative_path), false) {
+                continue;
+            }
+
+            files.push(ExportFile {
+                archive_path: format!("{}/{}", remote_path, relative_path),
+                local_path: entry.path().to_path_buf(),
+                size: entry.metadata().map(|metadata| metadata.len()).unwrap_or(0),
+            });
+        }
+    }
+
+    files
+}
+
+fn write_bundle(
+    output_path: &Path,
+    files: &[ExportFile],
+    metadata: Metadata,
+    on_progress: impl Fn(ExportProgress),
+) -> Result<usize> {
+    let total_bytes: u64 = files.iter().map(|file| file.size).sum();
+    let mut processed_bytes: u64 = 0;
+    let mut checksums: BTreeMap<String, String> = BTreeMap::new();
+
+    // Written next to the destination first, a failed export never leaves a truncated bundle behind
+    let temp_path = output_path.with_file_name(format!(
+        "{}.partial",
+        output_path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+    ));
+    let mut writer = ArchiveWriter::create(&temp_path, ArchiveFormat::from_path(output_path)?)?;
+
+    for file in files {
+        on_progress(ExportProgress {
+            processed_bytes,
+            total_bytes,
+            current_file: file.archive_path.clone(),
+        });
+
+        let mut reader = HashingReader {
+            inner: File::open(&file.local_path)?,
+            context: md5::Context::new(),
+        };
+        writer.append(&file.archive_path, file.size, &mut reader)?;
+        checksums.insert(
+            file.archive_path.clone(),
+            format!("{:x}", reader.context.compute()),
+        );
+        processed_bytes += file.size;
+    }
+
+    let bundle_metadata = BundleMetadata {
+        metadata,
+        checksums,
+    };
+    let metadata_json = serde_json::to_vec_pretty(&bundle_metadata)
+        .map_err(|err| Error::InvalidInstallation(err.to_string()))?;
+    writer.append(
+        BUNDLE_METADATA,
+        metadata_json.len() as u64,
+        &mut metadata_json.as_slice(),
+    )?;
+    writer.finish()?;
+
+    fs::rename(&temp_path, output_path)?;
+    on_progress(ExportProgress {
+        processed_bytes: total_bytes,
+        total_bytes,
+        current_file: String::new(),
+    });
+
+    Ok(files.len())
+}
+
+// Packs the installation of one game version into a bundle that import_mod_bundle accepts.
+// Only installations that match the metadata are exported, the receiver gets a verified copy.
+#[tauri::command]
+pub async fn export_mod_bundle(
+    app: AppHandle,
+    rpcs3_directory: &str,
+    game_id: &str,
+    beta: bool,
+    output_path: &str,
+) -> Result<ExportReport> {
+    let rpcs3_directory = &Rpcs3Directory::at(Path::new(rpcs3_directory));
+    let output_path = PathBuf::from(output_path);
+    ArchiveFormat::from_path(&output_path)?;
+
+    let mut metadata = load_metadata(&app, beta).map_err(Error::InvalidInstallation)?;
+    // Pinned to the installed game version, the other one isn't part of the bundle
+    metadata
+        .mod_
+        .files
+        .retain(|file| file.versions.iter().any(|version| version == game_id));
+    if metadata.mod_.files.is_empty() {
+        return Err(Error::InvalidInstallation(format!(
+            "no files for {}",
+            game_id
+        )));
+    }
+
+    // The installed psarcs are several GB, the metadata cache only hashes the ones modified since the last check
+    let layout = installed_layout(rpcs3_directory, &metadata, game_id);
+    let psarc_paths: Vec<PathBuf> = metadata
+        .files_for(game_id)
+        .filter(|file| file.file_type == ModFileType::Psarc)
+        .map(|file| rpcs3_directory.join(replace_game_id(&file.path, game_id)))
+        .collect();
+    let checked_paths = layout
+        .iter()
+        .filter(|entry| entry.md5.is_some())
+        .map(|entry| &entry.local_path)
+        .chain(psarc_paths.iter())
+        .map(|path| path.display().to_string())
+        .collect();
+    let checksums = get_cached_checksums(&app, checked_paths).await;
+    let matches_checksum = |path: &Path, md5: &str| {
+        checksums
+            .get(&path.display().to_string())
+            .map(String::as_str)
+            == Some(md5)
+    };
+
+    let mut errors: Vec<String> = Vec::new();
+    let mut layout_files: Vec<(String, PathBuf, Vec<String>)> = Vec::new();
+    for entry in layout {
+        if !entry.local_path.exists() {
+            // dlc folders are optional, everything else is needed to install
+            let base_path = replace_game_id(&metadata.base.remote_path, game_id);
+            if entry.md5.is_some() || entry.remote_path == base_path {
+                errors.push(format!("{} is missing", entry.local_path.display()));
+            }
+            continue;
+        }
+        if let Some(md5) = &entry.md5 {
+            if !matches_checksum(&entry.local_path, md5) {
+                errors.push(format!("{} is outdated", entry.local_path.display()));
+                continue;
+            }
+        }
+        layout_files.push((entry.remote_path, entry.local_path, entry.exclude_paths));
+    }
+
+    // psarc sources are exported, so the packed psarc has to match for them to be the right ones
+    for file in metadata.files_for(game_id) {
+        if file.file_type != ModFileType::Psarc {
+            continue;
+        }
+        let path = rpcs3_directory.join(replace_game_id(&file.path, game_id));
+        if !matches_checksum(&path, &file.md5) {
+            errors.push(format!("{} is missing or outdated", path.display()));
+        }
+        if !psarc_source_directory(rpcs3_directory, file).is_dir() {
+            errors.push(format!("{} sources are missing", file.name));
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::InvalidInstallation(errors.join(", ")));
+    }
+
+    let mod_version = metadata.mod_.mod_version.clone();
+    let export_app = app.clone();
+    let export_path = output_path.clone();
+    let (file_count, total_bytes) = tauri::async_runtime::spawn_blocking(move || {
+        let files = collect_export_files(layout_files);
+        let total_bytes: u64 = files.iter().map(|file| file.size).sum();
+        write_bundle(&export_path, &files, metadata, |progress| {
+            let _ = export_app.emit("bundle_export", progress);
+        })
+        .map(|count| (count, total_bytes))
+    })
+    .await
+    .map_err(|err| Error::InvalidInstallation(err.to_string()))??;
+
+    Ok(ExportReport {
+        output_path: output_path.display().to_string(),
+        mod_version,
+        file_count,
+        total_bytes,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let directory =
+            std::env::temp_dir().join(format!("bundle_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        directory
+    }
+
+    fn test_metadata(eboot_md5: &str, patch_md5: &str) -> Metadata {
+        serde_json::from_value(serde_json::json!({
+            "base": {
+                "path": "dev_hdd0/game/{GAME_ID}",
+                "remotePath": "base_folders/{GAME_ID}",
+                "dlcPath": "dev_hdd0/game/NPJB00512/USRDIR/dlc",
+                "dlcRemotePath": "base_folders/dlc",
+                "dlcNPJBRemoteBasePath": "base_folders/NPJB00512",
+                "dlcNPJBBasePath": "dev_hdd0/game/NPJB00512",
+                "patchPath": "patches/imported_patch.yml",
+                "patchRemotePath": "patches/imported_patch.yml",
+                "patchMd5": patch_md5,
+                "excludePaths": ["/USRDIR/EBOOT.BIN"]
+            },
+            "mod": {
+                "modVersion": "0.1.2",
+                "files": [{
+                    "versions": ["NPJB00512"],
+                    "name": "EBOOT",
+                    "path": "dev_hdd0/game/{GAME_ID}/USRDIR/EBOOT.BIN",
+                    "md5": eboot_md5,
+                    "remotePath": "patches/{GAME_ID}/EBOOT.BIN",
+                    "type": "file"
+                }]
+            }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn zip_entries_outside_the_destination_are_rejected() {
+        let directory = temp_directory("zip_slip");
+        let archive_path = directory.join("bundle.zip");
+        let mut writer = zip::ZipWriter::new(File::create(&archive_path).unwrap());
+        writer
+            .start_file("../escaped.txt", zip::write::FileOptions::default())
+            .unwrap();
+        writer.write_all(b"outside").unwrap();
+        writer.finish().unwrap();
+
+        let destination = directory.join("extracted");
+        assert!(matches!(
+            extract_bundle(&archive_path, &destination),
+            Err(Error::InvalidBundle(_))
+        ));
+        assert!(!directory.join("escaped.txt").exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn tar_entries_outside_the_destination_are_skipped() {
+        let directory = temp_directory("tar_slip");
+        let archive_path = directory.join("bundle.tar.zst");
+        let encoder = zstd::Encoder::new(File::create(&archive_path).unwrap(), 0)
+            .unwrap()
+            .auto_finish();
+        let mut builder = tar::Builder::new(encoder);
+        for (name, contents) in [("../escaped.txt", b"outside"), ("inside.txt", b"bundled")] {
+            // set_path refuses "..", the name is written into the header directly like a crafted archive would
+            let mut header = tar::Header::new_old();
+            header.as_old_mut().name[..name.len()].copy_from_slice(name.as_bytes());
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder.append(&header, &contents[..]).unwrap();
+        }
+        builder.into_inner().unwrap().flush().unwrap();
+
+        let destination = directory.join("extracted");
+        extract_bundle(&archive_path, &destination).unwrap();
+        assert!(!directory.join("escaped.txt").exists());
+        assert_eq!(
+            fs::read_to_string(destination.join("inside.txt")).unwrap(),
+            "bundled"
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn bundles_have_to_match_the_published_metadata() {
+        let published = test_metadata(
+            "0cd91049b84ffb65d82e35dc069073df",
+            "4b5356877c8707f2d059a8eb40c47b80",
+        );
+        assert!(compare_with_published(&published, &published, "NPJB00512").is_empty());
+
+        let modified = test_metadata(
+            "00000000000000000000000000000000",
+            "4b5356877c8707f2d059a8eb40c47b80",
+        );
+        assert_eq!(
+            compare_with_published(&modified, &published, "NPJB00512"),
+            ["EBOOT differs from the published version"]
+        );
+
+        let old_patch = test_metadata(
+            "0cd91049b84ffb65d82e35dc069073df",
+            "ffffffffffffffffffffffffffffffff",
+        );
+        assert_eq!(
+            compare_with_published(&old_patch, &published, "NPJB00512"),
+            ["the patch differs from the published version"]
+        );
+
+        let mut renamed = published.clone();
+        renamed.mod_.files[0].name = "EBOOT_OLD".to_string();
+        assert_eq!(
+            compare_with_published(&renamed, &published, "NPJB00512"),
+            [
+                "EBOOT is missing from the bundle",
+                "EBOOT_OLD is not a published file"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn exported_bundles_validate_after_extraction() {
+        let directory = temp_directory("round_trip");
+        let install = directory.join("install");
+        fs::create_dir_all(install.join("base/USRDIR")).unwrap();
+        fs::write(install.join("base/USRDIR/DATA.BIN"), "data").unwrap();
+        fs::write(install.join("base/USRDIR/EBOOT.BIN"), "original eboot").unwrap();
+        fs::write(install.join("EBOOT.BIN"), "modded eboot").unwrap();
+        fs::write(install.join("imported_patch.yml"), "patch").unwrap();
+
+        let metadata = test_metadata(
+            &format!("{:x}", md5::compute("modded eboot")),
+            &format!("{:x}", md5::compute("patch")),
+        );
+        let layout_files = vec![
+            (
+                "base_folders/NPJB00512".to_string(),
+                install.join("base"),
+                metadata.base.exclude_paths.clone(),
+            ),
+            (
+                "patches/NPJB00512/EBOOT.BIN".to_string(),
+                install.join("EBOOT.BIN"),
+                Vec::new(),
+            ),
+            (
+                "patches/imported_patch.yml".to_string(),
+                install.join("imported_patch.yml"),
+                Vec::new(),
+            ),
+        ];
+
+        for file_name in ["bundle.zip", "bundle.tar.zst"] {
+            let output_path = directory.join(file_name);
+            let files = collect_export_files(layout_files.clone());
+            let file_count = write_bundle(&output_path, &files, metadata.clone(), |_| {}).unwrap();
+            assert_eq!(file_count, 3, "{}", file_name);
+
+            let root = directory.join(format!("{}_extracted", file_name));
+            extract_bundle(&output_path, &root).unwrap();
+            let bundle: BundleMetadata =
+                serde_json::from_str(&fs::read_to_string(root.join(BUNDLE_METADATA)).unwrap())
+                    .unwrap();
+            assert_eq!(
+                bundle.checksums.keys().collect::<Vec<_>>(),
+                [
+                    "base_folders/NPJB00512/USRDIR/DATA.BIN",
+                    "patches/NPJB00512/EBOOT.BIN",
+                    "patches/imported_patch.yml"
+                ],
+                "{}",
+                file_name
+            );
+            assert!(validate_bundle(&root, &bundle, "NPJB00512")
+                .await
+                .is_empty());
+
+            fs::write(
+                root.join("base_folders/NPJB00512/USRDIR/DATA.BIN"),
+                "changed",
+            )
+            .unwrap();
+            fs::remove_file(root.join("patches/imported_patch.yml")).unwrap();
+            assert_eq!(
+                validate_bundle(&root, &bundle, "NPJB00512").await,
+                [
+                    "missing or modified patches/imported_patch.yml",
+                    "missing or modified base_folders/NPJB00512/USRDIR/DATA.BIN",
+                    "missing or modified patches/imported_patch.yml"
+                ],
+                "{}",
+                file_name
+            );
+            assert!(validate_bundle(&root, &bundle, "BLJS10250")
+                .await
+                .contains(&"no files for BLJS10250".to_string()));
+        }
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
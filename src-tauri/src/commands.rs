@@ -3,7 +3,7 @@ use tauri::AppHandle;
 use crate::file_metadata::{
     clear_cached_metadata, get_cached_metadata, get_file_modified_epoch, FileMetadata,
 };
-use crate::rclone::rclone;
+use crate::rclone::{rclone, Error as RcloneError, FilterRule, RcloneOptions, RcloneResult};
 
 #[tauri::command]
 pub async fn rclone_command(
@@ -12,18 +12,18 @@ pub async fn rclone_command(
     remote: &str,
     remote_path: &str,
     target_path: &str,
-    additional_flags: &str,
-    exclude_items: Vec<String>,
+    options: RcloneOptions,
+    filters: Vec<FilterRule>,
     listener_id: &str,
-) -> Result<bool, ()> {
+) -> Result<RcloneResult, RcloneError> {
     rclone(
         &app,
         command,
         remote,
         remote_path,
         target_path,
-        additional_flags,
-        exclude_items,
+        options,
+        filters,
         listener_id,
     )
     .await
@@ -1,16 +1,17 @@
 use std::collections::HashMap;
+use std::fs;
 use std::fs::File;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
 
 use relative_path::RelativePath;
-use tauri::path::BaseDirectory;
-use tauri::{App, AppHandle, Manager};
+use tauri::{App, AppHandle};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
 use crate::file_handler::get_file_system_entries;
-use crate::os::{get_os, OS};
+use crate::rpcs3_directory::{resolve_rpcs3_directory, Rpcs3Directory};
+use crate::tools::{ensure_tool, Tool};
 
 #[derive(Clone, serde::Serialize)]
 pub struct FullBoostVersions {
@@ -19,45 +20,35 @@ pub struct FullBoostVersions {
 }
 
 #[tauri::command]
-pub async fn check_game_versions(app: AppHandle, full_path: &str) -> Result<FullBoostVersions, ()> {
-    let mut fullboost_versions: FullBoostVersions = FullBoostVersions {
-        BLJS10250: false,
-        NPJB00512: false,
-    };
-
+pub async fn check_game_versions(
+    app: AppHandle,
+    full_path: &str,
+    rpcs3_directory_override: Option<String>,
+) -> Result<FullBoostVersions, ()> {
     // Don't need to continue parsing if the specified executable is invalid
     let path: &Path = Path::new(full_path);
     if !path.exists() {
         return Err(());
     }
 
-    let rpcs3_directory = match get_os() {
-        OS::Windows => match path.parent() {
-            Some(parent_path) => parent_path.to_str().unwrap().to_string(),
-            None => return Err(()),
-        },
-        OS::Linux => {
-            let home_dir = app
-                .path()
-                .home_dir()
-                .unwrap()
-                .as_path()
-                .display()
-                .to_string();
-            let config_rpcs3_relative_path = RelativePath::new(".config/rpcs3");
-            let path_buf = config_rpcs3_relative_path.to_path(&home_dir);
-            let path_str = path_buf.to_str().unwrap();
-            let path_string = path_str.to_string();
-            path_string
-        }
-        OS::Macos => String::from("~/Library/Application Support/rpcs3"),
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())
+            .map_err(|_| ())?;
+
+    Ok(find_game_versions(&app, &rpcs3_directory).await)
+}
+
+// Which Full Boost versions are installed in an rpcs3 data directory, dev_hdd0 and games can be relocated by vfs.yml
+pub async fn find_game_versions(
+    app: &AppHandle,
+    rpcs3_directory: &Rpcs3Directory,
+) -> FullBoostVersions {
+    let mut fullboost_versions: FullBoostVersions = FullBoostVersions {
+        BLJS10250: false,
+        NPJB00512: false,
     };
 
-    let dev_hdd0_game_relative_path = RelativePath::new("dev_hdd0/game");
-    let game_directory_string = dev_hdd0_game_relative_path
-        .to_path(&rpcs3_directory)
-        .display()
-        .to_string();
+    let game_directory_string = rpcs3_directory.join("dev_hdd0/game").display().to_string();
     let game_directory = &game_directory_string;
 
     // Check if NPJB00512 game version (Digital) exist
@@ -76,7 +67,7 @@ pub async fn check_game_versions(app: AppHandle, full_path: &str) -> Result<Full
     let mut sfo_exists = false;
     let mut eboot_exists = false;
     if let Some(_first_item) = npjb_sfo_paths.first() {
-        sfo_exists = check_sfo_title_id(&app, _first_item, "NPJB00512").await;
+        sfo_exists = check_sfo_title_id(app, _first_item, "NPJB00512").await;
     }
     if let Some(_first_item) = npjb_eboot_paths.first() {
         eboot_exists = true;
@@ -88,11 +79,7 @@ pub async fn check_game_versions(app: AppHandle, full_path: &str) -> Result<Full
 
     // Check if NPJB00512 game version (Disc) exist
     // BLJS could be installed in "dev_hdd0/disc" directory
-    let dev_hdd0_disc_relative_path = RelativePath::new("dev_hdd0/disc");
-    let disc_directory_string = dev_hdd0_disc_relative_path
-        .to_path(&rpcs3_directory)
-        .display()
-        .to_string();
+    let disc_directory_string = rpcs3_directory.join("dev_hdd0/disc").display().to_string();
     let disc_directory = &disc_directory_string;
     let dev_hdd0_disc_sfo_relative_path = RelativePath::new("param.sfo");
     let dev_hdd0_disc_sfo_directory = dev_hdd0_disc_sfo_relative_path
@@ -104,14 +91,30 @@ pub async fn check_game_versions(app: AppHandle, full_path: &str) -> Result<Full
 
     let mut bljs_exist = false;
     if let Some(_first_item) = disc_sfo_paths.first() {
-        bljs_exist = check_sfo_title_id(&app, _first_item, "BLJS10250").await;
+        bljs_exist = check_sfo_title_id(app, _first_item, "BLJS10250").await;
+    }
+
+    // Disc games can also be dumped into the games folder, which rpcs3 scans on start up
+    if !bljs_exist {
+        let games_directory = rpcs3_directory.vfs.games.display().to_string();
+        let games_sfo_paths =
+            get_file_system_entries(&games_directory, Some(&dev_hdd0_disc_sfo_directory));
+        for sfo_path in games_sfo_paths.iter() {
+            if check_sfo_title_id(app, sfo_path, "BLJS10250").await {
+                bljs_exist = true;
+                break;
+            }
+        }
     }
 
     // It might be possible the game was never installed (loaded from disc directly),
     // BLJS directory under "dev_hdd0/game/" or "dev_hdd0/disc" might not exist
     // rpcs3 uses games.yml to record down disc games like these
     if !bljs_exist {
-        let game_yml_paths = get_file_system_entries(&rpcs3_directory, Some(r"games.yml"));
+        let game_yml_paths = get_file_system_entries(
+            &rpcs3_directory.path.display().to_string(),
+            Some(r"games.yml"),
+        );
         if let Some(game_yaml_path) = game_yml_paths.first() {
             let game_yaml_path = game_yaml_path.clone();
 
@@ -123,14 +126,89 @@ pub async fn check_game_versions(app: AppHandle, full_path: &str) -> Result<Full
                 let games_config_sfo_paths =
                     get_file_system_entries(_value, Some(&dev_hdd0_disc_sfo_directory));
                 if let Some(_first_item) = games_config_sfo_paths.first() {
-                    bljs_exist = check_sfo_title_id(&app, _first_item, "BLJS10250").await;
+                    bljs_exist = check_sfo_title_id(app, _first_item, "BLJS10250").await;
                 }
             }
         }
     }
 
     fullboost_versions.BLJS10250 = bljs_exist;
-    Ok(fullboost_versions)
+    fullboost_versions
+}
+
+// Looks a path up below a directory one component at a time, ignoring case:
+// PKG installs and disc dumps don't agree on EBOOT.BIN vs eboot.bin.
+fn find_ignoring_case(directory: &Path, components: &[&str]) -> Option<PathBuf> {
+    let mut path = directory.to_path_buf();
+    for component in components {
+        let exact = path.join(component);
+        path = match exact.exists() {
+            true => exact,
+            false => fs::read_dir(&path)
+                .ok()?
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .find(|entry| {
+                    entry
+                        .file_name()
+                        .is_some_and(|name| name.to_string_lossy().eq_ignore_ascii_case(component))
+                })?,
+        };
+    }
+    Some(path)
+}
+
+// Installed games have USRDIR at the top, disc layouts keep it under PS3_GAME
+fn eboot_in(game_root: &Path) -> Option<PathBuf> {
+    find_ignoring_case(game_root, &["USRDIR", "EBOOT.BIN"])
+        .or_else(|| find_ignoring_case(game_root, &["PS3_GAME", "USRDIR", "EBOOT.BIN"]))
+}
+
+// rpcs3 records disc games booted from outside its directory in games.yml
+fn games_yml_entry(rpcs3_directory: &Rpcs3Directory, game_id: &str) -> Option<PathBuf> {
+    let games_yml = [
+        rpcs3_directory.path.join("config").join("games.yml"),
+        rpcs3_directory.path.join("games.yml"),
+    ]
+    .into_iter()
+    .find(|path| path.is_file())?;
+    let yaml_map: HashMap<String, String> =
+        serde_yaml::from_str(&fs::read_to_string(games_yml).ok()?).ok()?;
+    yaml_map.get(game_id).map(PathBuf::from)
+}
+
+// The EBOOT.BIN rpcs3 would boot for a title id, in the same places find_game_versions looks
+pub async fn find_eboot(
+    app: &AppHandle,
+    rpcs3_directory: &Rpcs3Directory,
+    game_id: &str,
+) -> Option<PathBuf> {
+    for relative_path in ["dev_hdd0/game", "dev_hdd0/disc"] {
+        let eboot = find_ignoring_case(&rpcs3_directory.join(relative_path), &[game_id])
+            .and_then(|game_root| eboot_in(&game_root));
+        if eboot.is_some() {
+            return eboot;
+        }
+    }
+
+    if let Some(eboot) =
+        games_yml_entry(rpcs3_directory, game_id).and_then(|game_root| eboot_in(&game_root))
+    {
+        return Some(eboot);
+    }
+
+    // Dumps in the games folder are named by whoever made them, only PARAM.SFO tells which game it is
+    let entries = fs::read_dir(&rpcs3_directory.vfs.games).ok()?;
+    for game_root in entries.filter_map(|entry| entry.ok().map(|entry| entry.path())) {
+        let Some(sfo_path) = find_ignoring_case(&game_root, &["PS3_GAME", "PARAM.SFO"]) else {
+            continue;
+        };
+        if check_sfo_title_id(app, &sfo_path.display().to_string(), game_id).await {
+            if let Some(eboot) = eboot_in(&game_root) {
+                return Some(eboot);
+            }
+        }
+    }
+    None
 }
 
 #[tauri::command]
@@ -140,26 +218,14 @@ pub async fn check_path_exist(full_path: &str) -> Result<(bool), ()> {
 }
 
 async fn check_sfo_title_id(app: &AppHandle, sfo_path: &str, match_str: &str) -> bool {
-    let sfo_name = match get_os() {
-        OS::Windows => "sfo.exe",
-        OS::Linux => "sfo",
-        OS::Macos => panic!("MacOs is not supported!"),
+    let executable_path = match ensure_tool(app, Tool::Sfo).await {
+        Ok(path) => path,
+        Err(error) => {
+            println!("{}", error);
+            return false;
+        }
     };
 
-    let executable_path = app
-        .path()
-        .resolve(format!("tools/sfo/{}", sfo_name), BaseDirectory::AppData)
-        .expect("failed to resolve resource");
-
-    if get_os() == OS::Linux {
-        async_process::Command::new("chmod")
-            .arg("+x")
-            .arg(&executable_path)
-            .output()
-            .await
-            .expect("chmod failed!");
-    }
-
     let mut cmd = Command::new(executable_path);
 
     cmd.arg("--query")
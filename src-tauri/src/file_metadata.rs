@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
@@ -11,8 +12,8 @@ use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
 
 #[derive(Clone, serde::Serialize, serde::Deserialize)]
 pub struct FileMetadata {
-    path: String,
-    checksum: String,
+    pub path: String,
+    pub checksum: String,
     last_modified: u64,
 }
 
@@ -108,6 +109,19 @@ pub async fn get_cached_metadata(
     Ok(result)
 }
 
+// md5 of each existing file keyed by path, only files modified since they were last cached are hashed
+pub async fn get_cached_checksums(
+    app: &AppHandle,
+    file_paths: Vec<String>,
+) -> HashMap<String, String> {
+    get_cached_metadata(app, file_paths, false)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file_metadata| (file_metadata.path, file_metadata.checksum))
+        .collect()
+}
+
 pub async fn get_file_modified_epoch(full_path: &str) -> Result<(u64), ()> {
     let path: &Path = Path::new(full_path);
     if !path.exists() {
@@ -128,7 +142,15 @@ pub async fn get_file_modified_epoch(full_path: &str) -> Result<(u64), ()> {
     }
 }
 
-async fn get_checksum(file_path: &str) -> String {
+// Hashed on a blocking thread, installed files go up to several GB and would stall the async runtime
+pub async fn get_checksum(file_path: &str) -> String {
+    let file_path = file_path.to_string();
+    tauri::async_runtime::spawn_blocking(move || checksum(&file_path))
+        .await
+        .expect("checksum task failed")
+}
+
+fn checksum(file_path: &str) -> String {
     let path: &Path = Path::new(&file_path);
     if !path.exists() {
         panic!("File not found!");
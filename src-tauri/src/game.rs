@@ -1,74 +1,308 @@
-use async_process::Command;
+use std::process::Stdio;
+use std::time::Instant;
 
-use crate::file_handler::{get_file_system_entries, get_rpcs3_os};
-use crate::os::OS;
+use serde::{ser::Serializer, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
+use tokio::sync::Mutex;
 
-#[tauri::command]
-pub fn auto_find_path_and_run_game(full_path: &str, game_type: &str) {
-    //find the game path first
-    //if gameType is npjb then npjb00512, else bljs10250
-    let game_type_path: &str = match game_type {
-        "npjb" => r"NPJB00512\USRDIR\EBOOT.bin",
-        "bljs" => r"BLJS10250\USRDIR\EBOOT.bin",
-        _ => "",
-    };
+use crate::file_check::find_eboot;
+use crate::launch_profiles::find_profile;
+use crate::os::{get_os, OS};
+use crate::rpcs3_directory::resolve_rpcs3_directory;
 
-    // find by game_type_path
-    // 1. we don't need the rpcs3.exe in path, so we need remove it
-    let find_eboot_path_string: &String = &full_path.replace("rpcs3.exe", "");
+const STARTED_EVENT: &str = "game://started";
+const EXITED_EVENT: &str = "game://exited";
+const OUTPUT_EVENT: &str = "game://output";
 
-    // eg. C:\rpcs3\rpcs3.exe => C:\rpcs3
-    let find_eboot_path = get_file_system_entries(find_eboot_path_string, Some(game_type_path));
-    // change to &str
-    let eboot_path: &str = match find_eboot_path.first() {
-        Some(path) => path,
-        None => "",
-    };
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Profile(#[from] crate::launch_profiles::Error),
+    #[error("{0} is already running, session {1}")]
+    AlreadyRunning(String, String),
+    #[error(transparent)]
+    Directory(#[from] crate::rpcs3_directory::Error),
+    #[error("EBOOT.BIN of {0} was not found")]
+    EbootNotFound(String),
+    #[error("launching games is not supported on this OS")]
+    UnsupportedOs,
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameSession {
+    pub session_id: String,
+    pub game_id: String,
+    pub executable_path: String,
+    pub profile_id: String,
+    pub pid: Option<u32>,
+    pub started_at: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameExited {
+    pub session_id: String,
+    pub game_id: String,
+    // None when rpcs3 was killed by a signal
+    pub exit_code: Option<i32>,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Copy, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GameOutput {
+    pub session_id: String,
+    pub stream: OutputStream,
+    pub line: String,
+}
+
+// Managed in app state, only one game runs at a time
+#[derive(Default)]
+pub struct GameSessionState(Mutex<Option<GameSession>>);
+
+// rpcs3 doesn't always write valid UTF-8, a bad line shouldn't end the stream
+fn stream_output<R>(
+    app: AppHandle,
+    session_id: String,
+    stream: OutputStream,
+    reader: R,
+) -> tauri::async_runtime::JoinHandle<()>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        let mut reader = BufReader::new(reader);
+        let mut buffer: Vec<u8> = Vec::new();
+        while let Ok(read) = reader.read_until(b'\n', &mut buffer).await {
+            if read == 0 {
+                break;
+            }
+            let line = String::from_utf8_lossy(&buffer).trim_end().to_string();
+            buffer.clear();
+            let _ = app.emit(
+                OUTPUT_EVENT,
+                GameOutput {
+                    session_id: session_id.clone(),
+                    stream,
+                    line,
+                },
+            );
+        }
+    })
+}
+
+// Waits for rpcs3 on its own task, the session is cleared before game://exited so the
+// frontend can launch again straight from the event.
+fn supervise(app: AppHandle, session: GameSession, mut child: Child, started: Instant) {
+    let stdout = child.stdout.take().map(|stdout| {
+        stream_output(
+            app.clone(),
+            session.session_id.clone(),
+            OutputStream::Stdout,
+            stdout,
+        )
+    });
+    let stderr = child.stderr.take().map(|stderr| {
+        stream_output(
+            app.clone(),
+            session.session_id.clone(),
+            OutputStream::Stderr,
+            stderr,
+        )
+    });
 
-    println!("{}", full_path);
+    tauri::async_runtime::spawn(async move {
+        let status = child.wait().await;
+        for task in [stdout, stderr].into_iter().flatten() {
+            let _ = task.await;
+        }
 
-    // if eboot_path is empty then return
-    if eboot_path.is_empty() {
-        return;
+        let state = app.state::<GameSessionState>();
+        let mut current = state.0.lock().await;
+        if current
+            .as_ref()
+            .is_some_and(|current| current.session_id == session.session_id)
+        {
+            *current = None;
+        }
+        drop(current);
+
+        let (exit_code, success, error) = match status {
+            Ok(status) => (status.code(), status.success(), None),
+            Err(err) => (None, false, Some(err.to_string())),
+        };
+        println!(
+            "Game session {} exited with {:?}",
+            session.session_id, exit_code
+        );
+        let _ = app.emit(
+            EXITED_EVENT,
+            GameExited {
+                session_id: session.session_id,
+                game_id: session.game_id,
+                exit_code,
+                success,
+                duration_ms: started.elapsed().as_millis() as u64,
+                error,
+            },
+        );
+    });
+}
+
+// Output is only captured when the launcher stays open, a closed pipe would take rpcs3 down with it
+async fn start_session(
+    app: &AppHandle,
+    full_path: &str,
+    game_id: &str,
+    profile_id: Option<&str>,
+    game_arg: String,
+    capture_output: bool,
+) -> Result<GameSession> {
+    let state = app.state::<GameSessionState>();
+    let mut current = state.0.lock().await;
+    if let Some(current) = current.as_ref() {
+        return Err(Error::AlreadyRunning(
+            current.game_id.clone(),
+            current.session_id.clone(),
+        ));
+    }
+
+    let profile = find_profile(app, profile_id)?;
+
+    match get_os() {
+        OS::Windows => {}
+        OS::Linux => {
+            Command::new("chmod")
+                .arg("+x")
+                .arg(full_path)
+                .output()
+                .await?;
+        }
+        _ => {
+            println!("Unsupported OS type");
+            return Err(Error::UnsupportedOs);
+        }
     }
-    //create new command to execute the game
-    let _command = Command::new("cmd")
-        .args(["/C", full_path, eboot_path])
-        .spawn()
-        .expect("failed to execute process");
-
-    // exit the app
-    std::process::exit(0);
+
+    let output = || match capture_output {
+        true => Stdio::piped(),
+        false => Stdio::null(),
+    };
+    let argv = profile.command_line(full_path, game_id, &[game_arg]);
+    println!("Launching {}", argv.join(" "));
+    let child = Command::new(&argv[0])
+        .args(&argv[1..])
+        .envs(&profile.env)
+        .stdin(Stdio::null())
+        .stdout(output())
+        .stderr(output())
+        .spawn()?;
+
+    let started_at = chrono::Utc::now();
+    let session = GameSession {
+        session_id: format!("{}-{}", game_id, started_at.timestamp_millis()),
+        game_id: game_id.to_string(),
+        executable_path: full_path.to_string(),
+        profile_id: profile.id.clone(),
+        pid: child.id(),
+        started_at: started_at.to_rfc3339(),
+    };
+    println!(
+        "Game session {} started with pid {:?}",
+        session.session_id, session.pid
+    );
+    *current = Some(session.clone());
+    drop(current);
+
+    let _ = app.emit(STARTED_EVENT, session.clone());
+    supervise(app.clone(), session.clone(), child, Instant::now());
+    Ok(session)
 }
 
+// Returns as soon as rpcs3 is started, game://started, game://output and game://exited follow
 #[tauri::command]
-pub async fn launch_game(full_path: &str, game_type: &str) -> Result<(), ()> {
-    if let Ok(OS::Windows) = get_rpcs3_os(full_path) {
-        let _game_output = Command::new(full_path)
-            .arg("--no-gui")
-            .arg(format!("%RPCS3_GAMEID%:{}", game_type))
-            .output()
-            .await
-            .unwrap_or_else(|_| panic!("Failed to launch game!"));
-    } else if let Ok(OS::Linux) = get_rpcs3_os(full_path) {
-        let _chmod_output = Command::new("chmod")
-            .arg("+x")
-            .arg(full_path)
-            .output()
-            .await
-            .unwrap_or_else(|_| panic!("Failed to turn AppImage into executable!"));
-
-        let _game_output = Command::new(full_path)
-            .arg("--no-gui")
-            .arg(format!("%RPCS3_GAMEID%:{}", game_type))
-            .output()
-            .await
-            .unwrap_or_else(|_| panic!("Failed to launch game!"));
-    } else if let Ok(OS::Macos) = get_rpcs3_os(full_path) {
-        println!("macOS is not supported")
-    } else {
-        println!("Unsupported OS type")
+pub async fn launch_game(
+    app: AppHandle,
+    full_path: &str,
+    game_type: &str,
+    profile_id: Option<String>,
+) -> Result<GameSession> {
+    start_session(
+        &app,
+        full_path,
+        game_type,
+        profile_id.as_deref(),
+        format!("%RPCS3_GAMEID%:{}", game_type),
+        true,
+    )
+    .await
+}
+
+// Boots the EBOOT.BIN directly instead of going through %RPCS3_GAMEID%, the launcher can close
+// itself right after, rpcs3 keeps running on its own.
+#[tauri::command]
+pub async fn launch_eboot(
+    app: AppHandle,
+    full_path: &str,
+    game_id: &str,
+    rpcs3_directory_override: Option<String>,
+    profile_id: Option<String>,
+    exit_launcher: bool,
+) -> Result<GameSession> {
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())?;
+    let eboot_path = find_eboot(&app, &rpcs3_directory, game_id)
+        .await
+        .ok_or(Error::EbootNotFound(game_id.to_string()))?;
+
+    let session = start_session(
+        &app,
+        full_path,
+        game_id,
+        profile_id.as_deref(),
+        eboot_path.display().to_string(),
+        !exit_launcher,
+    )
+    .await?;
+
+    if exit_launcher {
+        app.exit(0);
     }
+    Ok(session)
+}
 
-    Ok(())
+pub async fn current_session(app: &AppHandle) -> Option<GameSession> {
+    let state = app.state::<GameSessionState>();
+    let current = state.0.lock().await;
+    current.clone()
+}
+
+#[tauri::command]
+pub async fn get_game_session(app: AppHandle) -> Option<GameSession> {
+    current_session(&app).await
 }
@@ -3,40 +3,27 @@ use std::fs::{create_dir_all, metadata};
 use std::path::Path;
 
 use relative_path::RelativePath;
-use tauri::{App, AppHandle, Manager};
+use tauri::{App, AppHandle};
 
 use crate::file_handler::get_file_system_entries;
-use crate::os::{get_os, OS};
 use crate::psarc::unpack_psarc;
-use crate::rpcs3::validate_rpcs3_executable;
+use crate::rpcs3::{validate_rpcs3, ValidationStatus};
+use crate::rpcs3_directory::resolve_rpcs3_directory;
 
 #[tauri::command]
-pub async fn check_initialized(app: AppHandle, full_path: &str) -> Result<bool, ()> {
-    let rpcs3_directory = match get_os() {
-        OS::Windows => Path::new(full_path).parent().unwrap().to_path_buf(),
-        OS::Linux => {
-            let config_path = RelativePath::new(".config/rpcs3");
-            let app_path = &app.path()
-                .home_dir()
-                .unwrap()
-                .as_path()
-                .display()
-                .to_string();
-
-            let temp_path = config_path.to_path(&app_path);
-            temp_path.to_path_buf() // Clone the PathBuf here
-        },
-        OS::Macos => panic!("Not supported"),
-    };
-
-    let dev_hdd0_relative_path = RelativePath::new("dev_hdd0");
-    let game_directory_string = dev_hdd0_relative_path
-        .to_path(&rpcs3_directory)
-        .display()
-        .to_string();
+pub async fn check_initialized(
+    app: AppHandle,
+    full_path: &str,
+    rpcs3_directory_override: Option<String>,
+) -> Result<bool, ()> {
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())
+            .map_err(|_| ())?;
+
+    let game_directory_string = rpcs3_directory.vfs.dev_hdd0.display().to_string();
     let game_directory = &game_directory_string;
 
-    let cache_path_str = rpcs3_directory.join(".moddedboost").join("psarc");
+    let cache_path_str = rpcs3_directory.path.join(".moddedboost").join("psarc");
 
     let required_folders = vec![
         "patch_01_00",
@@ -75,42 +62,30 @@ pub async fn check_initialized(app: AppHandle, full_path: &str) -> Result<bool,
 }
 
 #[tauri::command]
-pub async fn initialize(app: AppHandle, rpcs3_executable: &str) -> Result<(), ()> {
-    let valid_rpcs3 = validate_rpcs3_executable(rpcs3_executable).await.unwrap();
-
-    if !valid_rpcs3 {
+pub async fn initialize(
+    app: AppHandle,
+    rpcs3_executable: &str,
+    rpcs3_directory_override: Option<String>,
+) -> Result<(), ()> {
+    // The minimum version is enforced before launching, an older build can still be set up
+    let validation = validate_rpcs3(rpcs3_executable, None).await;
+
+    if !validation.valid && validation.status != ValidationStatus::Outdated {
         return Err(());
     }
 
-    let rpcs3_directory = match get_os() {
-        OS::Windows => Path::new(rpcs3_executable).parent().unwrap().to_path_buf(),
-        OS::Linux => {
-            let config_path = RelativePath::new(".config/rpcs3");
-            let app_path = &app.path()
-                .home_dir()
-                .unwrap()
-                .as_path()
-                .display()
-                .to_string();
-
-            let temp_path = config_path.to_path(&app_path);
-            temp_path.to_path_buf() // Clone the PathBuf here
-        },
-        OS::Macos => panic!("Not supported"),
-    };
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, rpcs3_executable, rpcs3_directory_override.as_deref())
+            .map_err(|_| ())?;
     
     // Path to the cache folder is the ".moddedboost" folder.
-    let cache_path_str = rpcs3_directory.join(".moddedboost").join("psarc");
+    let cache_path_str = rpcs3_directory.path.join(".moddedboost").join("psarc");
     let cache_path = Path::new(&cache_path_str);
 
     create_dir_all(cache_path).unwrap();
 
     // Check if there are existing game versions in the game, if yes automatically do migrations.
-    let dev_hdd0_relative_path = RelativePath::new("dev_hdd0");
-    let game_directory_string = dev_hdd0_relative_path
-        .to_path(&rpcs3_directory)
-        .display()
-        .to_string();
+    let game_directory_string = rpcs3_directory.vfs.dev_hdd0.display().to_string();
     let game_directory = &game_directory_string;
 
     let files_to_extract = vec![
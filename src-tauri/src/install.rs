@@ -0,0 +1,254 @@
+use std::path::Path;
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager};
+
+use crate::file_metadata::get_checksum;
+use crate::metadata::{psarc_source_directory, replace_game_id, Metadata, ModFileType};
+use crate::patches::activate_patch;
+use crate::psarc::pack_psarc_command;
+use crate::rclone::{rclone, FilterRule, RcloneOptions};
+use crate::rpcs3_directory::Rpcs3Directory;
+
+// Sources on disk are passed to rclone as plain paths, any name works as long as it isn't in rclone.conf
+pub const LOCAL_REMOTE: &str = "local";
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallStep {
+    pub name: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstallReport {
+    pub success: bool,
+    pub mod_version: String,
+    pub game_id: String,
+    pub steps: Vec<InstallStep>,
+    // Installed mod files whose md5 doesn't match the metadata afterwards
+    pub mismatched_files: Vec<String>,
+}
+
+// Where the remote layout (base_folders, patches, psarc, ...) is read from,
+// "{remote}:" for a mirror or a directory for an extracted bundle.
+pub struct InstallSource {
+    pub remote: String,
+    pub root: String,
+}
+
+impl InstallSource {
+    pub fn local(root: &Path) -> InstallSource {
+        InstallSource {
+            remote: LOCAL_REMOTE.to_string(),
+            root: root.display().to_string(),
+        }
+    }
+
+    fn path(&self, remote_path: &str) -> String {
+        format!("{}/{}", self.root.trim_end_matches('/'), remote_path)
+    }
+
+    fn is_local(&self) -> bool {
+        self.remote == LOCAL_REMOTE
+    }
+}
+
+struct Installer<'a> {
+    app: &'a AppHandle,
+    source: &'a InstallSource,
+    listener_id: &'a str,
+    steps: Vec<InstallStep>,
+}
+
+impl Installer<'_> {
+    fn record(&mut self, name: String, result: Result<(), String>) -> bool {
+        let _ = self
+            .app
+            .emit(&format!("install_{}", self.listener_id), &name);
+        let success = result.is_ok();
+        self.steps.push(InstallStep {
+            name,
+            success,
+            error: result.err(),
+        });
+        success
+    }
+
+    async fn run_rclone(
+        &mut self,
+        name: String,
+        command: &str,
+        remote_path: &str,
+        target_path: &Path,
+        options: RcloneOptions,
+        filters: Vec<FilterRule>,
+    ) -> bool {
+        // Bundles don't have to ship the optional folders (e.g. no dlc), mirrors always do
+        let source_path = self.source.path(remote_path);
+        if self.source.is_local() && !Path::new(&source_path).exists() {
+            return true;
+        }
+
+        let result = rclone(
+            self.app,
+            command,
+            &self.source.remote,
+            &source_path,
+            &target_path.display().to_string(),
+            options,
+            filters,
+            self.listener_id,
+        )
+        .await;
+
+        let result = match result {
+            Ok(result) if result.success => Ok(()),
+            Ok(result) => Err(result.stderr_tail.last().cloned().unwrap_or_default()),
+            Err(err) => Err(err.to_string()),
+        };
+        self.record(name, result)
+    }
+}
+
+// Backend version of the update in game-tabs.tsx and update.ts: base folders, dlc, mod files,
+// psarc packing and patch activation, in that order.
+pub async fn install_mod(
+    app: &AppHandle,
+    source: &InstallSource,
+    rpcs3_directory: &Rpcs3Directory,
+    game_id: &str,
+    metadata: &Metadata,
+    listener_id: &str,
+) -> InstallReport {
+    let mut installer = Installer {
+        app,
+        source,
+        listener_id,
+        steps: Vec::new(),
+    };
+    let base = &metadata.base;
+    let excludes = FilterRule::excludes(&base.exclude_paths);
+
+    installer
+        .run_rclone(
+            "base folder".to_string(),
+            "sync",
+            &replace_game_id(&base.remote_path, game_id),
+            &rpcs3_directory.join(replace_game_id(&base.path, game_id)),
+            RcloneOptions::sync_ignore_size(),
+            excludes.clone(),
+        )
+        .await;
+    installer
+        .run_rclone(
+            "dlc folder".to_string(),
+            "sync",
+            &base.dlc_remote_path,
+            &rpcs3_directory.join(replace_game_id(&base.dlc_path, game_id)),
+            RcloneOptions::sync_ignore_size(),
+            excludes.clone(),
+        )
+        .await;
+    // For BLJS the base folder of NPJB00512 is synced as well so that DLC works
+    if game_id == "BLJS10250" {
+        installer
+            .run_rclone(
+                "NPJB00512 base folder".to_string(),
+                "sync",
+                &base.dlc_npjb_remote_base_path,
+                &rpcs3_directory.join(&base.dlc_npjb_base_path),
+                RcloneOptions::sync_ignore_size(),
+                excludes.clone(),
+            )
+            .await;
+    }
+
+    let mut mismatched_files: Vec<String> = Vec::new();
+    for file in metadata.files_for(game_id) {
+        let remote_path = replace_game_id(&file.remote_path, game_id);
+        let path = rpcs3_directory.join(replace_game_id(&file.path, game_id));
+
+        let installed = match file.file_type {
+            ModFileType::File => {
+                installer
+                    .run_rclone(
+                        file.name.clone(),
+                        "copyto",
+                        &remote_path,
+                        &path,
+                        RcloneOptions::copy(),
+                        Vec::new(),
+                    )
+                    .await
+            }
+            ModFileType::Psarc => {
+                let source_directory = psarc_source_directory(rpcs3_directory, file);
+                let synced = installer
+                    .run_rclone(
+                        format!("{} sources", file.name),
+                        "sync",
+                        &remote_path,
+                        &source_directory,
+                        RcloneOptions::sync(),
+                        Vec::new(),
+                    )
+                    .await;
+
+                let destination_directory = path.parent().unwrap_or(&rpcs3_directory.path);
+                synced
+                    && installer.record(
+                        file.name.clone(),
+                        pack_psarc_command(
+                            app.clone(),
+                            &source_directory.display().to_string(),
+                            &file.name,
+                            &destination_directory.display().to_string(),
+                        )
+                        .await
+                        .map_err(|_| "failed to pack psarc".to_string()),
+                    )
+            }
+        };
+
+        if installed
+            && (!path.exists() || get_checksum(&path.display().to_string()).await != file.md5)
+        {
+            mismatched_files.push(file.name.clone());
+        }
+    }
+
+    let patch_path = rpcs3_directory.join(replace_game_id(&base.patch_path, game_id));
+    let patch_copied = installer
+        .run_rclone(
+            "patch".to_string(),
+            "copyto",
+            &base.patch_remote_path,
+            &patch_path,
+            RcloneOptions::copy(),
+            Vec::new(),
+        )
+        .await;
+    if patch_copied {
+        let patch_config_path = rpcs3_directory.patch_config_path();
+        let activated = activate_patch(&patch_config_path.display().to_string()).await;
+        installer.record(
+            "patch activation".to_string(),
+            match activated {
+                Ok(true) => Ok(()),
+                _ => Err("failed to activate the patch in patch_config.yml".to_string()),
+            },
+        );
+    }
+
+    let success = installer.steps.iter().all(|step| step.success) && mismatched_files.is_empty();
+    InstallReport {
+        success,
+        mod_version: metadata.mod_.mod_version.clone(),
+        game_id: game_id.to_string(),
+        steps: installer.steps,
+        mismatched_files,
+    }
+}
@@ -0,0 +1,643 @@
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::net::{SocketAddr, UdpSocket};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{header, Body, Method, Request, Response, Server, StatusCode};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, NON_ALPHANUMERIC};
+use serde::{ser::Serializer, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio::sync::{oneshot, Mutex};
+use tokio_util::io::ReaderStream;
+
+use crate::file_metadata::get_cached_metadata;
+use crate::metadata::{
+    installed_layout, is_excluded, load_metadata, replace_game_id, LayoutEntry, Metadata,
+};
+use crate::rclone_conf::{save_user_remote, ConfSection};
+use crate::rpcs3_directory::Rpcs3Directory;
+
+const DEFAULT_PORT: u16 = 8765;
+const LAN_REMOTE_NAME: &str = "lan_mirror";
+const GENERATED_FILES: [&str; 3] = ["metadata.json", "mirrors.json", "rclone.conf"];
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Conf(#[from] crate::rclone_conf::Error),
+    #[error("the LAN mirror is already running on port {0}")]
+    AlreadyRunning(u16),
+    #[error("failed to start the LAN mirror: {0}")]
+    Bind(String),
+    #[error("{0}")]
+    Metadata(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanMirrorStatus {
+    pub running: bool,
+    pub port: Option<u16>,
+    pub urls: Vec<String>,
+    pub mod_version: Option<String>,
+    pub served_paths: Vec<String>,
+    // Mod files that are installed but don't match the metadata md5, never served
+    pub skipped_files: Vec<String>,
+}
+
+struct RunningMirror {
+    status: LanMirrorStatus,
+    shutdown: oneshot::Sender<()>,
+}
+
+// Managed in app state, only one mirror runs at a time
+#[derive(Default)]
+pub struct LanMirrorState(Mutex<Option<RunningMirror>>);
+
+// A local file or directory exposed under a path of the remote layout, e.g. base_folders/NPJB00512
+struct Mount {
+    remote_path: Vec<String>,
+    local_path: PathBuf,
+    exclude_paths: Vec<String>,
+}
+
+struct MirrorContent {
+    mounts: Vec<Mount>,
+    metadata_json: String,
+    port: u16,
+}
+
+enum Target {
+    Directory(BTreeMap<String, Option<u64>>),
+    File(PathBuf),
+    Generated(&'static str),
+    NotFound,
+}
+
+fn split_path(path: &str) -> Vec<String> {
+    path.split('/')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| segment.to_string())
+        .collect()
+}
+
+impl MirrorContent {
+    // url is where the request came in, the generated descriptors (and so their sizes) depend on it
+    fn resolve(&self, segments: &[String], url: &str) -> Target {
+        if let [name] = segments {
+            if let Some(generated) = GENERATED_FILES.iter().find(|generated| *generated == name) {
+                return Target::Generated(generated);
+            }
+        }
+
+        if let Some(mount) = self
+            .mounts
+            .iter()
+            .find(|mount| segments.starts_with(&mount.remote_path))
+        {
+            return self.resolve_mount(mount, &segments[mount.remote_path.len()..]);
+        }
+
+        // Parent folders of the mounts only exist in the remote layout
+        let mut children: BTreeMap<String, Option<u64>> = BTreeMap::new();
+        for mount in self.mounts.iter() {
+            if mount.remote_path.len() > segments.len() && mount.remote_path.starts_with(segments) {
+                let is_dir =
+                    mount.remote_path.len() > segments.len() + 1 || mount.local_path.is_dir();
+                let size = match is_dir {
+                    true => None,
+                    false => std::fs::metadata(&mount.local_path)
+                        .ok()
+                        .map(|metadata| metadata.len()),
+                };
+                children.insert(mount.remote_path[segments.len()].clone(), size);
+            }
+        }
+        // rclone's http backend trusts the listed sizes, they have to match what is served
+        if segments.is_empty() {
+            for generated in GENERATED_FILES {
+                let (_, contents) = generated_content(generated, url, &self.metadata_json);
+                children.insert(generated.to_string(), Some(contents.len() as u64));
+            }
+        }
+
+        match children.is_empty() {
+            true => Target::NotFound,
+            false => Target::Directory(children),
+        }
+    }
+
+    fn resolve_mount(&self, mount: &Mount, remainder: &[String]) -> Target {
+        let Ok(root) = mount.local_path.canonicalize() else {
+            return Target::NotFound;
+        };
+        let Ok(local_path) = remainder
+            .iter()
+            .fold(mount.local_path.clone(), |path, segment| path.join(segment))
+            .canonicalize()
+        else {
+            return Target::NotFound;
+        };
+
+        // Symlinks inside the mod folders must not lead anywhere else on the machine
+        if !local_path.starts_with(&root) {
+            return Target::NotFound;
+        }
+
+        let relative_path = format!("/{}", remainder.join("/"));
+        if !remainder.is_empty()
+            && is_excluded(&mount.exclude_paths, &relative_path, local_path.is_dir())
+        {
+            return Target::NotFound;
+        }
+
+        if local_path.is_file() {
+            return Target::File(local_path);
+        }
+
+        let Ok(entries) = std::fs::read_dir(&local_path) else {
+            return Target::NotFound;
+        };
+        let mut children: BTreeMap<String, Option<u64>> = BTreeMap::new();
+        for entry in entries.flatten() {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let name = entry.file_name().to_string_lossy().to_string();
+            let child_path = match relative_path.as_str() {
+                "/" => format!("/{}", name),
+                _ => format!("{}/{}", relative_path, name),
+            };
+            if is_excluded(&mount.exclude_paths, &child_path, metadata.is_dir()) {
+                continue;
+            }
+            children.insert(name, metadata.is_file().then(|| metadata.len()));
+        }
+
+        Target::Directory(children)
+    }
+}
+
+fn local_address() -> Option<String> {
+    // Connecting a UDP socket sends nothing, it only picks the interface that would route outwards
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    Some(socket.local_addr().ok()?.ip().to_string())
+}
+
+fn mirror_url(request: &Request<Body>, port: u16) -> String {
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|host| host.to_str().ok())
+        .map(|host| host.to_string())
+        .unwrap_or(format!(
+            "{}:{}",
+            local_address().unwrap_or("localhost".to_string()),
+            port
+        ));
+    format!("http://{}/", host)
+}
+
+// Descriptors are generated per request so the url matches whatever address the other launcher used
+fn generated_content(name: &str, url: &str, metadata_json: &str) -> (&'static str, String) {
+    let contents = match name {
+        "mirrors.json" => serde_json::json!({
+            "mirrorGroups": [{
+                "name": format!("LAN ({})", url),
+                "testUrl": format!("{}metadata.json", url),
+                "remotes": [{ "name": "LAN", "rcloneName": LAN_REMOTE_NAME }],
+            }]
+        })
+        .to_string(),
+        "rclone.conf" => format!("[{}]\ntype = http\nurl = {}\n", LAN_REMOTE_NAME, url),
+        _ => metadata_json.to_string(),
+    };
+
+    match name {
+        "rclone.conf" => ("text/plain; charset=utf-8", contents),
+        _ => ("application/json", contents),
+    }
+}
+
+fn status_response(status: StatusCode) -> Response<Body> {
+    let mut response = Response::new(Body::from(status.to_string()));
+    *response.status_mut() = status;
+    response
+}
+
+// rclone's http backend reads plain html listings, folders have to end with a slash
+fn directory_listing(segments: &[String], children: &BTreeMap<String, Option<u64>>) -> String {
+    let title = format!("/{}", segments.join("/"));
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html>\n<head><title>{0}</title></head>\n<body>\n<h1>{0}</h1>\n<ul>\n",
+        title
+    );
+    for (name, size) in children.iter() {
+        let href = utf8_percent_encode(name, NON_ALPHANUMERIC).to_string();
+        match size {
+            Some(size) => html.push_str(&format!(
+                "<li><a href=\"{}\">{}</a> {}</li>\n",
+                href, name, size
+            )),
+            None => html.push_str(&format!("<li><a href=\"{}/\">{}/</a></li>\n", href, name)),
+        }
+    }
+    html.push_str("</ul>\n</body>\n</html>\n");
+    html
+}
+
+// Single ranges only, which is all rclone asks for when resuming or using multi-thread downloads
+fn parse_range(range: &str, len: u64) -> Option<(u64, u64)> {
+    let (start, end) = range.strip_prefix("bytes=")?.split_once('-')?;
+    let (start, end) = match (start.trim(), end.trim()) {
+        ("", suffix) => (
+            len.saturating_sub(suffix.parse().ok()?),
+            len.checked_sub(1)?,
+        ),
+        (start, "") => (start.parse().ok()?, len.checked_sub(1)?),
+        (start, end) => (
+            start.parse().ok()?,
+            end.parse::<u64>().ok()?.min(len.checked_sub(1)?),
+        ),
+    };
+    (start <= end && start < len).then_some((start, end))
+}
+
+async fn file_response(request: &Request<Body>, path: &Path) -> std::io::Result<Response<Body>> {
+    let mut file = tokio::fs::File::open(path).await?;
+    let metadata = file.metadata().await?;
+    let len = metadata.len();
+
+    let mut builder = Response::builder()
+        .header(header::CONTENT_TYPE, "application/octet-stream")
+        .header(header::ACCEPT_RANGES, "bytes");
+    if let Ok(modified) = metadata.modified() {
+        let modified: chrono::DateTime<chrono::Utc> = modified.into();
+        builder = builder.header(
+            header::LAST_MODIFIED,
+            modified.format("%a, %d %b %Y %H:%M:%S GMT").to_string(),
+        );
+    }
+
+    let range = request
+        .headers()
+        .get(header::RANGE)
+        .and_then(|range| range.to_str().ok());
+    let (start, end) = match range {
+        Some(range) => match parse_range(range, len) {
+            Some(range) => {
+                builder = builder.status(StatusCode::PARTIAL_CONTENT).header(
+                    header::CONTENT_RANGE,
+                    format!("bytes {}-{}/{}", range.0, range.1, len),
+                );
+                range
+            }
+            None => {
+                let mut response = status_response(StatusCode::RANGE_NOT_SATISFIABLE);
+                response.headers_mut().insert(
+                    header::CONTENT_RANGE,
+                    format!("bytes */{}", len).parse().unwrap(),
+                );
+                return Ok(response);
+            }
+        },
+        None => (0, len.saturating_sub(1)),
+    };
+    let content_length = match len {
+        0 => 0,
+        _ => end - start + 1,
+    };
+    builder = builder.header(header::CONTENT_LENGTH, content_length);
+
+    if request.method() == Method::HEAD {
+        return Ok(builder.body(Body::empty()).unwrap());
+    }
+
+    file.seek(std::io::SeekFrom::Start(start)).await?;
+    let body = Body::wrap_stream(ReaderStream::new(file.take(content_length)));
+    Ok(builder.body(body).unwrap())
+}
+
+async fn handle_request(
+    content: Arc<MirrorContent>,
+    request: Request<Body>,
+) -> std::result::Result<Response<Body>, Infallible> {
+    // Read-only, nothing on the mirror can be changed from the network
+    if request.method() != Method::GET && request.method() != Method::HEAD {
+        return Ok(status_response(StatusCode::METHOD_NOT_ALLOWED));
+    }
+
+    let Ok(path) = percent_decode_str(request.uri().path()).decode_utf8() else {
+        return Ok(status_response(StatusCode::BAD_REQUEST));
+    };
+    let segments = split_path(&path);
+    if segments
+        .iter()
+        .any(|segment| segment == "." || segment == ".." || segment.contains('\\'))
+    {
+        return Ok(status_response(StatusCode::BAD_REQUEST));
+    }
+
+    let url = mirror_url(&request, content.port);
+    let response = match content.resolve(&segments, &url) {
+        Target::NotFound => status_response(StatusCode::NOT_FOUND),
+        Target::File(local_path) => file_response(&request, &local_path)
+            .await
+            .unwrap_or_else(|_| status_response(StatusCode::INTERNAL_SERVER_ERROR)),
+        Target::Directory(children) => {
+            let html = directory_listing(&segments, &children);
+            Response::builder()
+                .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
+                .body(Body::from(html))
+                .unwrap()
+        }
+        Target::Generated(name) => {
+            let (content_type, contents) = generated_content(name, &url, &content.metadata_json);
+            Response::builder()
+                .header(header::CONTENT_TYPE, content_type)
+                .body(Body::from(contents))
+                .unwrap()
+        }
+    };
+
+    Ok(response)
+}
+
+// Serves every game version that is installed, skipping mod files that don't match the metadata.
+// Checksums come from the metadata cache, psarcs are only hashed again when they were modified.
+async fn build_mounts(
+    app: &AppHandle,
+    rpcs3_directory: &Rpcs3Directory,
+    metadata: &Metadata,
+) -> (Vec<Mount>, Vec<String>) {
+    let mut game_ids: Vec<String> = metadata
+        .mod_
+        .files
+        .iter()
+        .flat_map(|file| file.versions.clone())
+        .collect();
+    game_ids.sort();
+    game_ids.dedup();
+
+    let mut entries: Vec<LayoutEntry> = Vec::new();
+    for game_id in game_ids.iter() {
+        let base_path = rpcs3_directory.join(replace_game_id(&metadata.base.path, game_id));
+        if !base_path.is_dir() {
+            continue;
+        }
+
+        for entry in installed_layout(rpcs3_directory, metadata, game_id) {
+            if entry.local_path.exists()
+                && !entries
+                    .iter()
+                    .any(|existing| existing.remote_path == entry.remote_path)
+            {
+                entries.push(entry);
+            }
+        }
+    }
+
+    let checked_paths: Vec<String> = entries
+        .iter()
+        .filter(|entry| entry.md5.is_some())
+        .map(|entry| entry.local_path.display().to_string())
+        .collect();
+    let checksums: HashMap<String, String> = get_cached_metadata(app, checked_paths, false)
+        .await
+        .unwrap_or_default()
+        .into_iter()
+        .map(|file_metadata| (file_metadata.path, file_metadata.checksum))
+        .collect();
+
+    let mut mounts: Vec<Mount> = Vec::new();
+    let mut skipped_files: Vec<String> = Vec::new();
+    for entry in entries {
+        if let Some(md5) = &entry.md5 {
+            if checksums.get(&entry.local_path.display().to_string()) != Some(md5) {
+                skipped_files.push(entry.remote_path);
+                continue;
+            }
+        }
+
+        mounts.push(Mount {
+            remote_path: split_path(&entry.remote_path),
+            local_path: entry.local_path,
+            exclude_paths: entry.exclude_paths,
+        });
+    }
+
+    // Longest first so nested mounts win over the folder they are in
+    mounts.sort_by(|a, b| b.remote_path.len().cmp(&a.remote_path.len()));
+    (mounts, skipped_files)
+}
+
+#[tauri::command]
+pub async fn start_lan_mirror(
+    app: AppHandle,
+    rpcs3_directory: &str,
+    beta: bool,
+    port: Option<u16>,
+) -> Result<LanMirrorStatus> {
+    let state = app.state::<LanMirrorState>();
+    let mut running = state.0.lock().await;
+    if let Some(running) = running.as_ref() {
+        return Err(Error::AlreadyRunning(
+            running.status.port.unwrap_or_default(),
+        ));
+    }
+
+    let metadata = load_metadata(&app, beta).map_err(Error::Metadata)?;
+    let (mounts, skipped_files) = build_mounts(
+        &app,
+        &Rpcs3Directory::at(Path::new(rpcs3_directory)),
+        &metadata,
+    )
+    .await;
+
+    let port = port.unwrap_or(DEFAULT_PORT);
+    let served_paths = mounts
+        .iter()
+        .map(|mount| mount.remote_path.join("/"))
+        .collect();
+    let content = Arc::new(MirrorContent {
+        mounts,
+        metadata_json: serde_json::to_string(&metadata)
+            .map_err(|err| Error::Metadata(err.to_string()))?,
+        port,
+    });
+
+    let make_service = make_service_fn(move |_connection| {
+        let content = content.clone();
+        async move {
+            Ok::<_, Infallible>(service_fn(move |request| {
+                handle_request(content.clone(), request)
+            }))
+        }
+    });
+
+    let (shutdown, shutdown_receiver) = oneshot::channel::<()>();
+    let server = Server::try_bind(&SocketAddr::from(([0, 0, 0, 0], port)))
+        .map_err(|err| Error::Bind(err.to_string()))?
+        .serve(make_service)
+        .with_graceful_shutdown(async {
+            shutdown_receiver.await.ok();
+        });
+    tauri::async_runtime::spawn(async move {
+        if let Err(err) = server.await {
+            println!("LAN mirror stopped: {}", err);
+        }
+    });
+
+    let status = LanMirrorStatus {
+        running: true,
+        port: Some(port),
+        urls: local_address()
+            .into_iter()
+            .chain(["localhost".to_string()])
+            .map(|host| format!("http://{}:{}/", host, port))
+            .collect(),
+        mod_version: Some(metadata.mod_.mod_version.clone()),
+        served_paths,
+        skipped_files,
+    };
+    println!("LAN mirror started on port {}", port);
+
+    *running = Some(RunningMirror {
+        status: status.clone(),
+        shutdown,
+    });
+    Ok(status)
+}
+
+#[tauri::command]
+pub async fn stop_lan_mirror(app: AppHandle) -> Result<LanMirrorStatus> {
+    let state = app.state::<LanMirrorState>();
+    if let Some(running) = state.0.lock().await.take() {
+        let _ = running.shutdown.send(());
+        println!("LAN mirror stopped");
+    }
+    Ok(LanMirrorStatus::default())
+}
+
+#[tauri::command]
+pub async fn get_lan_mirror_status(app: AppHandle) -> Result<LanMirrorStatus> {
+    let state = app.state::<LanMirrorState>();
+    let running = state.0.lock().await;
+    Ok(running
+        .as_ref()
+        .map(|running| running.status.clone())
+        .unwrap_or_default())
+}
+
+// Adds another launcher's mirror as a user remote, which loadMirrors tries first in every mirror group
+#[tauri::command]
+pub async fn add_lan_mirror(app: AppHandle, url: &str) -> Result<String> {
+    let url = match url.ends_with('/') {
+        true => url.to_string(),
+        false => format!("{}/", url),
+    };
+
+    let client = reqwest::Client::new();
+    let body = client
+        .get(format!("{}metadata.json", url))
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await?
+        .error_for_status()?
+        .text()
+        .await?;
+    let metadata: Metadata =
+        serde_json::from_str(&body).map_err(|err| Error::Metadata(err.to_string()))?;
+
+    save_user_remote(
+        &app,
+        ConfSection {
+            name: LAN_REMOTE_NAME.to_string(),
+            entries: vec![
+                ("type".to_string(), "http".to_string()),
+                ("url".to_string(), url),
+            ],
+        },
+    )?;
+
+    Ok(metadata.mod_.mod_version)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_range_reads_single_ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some((0, 99)));
+        assert_eq!(parse_range("bytes=900-", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-100", 1000), Some((900, 999)));
+        assert_eq!(parse_range("bytes=-5000", 1000), Some((0, 999)));
+        // An end past the file is clamped, rclone asks for whole chunks
+        assert_eq!(parse_range("bytes=500-4999", 1000), Some((500, 999)));
+    }
+
+    #[test]
+    fn parse_range_rejects_unsatisfiable_ranges() {
+        for range in [
+            "bytes=1000-",
+            "bytes=100-50",
+            "bytes=0-99,200-299",
+            "bytes=a-b",
+            "items=0-99",
+            "bytes=-",
+        ] {
+            assert_eq!(parse_range(range, 1000), None, "{}", range);
+        }
+        assert_eq!(parse_range("bytes=0-", 0), None);
+    }
+
+    #[test]
+    fn root_listing_sizes_match_the_generated_files() {
+        let content = MirrorContent {
+            mounts: Vec::new(),
+            metadata_json: r#"{"mod":{}}"#.to_string(),
+            port: DEFAULT_PORT,
+        };
+        let url = "http://192.168.1.20:8765/";
+        let Target::Directory(children) = content.resolve(&[], url) else {
+            panic!("root is not a directory");
+        };
+        for name in GENERATED_FILES {
+            let (_, contents) = generated_content(name, url, &content.metadata_json);
+            assert_eq!(
+                children.get(name),
+                Some(&Some(contents.len() as u64)),
+                "{}",
+                name
+            );
+        }
+        assert!(matches!(
+            content.resolve(&["metadata.json".to_string()], url),
+            Target::Generated("metadata.json")
+        ));
+        assert!(matches!(
+            content.resolve(&["base_folders".to_string()], url),
+            Target::NotFound
+        ));
+    }
+}
@@ -0,0 +1,305 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::metadata::replace_game_id;
+use crate::os::{get_os, OS};
+
+const PROFILES_FILE: &str = "launch_profiles.json";
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("invalid launch profile {0}: {1}")]
+    Invalid(String, String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LaunchProfile {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_no_gui")]
+    pub no_gui: bool,
+    // Extra rpcs3 arguments before the game, e.g. --config, {GAME_ID} is replaced with the serial
+    #[serde(default)]
+    pub args: Vec<String>,
+    // e.g. MESA_VK_WSI_PRESENT_MODE or __GL_THREADED_OPTIMIZATIONS
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    // Linux only, each entry is a whole command such as "gamemoderun" or "taskset -c 2-7",
+    // prepended to argv in order.
+    #[serde(default)]
+    pub wrappers: Vec<String>,
+}
+
+fn default_no_gui() -> bool {
+    true
+}
+
+impl LaunchProfile {
+    // What launch_game always did: rpcs3 --no-gui %RPCS3_GAMEID%:{game}
+    fn default_profile() -> LaunchProfile {
+        LaunchProfile {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "Default".to_string(),
+            no_gui: true,
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            wrappers: Vec::new(),
+        }
+    }
+
+    fn validate(&self) -> Result<()> {
+        let invalid = |reason: &str| Err(Error::Invalid(self.id.clone(), reason.to_string()));
+        if self.id.trim().is_empty() {
+            return invalid("the id is empty");
+        }
+        if self
+            .env
+            .keys()
+            .any(|name| name.is_empty() || name.contains('=') || name.contains('\0'))
+        {
+            return invalid("environment variable names can't be empty or contain '='");
+        }
+        if self
+            .wrappers
+            .iter()
+            .any(|wrapper| wrapper.split_whitespace().next().is_none())
+        {
+            return invalid("wrapper commands can't be empty");
+        }
+        Ok(())
+    }
+
+    // Program and arguments to spawn, the wrappers come first and run rpcs3 themselves
+    pub fn command_line(
+        &self,
+        executable_path: &str,
+        game_id: &str,
+        game_args: &[String],
+    ) -> Vec<String> {
+        let mut argv: Vec<String> = Vec::new();
+        match get_os() {
+            OS::Linux => argv.extend(
+                self.wrappers
+                    .iter()
+                    .flat_map(|wrapper| wrapper.split_whitespace().map(str::to_string)),
+            ),
+            _ if !self.wrappers.is_empty() => {
+                println!("Wrapper commands are only supported on Linux, ignoring them")
+            }
+            _ => {}
+        }
+        argv.push(executable_path.to_string());
+        if self.no_gui {
+            argv.push("--no-gui".to_string());
+        }
+        argv.extend(self.args.iter().map(|arg| replace_game_id(arg, game_id)));
+        argv.extend(game_args.iter().cloned());
+        argv
+    }
+}
+
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ProfilesFile {
+    profiles: Vec<LaunchProfile>,
+}
+
+fn profiles_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .resolve(PROFILES_FILE, BaseDirectory::AppData)
+        .expect("failed to resolve resource")
+}
+
+// The default profile is always there, saving one with its id changes it
+pub fn load_profiles(app: &AppHandle) -> Result<Vec<LaunchProfile>> {
+    let path = profiles_path(app);
+    let mut profiles = match path.exists() {
+        true => serde_json::from_str::<ProfilesFile>(&fs::read_to_string(&path)?)?.profiles,
+        false => Vec::new(),
+    };
+    if !profiles
+        .iter()
+        .any(|profile| profile.id == DEFAULT_PROFILE_ID)
+    {
+        profiles.insert(0, LaunchProfile::default_profile());
+    }
+    Ok(profiles)
+}
+
+fn save_profiles(app: &AppHandle, profiles: Vec<LaunchProfile>) -> Result<()> {
+    let path = profiles_path(app);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(
+        &temp_path,
+        serde_json::to_string_pretty(&ProfilesFile { profiles })?,
+    )?;
+    fs::rename(&temp_path, &path)?;
+    Ok(())
+}
+
+// A stored id whose profile was deleted since launches with the default one instead of failing
+fn select_profile(mut profiles: Vec<LaunchProfile>, profile_id: Option<&str>) -> LaunchProfile {
+    let profile_id = profile_id.unwrap_or(DEFAULT_PROFILE_ID);
+    if let Some(index) = profiles.iter().position(|profile| profile.id == profile_id) {
+        return profiles.swap_remove(index);
+    }
+    println!(
+        "Unknown launch profile {}, using the default profile",
+        profile_id
+    );
+    profiles
+        .into_iter()
+        .find(|profile| profile.id == DEFAULT_PROFILE_ID)
+        .unwrap_or_else(LaunchProfile::default_profile)
+}
+
+pub fn find_profile(app: &AppHandle, profile_id: Option<&str>) -> Result<LaunchProfile> {
+    Ok(select_profile(load_profiles(app)?, profile_id))
+}
+
+#[tauri::command]
+pub async fn get_launch_profiles(app: AppHandle) -> Result<Vec<LaunchProfile>> {
+    load_profiles(&app)
+}
+
+// Adds the profile or replaces the one with the same id
+#[tauri::command]
+pub async fn save_launch_profile(
+    app: AppHandle,
+    profile: LaunchProfile,
+) -> Result<Vec<LaunchProfile>> {
+    profile.validate()?;
+    let mut profiles = load_profiles(&app)?;
+    match profiles
+        .iter_mut()
+        .find(|existing| existing.id == profile.id)
+    {
+        Some(existing) => *existing = profile,
+        None => profiles.push(profile),
+    }
+    save_profiles(&app, profiles.clone())?;
+    Ok(profiles)
+}
+
+// Deleting the default profile resets it
+#[tauri::command]
+pub async fn delete_launch_profile(app: AppHandle, profile_id: &str) -> Result<Vec<LaunchProfile>> {
+    let profiles: Vec<LaunchProfile> = load_profiles(&app)?
+        .into_iter()
+        .filter(|profile| profile.id != profile_id)
+        .collect();
+    save_profiles(&app, profiles)?;
+    load_profiles(&app)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile() -> LaunchProfile {
+        LaunchProfile {
+            id: "gamemode".to_string(),
+            name: "Gamemode".to_string(),
+            no_gui: true,
+            args: vec![
+                "--config".to_string(),
+                "custom_configs/config_{GAME_ID}.yml".to_string(),
+            ],
+            env: BTreeMap::from([("__GL_THREADED_OPTIMIZATIONS".to_string(), "1".to_string())]),
+            wrappers: vec!["gamemoderun".to_string(), "taskset -c 2-7".to_string()],
+        }
+    }
+
+    #[test]
+    fn default_profile_launches_like_before() {
+        assert_eq!(
+            LaunchProfile::default_profile().command_line(
+                "/opt/rpcs3",
+                "BLJS10250",
+                &["%RPCS3_GAMEID%:BLJS10250".to_string()]
+            ),
+            ["/opt/rpcs3", "--no-gui", "%RPCS3_GAMEID%:BLJS10250"]
+        );
+    }
+
+    #[test]
+    fn command_line_puts_wrappers_first_on_linux_only() {
+        let argv = profile().command_line("/opt/rpcs3", "NPJB00512", &["EBOOT.BIN".to_string()]);
+        let rpcs3_argv = [
+            "/opt/rpcs3",
+            "--no-gui",
+            "--config",
+            "custom_configs/config_NPJB00512.yml",
+            "EBOOT.BIN",
+        ];
+        match get_os() {
+            OS::Linux => {
+                assert_eq!(argv[..4], ["gamemoderun", "taskset", "-c", "2-7"]);
+                assert_eq!(argv[4..], rpcs3_argv);
+            }
+            _ => assert_eq!(argv, rpcs3_argv),
+        }
+    }
+
+    #[test]
+    fn unknown_profile_falls_back_to_the_default() {
+        let mut default_profile = LaunchProfile::default_profile();
+        default_profile.no_gui = false;
+        let profiles = vec![default_profile, profile()];
+
+        assert_eq!(
+            select_profile(profiles.clone(), Some("gamemode")).id,
+            "gamemode"
+        );
+        let selected = select_profile(profiles.clone(), Some("deleted"));
+        assert_eq!(selected.id, DEFAULT_PROFILE_ID);
+        assert!(!selected.no_gui);
+        assert_eq!(select_profile(profiles, None).id, DEFAULT_PROFILE_ID);
+        assert_eq!(
+            select_profile(Vec::new(), Some("deleted")).id,
+            DEFAULT_PROFILE_ID
+        );
+    }
+
+    #[test]
+    fn validate_rejects_broken_profiles() {
+        assert!(profile().validate().is_ok());
+
+        let mut empty_id = profile();
+        empty_id.id = " ".to_string();
+        assert!(empty_id.validate().is_err());
+
+        let mut bad_env = profile();
+        bad_env.env.insert("A=B".to_string(), "1".to_string());
+        assert!(bad_env.validate().is_err());
+
+        let mut empty_wrapper = profile();
+        empty_wrapper.wrappers.push("  ".to_string());
+        assert!(empty_wrapper.validate().is_err());
+    }
+}
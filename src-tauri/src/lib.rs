@@ -1,22 +1,45 @@
 use tauri::Manager;
 
 use crate::app_initialize::initialize_resources;
+use crate::bundle::{export_mod_bundle, import_mod_bundle};
 use crate::commands::{
     clear_cached_metadata_command, get_file_metadata_command, get_file_modified_epoch_command, rclone_command,
 };
 use crate::downloader::custom_downloader;
+use crate::preflight::preflight_check;
 use crate::psarc::{pack_psarc_command};
+use crate::rclone_conf::{
+    get_user_rclone_conf, list_rclone_remotes, save_user_rclone_conf, test_rclone_remote,
+    test_rclone_remotes,
+};
+use crate::rclone_preview::preview_update;
+use crate::remote_fetch::fetch_remote_file;
+use crate::remote_hooks::RemoteHookCache;
 use crate::file_check::{check_game_versions, check_path_exist};
 use crate::file_handler::get_file_system_entries;
-use crate::game::{auto_find_path_and_run_game, launch_game};
+use crate::game::{get_game_session, launch_eboot, launch_game, GameSessionState};
 use crate::initialize::{check_initialized, initialize};
+use crate::lan_mirror::{
+    add_lan_mirror, get_lan_mirror_status, start_lan_mirror, stop_lan_mirror, LanMirrorState,
+};
+use crate::launch_profiles::{delete_launch_profile, get_launch_profiles, save_launch_profile};
+use crate::mirror_benchmark::benchmark_mirrors;
+use crate::mirrors::{get_mirrors, get_remote_urls, save_mirrors, validate_mirrors};
 use crate::notify::notify;
 use crate::patches::{activate_patch, check_patch_activated};
 use crate::request::get_is_success;
-use crate::rpcs3::{check_rpcs3_running, validate_rpcs3_executable};
+use crate::rpcs3_cache::{check_eboot_cache, clear_game_cache, get_cache_usage};
+use crate::rpcs3_config::{get_rpcs3_settings, set_rpcs3_settings};
+use crate::rpcs3_directory::get_rpcs3_directory;
+use crate::rpcs3_log::{analyze_rpcs3_log, start_log_tail, LogTailState};
+use crate::rpcs3_discovery::discover_rpcs3_installations;
+use crate::rpcs3::{check_rpcs3_running, stop_rpcs3, validate_rpcs3_executable};
+use crate::settings_presets::{apply_settings_preset, diff_settings_preset, get_settings_presets};
+use crate::tools::{get_tool_status, ToolVerificationCache};
 use crate::updater::update_tauri;
 
 mod app_initialize;
+mod bundle;
 mod commands;
 mod downloader;
 mod file_check;
@@ -24,19 +47,42 @@ mod file_handler;
 mod file_metadata;
 mod game;
 mod initialize;
+mod install;
+mod lan_mirror;
+mod launch_profiles;
+mod metadata;
+mod mirror_benchmark;
+mod mirrors;
 mod notify;
 mod os;
 mod patches;
+mod preflight;
 mod psarc;
 mod rclone;
+mod rclone_conf;
+mod rclone_preview;
+mod remote_fetch;
+mod remote_hooks;
 mod request;
 mod rpcs3;
+mod rpcs3_cache;
+mod rpcs3_config;
+mod rpcs3_directory;
+mod rpcs3_discovery;
+mod rpcs3_log;
+mod settings_presets;
+mod tools;
 mod updater;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_os::init())
+        .manage(RemoteHookCache::default())
+        .manage(ToolVerificationCache::default())
+        .manage(LanMirrorState::default())
+        .manage(GameSessionState::default())
+        .manage(LogTailState::default())
         .setup(|app| {
             #[cfg(desktop)]
             app.handle()
@@ -65,7 +111,7 @@ pub fn run() {
             get_file_system_entries,
             check_game_versions,
             check_path_exist,
-            auto_find_path_and_run_game,
+            launch_eboot,
             launch_game,
             update_tauri,
             get_file_metadata_command,
@@ -81,7 +127,44 @@ pub fn run() {
             initialize_resources,
             check_patch_activated,
             activate_patch,
-            get_is_success
+            get_is_success,
+            list_rclone_remotes,
+            get_user_rclone_conf,
+            save_user_rclone_conf,
+            test_rclone_remote,
+            test_rclone_remotes,
+            preview_update,
+            get_tool_status,
+            start_lan_mirror,
+            stop_lan_mirror,
+            get_lan_mirror_status,
+            add_lan_mirror,
+            import_mod_bundle,
+            export_mod_bundle,
+            benchmark_mirrors,
+            get_mirrors,
+            get_remote_urls,
+            validate_mirrors,
+            save_mirrors,
+            fetch_remote_file,
+            discover_rpcs3_installations,
+            get_rpcs3_directory,
+            get_rpcs3_settings,
+            set_rpcs3_settings,
+            get_settings_presets,
+            apply_settings_preset,
+            diff_settings_preset,
+            get_game_session,
+            analyze_rpcs3_log,
+            start_log_tail,
+            stop_rpcs3,
+            get_launch_profiles,
+            save_launch_profile,
+            delete_launch_profile,
+            preflight_check,
+            get_cache_usage,
+            clear_game_cache,
+            check_eboot_cache
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
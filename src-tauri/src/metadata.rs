@@ -0,0 +1,326 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::rpcs3_directory::Rpcs3Directory;
+use crate::settings_presets::SettingsPreset;
+
+// The Full Boost serials, NPJB00512 is the digital release and BLJS10250 the disc
+pub const GAME_IDS: [&str; 2] = ["NPJB00512", "BLJS10250"];
+
+// Rust side of src/lib/metadata.ts, paths are relative to the rpcs3 directory
+// (or the remote root for remote paths) and may contain {GAME_ID}.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Metadata {
+    pub base: SyncBase,
+    #[serde(rename = "mod")]
+    pub mod_: SyncMod,
+    // Oldest rpcs3 build the modded EBOOT runs on, e.g. "0.0.29-15000"
+    #[serde(default)]
+    pub min_rpcs3_version: Option<String>,
+    // Extra or updated presets on top of the bundled ones
+    #[serde(default, deserialize_with = "lenient_presets")]
+    pub settings_presets: Vec<SettingsPreset>,
+}
+
+// A preset an older launcher doesn't understand is skipped, it must not take the whole metadata.json with it
+fn lenient_presets<'de, D>(deserializer: D) -> Result<Vec<SettingsPreset>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    Ok(values
+        .into_iter()
+        .filter_map(
+            |value| match serde_json::from_value::<SettingsPreset>(value) {
+                Ok(preset) => Some(preset),
+                Err(err) => {
+                    println!("Skipping invalid settings preset: {}", err);
+                    None
+                }
+            },
+        )
+        .collect())
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncBase {
+    pub path: String,
+    pub remote_path: String,
+    pub dlc_path: String,
+    pub dlc_remote_path: String,
+    #[serde(rename = "dlcNPJBRemoteBasePath")]
+    pub dlc_npjb_remote_base_path: String,
+    #[serde(rename = "dlcNPJBBasePath")]
+    pub dlc_npjb_base_path: String,
+    pub patch_path: String,
+    pub patch_remote_path: String,
+    pub patch_md5: String,
+    pub exclude_paths: Vec<String>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncMod {
+    pub mod_version: String,
+    pub files: Vec<ModFile>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModFileType {
+    File,
+    Psarc,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModFile {
+    pub versions: Vec<String>,
+    pub name: String,
+    pub path: String,
+    pub md5: String,
+    pub remote_path: String,
+    #[serde(rename = "type")]
+    pub file_type: ModFileType,
+}
+
+impl Metadata {
+    pub fn files_for(&self, game_id: &str) -> impl Iterator<Item = &ModFile> {
+        let game_id = game_id.to_string();
+        self.mod_
+            .files
+            .iter()
+            .filter(move |file| file.versions.contains(&game_id))
+    }
+}
+
+pub fn replace_game_id(path: &str, game_id: &str) -> String {
+    path.replace("{GAME_ID}", game_id)
+}
+
+pub fn metadata_file_name(beta: bool) -> &'static str {
+    match beta {
+        true => "metadata-beta.json",
+        false => "metadata.json",
+    }
+}
+
+// psarc files are synced as folders into rpcs3/.moddedboost/{remotePath} and packed from there
+pub fn psarc_source_directory(rpcs3_directory: &Rpcs3Directory, file: &ModFile) -> PathBuf {
+    rpcs3_directory
+        .path
+        .join(".moddedboost")
+        .join(&file.remote_path)
+}
+
+// A file or folder of the installed mod and where it lives in the remote layout
+pub struct LayoutEntry {
+    pub remote_path: String,
+    pub local_path: PathBuf,
+    pub exclude_paths: Vec<String>,
+    // Only known for single files, psarc source folders are checked through the packed psarc
+    pub md5: Option<String>,
+}
+
+// Maps the installation of one game version back to the remote layout:
+// base folders, dlc, mod files, psarc source folders and the patch, the way an update installs them.
+pub fn installed_layout(
+    rpcs3_directory: &Rpcs3Directory,
+    metadata: &Metadata,
+    game_id: &str,
+) -> Vec<LayoutEntry> {
+    let base = &metadata.base;
+    let mut layout: Vec<LayoutEntry> = Vec::new();
+    let mut push = |entry: LayoutEntry| {
+        if !layout
+            .iter()
+            .any(|existing| existing.remote_path == entry.remote_path)
+        {
+            layout.push(entry);
+        }
+    };
+
+    push(LayoutEntry {
+        remote_path: replace_game_id(&base.remote_path, game_id),
+        local_path: rpcs3_directory.join(replace_game_id(&base.path, game_id)),
+        exclude_paths: base.exclude_paths.clone(),
+        md5: None,
+    });
+    push(LayoutEntry {
+        remote_path: base.dlc_remote_path.clone(),
+        local_path: rpcs3_directory.join(replace_game_id(&base.dlc_path, game_id)),
+        exclude_paths: base.exclude_paths.clone(),
+        md5: None,
+    });
+    // For BLJS the base folder of NPJB00512 is installed as well so that DLC works
+    if game_id == "BLJS10250" {
+        push(LayoutEntry {
+            remote_path: base.dlc_npjb_remote_base_path.clone(),
+            local_path: rpcs3_directory.join(&base.dlc_npjb_base_path),
+            exclude_paths: base.exclude_paths.clone(),
+            md5: None,
+        });
+    }
+
+    for file in metadata.files_for(game_id) {
+        let (local_path, md5) = match file.file_type {
+            ModFileType::File => (
+                rpcs3_directory.join(replace_game_id(&file.path, game_id)),
+                Some(file.md5.clone()),
+            ),
+            ModFileType::Psarc => (psarc_source_directory(rpcs3_directory, file), None),
+        };
+        push(LayoutEntry {
+            remote_path: replace_game_id(&file.remote_path, game_id),
+            local_path,
+            exclude_paths: Vec::new(),
+            md5,
+        });
+    }
+
+    push(LayoutEntry {
+        remote_path: base.patch_remote_path.clone(),
+        local_path: rpcs3_directory.join(replace_game_id(&base.patch_path, game_id)),
+        exclude_paths: Vec::new(),
+        md5: Some(base.patch_md5.clone()),
+    });
+
+    layout
+}
+
+// rclone style globs as used in excludePaths, "/" anchors to the folder root and "**" crosses directories
+fn glob_match(pattern: &[u8], path: &[u8]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(b'*'), _) if pattern.get(1) == Some(&b'*') => {
+            (0..=path.len()).any(|index| glob_match(&pattern[2..], &path[index..]))
+        }
+        (Some(b'*'), _) => (0..=path.len())
+            .take_while(|index| *index == 0 || path[index - 1] != b'/')
+            .any(|index| glob_match(&pattern[1..], &path[index..])),
+        (Some(pattern_char), Some(path_char)) if pattern_char == path_char => {
+            glob_match(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+// relative_path starts with a "/", e.g. "/USRDIR/EBOOT.BIN".
+// Patterns without a leading "/" match the end of the path at any depth, starting at a name.
+pub fn is_excluded(exclude_paths: &[String], relative_path: &str, is_dir: bool) -> bool {
+    let dir_path = format!("{}/", relative_path);
+    exclude_paths.iter().any(|pattern| {
+        let patterns = match pattern.starts_with('/') {
+            true => vec![pattern.clone()],
+            false => vec![format!("/{}", pattern), format!("/**/{}", pattern)],
+        };
+        patterns.iter().any(|pattern| {
+            glob_match(pattern.as_bytes(), relative_path.as_bytes())
+                || (is_dir && glob_match(pattern.as_bytes(), dir_path.as_bytes()))
+        })
+    })
+}
+
+// Reads the copy in app data that updateMetadata keeps current, falling back to the bundled one
+pub fn load_metadata(app: &AppHandle, beta: bool) -> Result<Metadata, String> {
+    let file_name = metadata_file_name(beta);
+    let app_data_path = app
+        .path()
+        .resolve(file_name, BaseDirectory::AppData)
+        .map_err(|err| err.to_string())?;
+    let resource_path = app
+        .path()
+        .resolve(format!("resources/{}", file_name), BaseDirectory::Resource)
+        .map_err(|err| err.to_string())?;
+
+    let contents = std::fs::read_to_string(&app_data_path)
+        .or_else(|_| std::fs::read_to_string(&resource_path))
+        .map_err(|err| err.to_string())?;
+
+    serde_json::from_str(&contents).map_err(|err| format!("invalid {}: {}", file_name, err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excludes(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|pattern| pattern.to_string()).collect()
+    }
+
+    #[test]
+    fn invalid_settings_presets_are_skipped() {
+        let mut metadata: serde_json::Value =
+            serde_json::from_str(include_str!("../resources/metadata.json")).unwrap();
+        metadata["settingsPresets"] = serde_json::json!([
+            {"id": "fast", "name": "Fast", "settings": {"video": {"resolutionScale": 50}}},
+            {"id": "broken", "name": "Broken", "settings": {"video": {"resolutionScale": "50%"}}},
+            {"id": "noSettings", "name": "No settings"},
+        ]);
+
+        let metadata: Metadata = serde_json::from_value(metadata).unwrap();
+        let ids: Vec<&str> = metadata
+            .settings_presets
+            .iter()
+            .map(|preset| preset.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["fast"]);
+        assert_eq!(
+            metadata.settings_presets[0].settings.video.resolution_scale,
+            Some(50)
+        );
+    }
+
+    #[test]
+    fn bundled_metadata_parses() {
+        for contents in [
+            include_str!("../resources/metadata.json"),
+            include_str!("../resources/metadata-beta.json"),
+        ] {
+            assert!(serde_json::from_str::<Metadata>(contents).is_ok());
+        }
+    }
+
+    #[test]
+    fn glob_match_follows_rclone_wildcards() {
+        assert!(glob_match(b"/USRDIR/*.psarc", b"/USRDIR/patch_01_00.psarc"));
+        assert!(!glob_match(b"/USRDIR/*.psarc", b"/USRDIR/dlc/a.psarc"));
+        assert!(glob_match(b"/USRDIR/dlc/**", b"/USRDIR/dlc/a/b.psarc"));
+        assert!(glob_match(b"/USRDIR/dlc/**", b"/USRDIR/dlc/"));
+        assert!(!glob_match(b"/USRDIR/dlc/**", b"/USRDIR/dlc"));
+        assert!(!glob_match(b"/USRDIR/EBOOT.BIN", b"/USRDIR/EBOOT.BIN.bak"));
+    }
+
+    #[test]
+    fn anchored_excludes_only_match_from_the_root() {
+        let exclude_paths = excludes(&["/USRDIR/dlc/**", "/USRDIR/patch_01_00.psarc"]);
+        assert!(is_excluded(
+            &exclude_paths,
+            "/USRDIR/patch_01_00.psarc",
+            false
+        ));
+        assert!(is_excluded(&exclude_paths, "/USRDIR/dlc/x.psarc", false));
+        assert!(is_excluded(&exclude_paths, "/USRDIR/dlc", true));
+        assert!(!is_excluded(&exclude_paths, "/USRDIR/dlc", false));
+        assert!(!is_excluded(
+            &exclude_paths,
+            "/backup/USRDIR/patch_01_00.psarc",
+            false
+        ));
+    }
+
+    #[test]
+    fn unanchored_excludes_match_whole_names_at_any_depth() {
+        let exclude_paths = excludes(&["EBOOT.BIN", "*.pdb"]);
+        assert!(is_excluded(&exclude_paths, "/EBOOT.BIN", false));
+        assert!(is_excluded(&exclude_paths, "/USRDIR/EBOOT.BIN", false));
+        assert!(is_excluded(&exclude_paths, "/USRDIR/debug/game.pdb", false));
+        assert!(!is_excluded(&exclude_paths, "/USRDIR/OLDEBOOT.BIN", false));
+        assert!(!is_excluded(&exclude_paths, "/USRDIR/game.pdb.txt", false));
+    }
+}
@@ -0,0 +1,326 @@
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+
+use serde::{ser::Serializer, Serialize};
+use tauri::AppHandle;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
+use tokio::net::TcpStream;
+
+use crate::metadata::{load_metadata, replace_game_id, ModFileType};
+use crate::mirrors::{load_mirrors, MirrorRemote};
+use crate::rclone::RcloneContext;
+use crate::rclone_conf::{ConfSection, RcloneConf};
+
+const DEFAULT_TIMEOUT_SECS: u64 = 20;
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+// Enough to get past TCP slow start without making the benchmark a download of its own
+const SAMPLE_BYTES: u64 = 4 * 1024 * 1024;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("{0}")]
+    Metadata(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteBenchmark {
+    pub group: String,
+    pub name: String,
+    pub rclone_name: String,
+    pub latency_ms: Option<u64>,
+    pub throughput_bytes_per_sec: Option<u64>,
+    pub downloaded_bytes: u64,
+    pub error: Option<String>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GroupBenchmark {
+    pub name: String,
+    // Of the fastest remote in the group, which is the one an update ends up using
+    pub throughput_bytes_per_sec: Option<u64>,
+    pub latency_ms: Option<u64>,
+    pub reachable_remotes: usize,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorBenchmark {
+    pub remotes: Vec<RemoteBenchmark>,
+    pub groups: Vec<GroupBenchmark>,
+    pub recommended_group: Option<String>,
+}
+
+// Hosts the remote talks to, for the connect latency.
+// Remotes without a url or endpoint use the api host of their backend.
+fn remote_host(section: &ConfSection) -> Option<(String, u16)> {
+    let url = match (
+        section.get("url"),
+        section.get("endpoint"),
+        section.get("type"),
+    ) {
+        (Some(url), _, _) => url.to_string(),
+        (None, Some(endpoint), _) if endpoint.contains("://") => endpoint.to_string(),
+        (None, Some(endpoint), _) => format!("https://{}", endpoint),
+        (None, None, Some("drive")) => "https://www.googleapis.com".to_string(),
+        (None, None, Some("onedrive")) => "https://graph.microsoft.com".to_string(),
+        (None, None, Some("dropbox")) => "https://api.dropboxapi.com".to_string(),
+        (None, None, Some("s3")) => "https://s3.amazonaws.com".to_string(),
+        _ => return None,
+    };
+
+    let url = reqwest::Url::parse(&url).ok()?;
+    Some((url.host_str()?.to_string(), url.port_or_known_default()?))
+}
+
+async fn connect_latency(host: &str, port: u16) -> std::result::Result<u64, String> {
+    let started = Instant::now();
+    tokio::time::timeout(CONNECT_TIMEOUT, TcpStream::connect((host, port)))
+        .await
+        .map_err(|_| format!("connecting to {} timed out", host))?
+        .map_err(|err| err.to_string())?;
+    Ok(started.elapsed().as_millis() as u64)
+}
+
+// Ranged download through rclone itself, so auth, hooks and the backend are the same as an update.
+// Time is measured from the first byte, rclone's own start up and listing don't count as throughput.
+// Returns the downloaded bytes, bytes per second and the time to the first byte in ms.
+async fn ranged_download(
+    context: &RcloneContext,
+    remote: &str,
+    sample_path: &str,
+) -> std::result::Result<(u64, u64, u64), String> {
+    let started = Instant::now();
+    let mut cmd = context.command("cat");
+    cmd.arg(format!("{}:/{}", remote, sample_path))
+        .arg("--offset")
+        .arg("0")
+        .arg("--count")
+        .arg(SAMPLE_BYTES.to_string())
+        .arg("--retries")
+        .arg("1")
+        .arg("--low-level-retries")
+        .arg("1")
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    context.apply_overrides(&mut cmd);
+
+    let mut child = cmd.spawn().map_err(|err| err.to_string())?;
+    let mut stdout = child
+        .stdout
+        .take()
+        .expect("child did not have a handle to stdout");
+    let stderr = child
+        .stderr
+        .take()
+        .expect("child did not have a handle to stderr");
+
+    // Drained on its own task, retries logged by rclone could otherwise fill the pipe and block it
+    let stderr_task = tokio::spawn(async move {
+        let mut reader = BufReader::new(stderr).lines();
+        let mut last_line = String::new();
+        while let Ok(Some(line)) = reader.next_line().await {
+            last_line = line;
+        }
+        last_line
+    });
+
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut downloaded: u64 = 0;
+    let mut first_byte: Option<Instant> = None;
+    loop {
+        let read = stdout
+            .read(&mut buffer)
+            .await
+            .map_err(|err| err.to_string())?;
+        if read == 0 {
+            break;
+        }
+        first_byte.get_or_insert_with(Instant::now);
+        downloaded += read as u64;
+    }
+
+    let status = child.wait().await.map_err(|err| err.to_string())?;
+    let last_stderr_line = stderr_task.await.unwrap_or_default();
+    if !status.success() {
+        return Err(last_stderr_line);
+    }
+
+    let Some(first_byte) = first_byte else {
+        return Err(format!("{} is empty", sample_path));
+    };
+    let elapsed = first_byte.elapsed().as_secs_f64().max(0.001);
+    Ok((
+        downloaded,
+        (downloaded as f64 / elapsed) as u64,
+        (first_byte - started).as_millis() as u64,
+    ))
+}
+
+async fn benchmark_remote(
+    app: &AppHandle,
+    group: &str,
+    remote: &MirrorRemote,
+    sample_path: &str,
+    timeout: Duration,
+) -> RemoteBenchmark {
+    let mut benchmark = RemoteBenchmark {
+        group: group.to_string(),
+        name: remote.name.clone(),
+        rclone_name: remote.rclone_name.clone(),
+        latency_ms: None,
+        throughput_bytes_per_sec: None,
+        downloaded_bytes: 0,
+        error: None,
+    };
+
+    let run = async {
+        let context = RcloneContext::prepare(app, &remote.rclone_name)
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let conf = RcloneConf::read(&context.conf_path).map_err(|err| err.to_string())?;
+        let section = conf
+            .get(&remote.rclone_name)
+            .ok_or(format!("{} is not in rclone.conf", remote.rclone_name))?;
+        if let Some((host, port)) = remote_host(section) {
+            benchmark.latency_ms = Some(connect_latency(&host, port).await?);
+        }
+
+        let (downloaded, throughput, first_byte_ms) =
+            ranged_download(&context, &remote.rclone_name, sample_path).await?;
+        // Without a known host the time to the first byte is the closest thing to a latency
+        benchmark.latency_ms.get_or_insert(first_byte_ms);
+        benchmark.downloaded_bytes = downloaded;
+        benchmark.throughput_bytes_per_sec = Some(throughput);
+        Ok::<(), String>(())
+    };
+
+    match tokio::time::timeout(timeout, run).await {
+        Ok(Ok(())) => {}
+        Ok(Err(err)) => benchmark.error = Some(err),
+        Err(_) => benchmark.error = Some(format!("timed out after {}s", timeout.as_secs())),
+    }
+    benchmark
+}
+
+// Fastest first, remotes that failed go last
+fn rank(
+    a_throughput: Option<u64>,
+    a_latency: Option<u64>,
+    b_throughput: Option<u64>,
+    b_latency: Option<u64>,
+) -> std::cmp::Ordering {
+    b_throughput.cmp(&a_throughput).then(
+        a_latency
+            .unwrap_or(u64::MAX)
+            .cmp(&b_latency.unwrap_or(u64::MAX)),
+    )
+}
+
+// The modded EBOOT, a single file of a few MB that every mirror has, the psarcs are folders on the remote
+fn default_sample_path(app: &AppHandle, beta: bool) -> Result<String> {
+    let metadata = load_metadata(app, beta).map_err(Error::Metadata)?;
+    let file = metadata
+        .mod_
+        .files
+        .iter()
+        .find(|file| file.file_type == ModFileType::File && file.name == "EBOOT")
+        .or(metadata
+            .mod_
+            .files
+            .iter()
+            .find(|file| file.file_type == ModFileType::File))
+        .ok_or(Error::Metadata(
+            "metadata.json has no files to download".to_string(),
+        ))?;
+
+    let game_id = file.versions.first().cloned().unwrap_or_default();
+    Ok(replace_game_id(&file.remote_path, &game_id))
+}
+
+#[tauri::command]
+pub async fn benchmark_mirrors(
+    app: AppHandle,
+    beta: bool,
+    sample_path: Option<String>,
+    timeout_secs: Option<u64>,
+) -> Result<MirrorBenchmark> {
+    let mirrors = load_mirrors(&app, beta).map_err(|err| Error::Metadata(err.to_string()))?;
+
+    let sample_path = match sample_path {
+        Some(sample_path) => sample_path,
+        None => default_sample_path(&app, beta)?,
+    };
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TIMEOUT_SECS));
+
+    // One remote at a time, parallel downloads would share the downlink and skew the throughput
+    let mut remotes: Vec<RemoteBenchmark> = Vec::new();
+    for group in mirrors.mirror_groups.iter() {
+        for remote in group.remotes.iter() {
+            remotes.push(benchmark_remote(&app, &group.name, remote, &sample_path, timeout).await);
+        }
+    }
+    remotes.sort_by(|a, b| {
+        rank(
+            a.throughput_bytes_per_sec,
+            a.latency_ms,
+            b.throughput_bytes_per_sec,
+            b.latency_ms,
+        )
+    });
+
+    let mut groups: Vec<GroupBenchmark> = mirrors
+        .mirror_groups
+        .iter()
+        .map(|group| {
+            let group_remotes: Vec<&RemoteBenchmark> = remotes
+                .iter()
+                .filter(|remote| remote.group == group.name && remote.error.is_none())
+                .collect();
+            // remotes is already ranked, the first one of the group is its best
+            let best = group_remotes.first();
+            GroupBenchmark {
+                name: group.name.clone(),
+                throughput_bytes_per_sec: best.and_then(|remote| remote.throughput_bytes_per_sec),
+                latency_ms: best.and_then(|remote| remote.latency_ms),
+                reachable_remotes: group_remotes.len(),
+            }
+        })
+        .collect();
+    groups.sort_by(|a, b| {
+        rank(
+            a.throughput_bytes_per_sec,
+            a.latency_ms,
+            b.throughput_bytes_per_sec,
+            b.latency_ms,
+        )
+    });
+
+    let recommended_group = groups
+        .first()
+        .filter(|group| group.reachable_remotes > 0)
+        .map(|group| group.name.clone());
+
+    Ok(MirrorBenchmark {
+        remotes,
+        groups,
+        recommended_group,
+    })
+}
@@ -0,0 +1,305 @@
+use std::fs;
+use std::path::PathBuf;
+
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::rclone_conf::{ensure_merged_conf, RcloneConf};
+use crate::remote_hooks::HookConfig;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Conf(#[from] crate::rclone_conf::Error),
+    #[error("invalid {0}: {1}")]
+    Invalid(String, String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Mirrors {
+    pub mirror_groups: Vec<MirrorGroup>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorGroup {
+    pub name: String,
+    pub test_url: String,
+    pub remotes: Vec<MirrorRemote>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorRemote {
+    pub name: String,
+    pub rclone_name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hook: Option<HookConfig>,
+}
+
+// remote.json, where the launcher fetches the latest copies of everything else from
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteUrls {
+    pub mirrors: String,
+    pub metadata: String,
+    pub rclone_conf: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorIssue {
+    pub group: String,
+    pub remote: Option<String>,
+    pub message: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorValidation {
+    pub valid: bool,
+    pub issues: Vec<MirrorIssue>,
+}
+
+impl Mirrors {
+    pub fn remotes(&self) -> impl Iterator<Item = (&MirrorGroup, &MirrorRemote)> {
+        self.mirror_groups
+            .iter()
+            .flat_map(|group| group.remotes.iter().map(move |remote| (group, remote)))
+    }
+
+    // Structural checks only, rclone.conf is checked by validate_against
+    pub fn parse(file_name: &str, contents: &str) -> Result<Mirrors> {
+        let mirrors: Mirrors = serde_json::from_str(contents)
+            .map_err(|err| Error::Invalid(file_name.to_string(), err.to_string()))?;
+
+        if mirrors.mirror_groups.is_empty() {
+            return Err(Error::Invalid(
+                file_name.to_string(),
+                "no mirror groups".to_string(),
+            ));
+        }
+        for group in mirrors.mirror_groups.iter() {
+            if group.remotes.is_empty() {
+                return Err(Error::Invalid(
+                    file_name.to_string(),
+                    format!("mirror group '{}' has no remotes", group.name),
+                ));
+            }
+        }
+
+        Ok(mirrors)
+    }
+
+    pub fn validate_against(&self, conf: &RcloneConf) -> MirrorValidation {
+        let mut issues: Vec<MirrorIssue> = Vec::new();
+
+        for group in self.mirror_groups.iter() {
+            if reqwest::Url::parse(&group.test_url).is_err() {
+                issues.push(MirrorIssue {
+                    group: group.name.clone(),
+                    remote: None,
+                    message: format!("testUrl '{}' is not a valid url", group.test_url),
+                });
+            }
+        }
+        for (group, remote) in self.remotes() {
+            if conf.get(&remote.rclone_name).is_none() {
+                issues.push(MirrorIssue {
+                    group: group.name.clone(),
+                    remote: Some(remote.rclone_name.clone()),
+                    message: format!("'{}' is not a remote in rclone.conf", remote.rclone_name),
+                });
+            }
+        }
+
+        MirrorValidation {
+            valid: issues.is_empty(),
+            issues,
+        }
+    }
+}
+
+pub fn mirrors_file_name(beta: bool) -> &'static str {
+    match beta {
+        true => "mirrors-beta.json",
+        false => "mirrors.json",
+    }
+}
+
+fn remote_file_name(beta: bool) -> &'static str {
+    match beta {
+        true => "remote-beta.json",
+        false => "remote.json",
+    }
+}
+
+fn app_data_path(app: &AppHandle, file_name: &str) -> PathBuf {
+    app.path()
+        .resolve(file_name, BaseDirectory::AppData)
+        .expect("failed to resolve resource")
+}
+
+fn resource_path(app: &AppHandle, file_name: &str) -> PathBuf {
+    app.path()
+        .resolve(format!("resources/{}", file_name), BaseDirectory::Resource)
+        .expect("failed to resolve resource")
+}
+
+// The app data copy is the latest fetched one, when it's missing or broken the bundled copy
+// is used and written back so the next read doesn't have to fall back again.
+pub fn load_mirrors(app: &AppHandle, beta: bool) -> Result<Mirrors> {
+    let file_name = mirrors_file_name(beta);
+    let path = app_data_path(app, file_name);
+
+    let loaded = fs::read_to_string(&path)
+        .map_err(Error::from)
+        .and_then(|contents| Mirrors::parse(file_name, &contents));
+    match loaded {
+        Ok(mirrors) => Ok(mirrors),
+        Err(err) => {
+            println!("{}, falling back to the bundled copy", err);
+            let contents = fs::read_to_string(resource_path(app, file_name))?;
+            let mirrors = Mirrors::parse(file_name, &contents)?;
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::write(&path, contents)?;
+            Ok(mirrors)
+        }
+    }
+}
+
+// Only ever shipped with the app, never fetched
+pub fn load_remote_urls(app: &AppHandle, beta: bool) -> Result<RemoteUrls> {
+    let file_name = remote_file_name(beta);
+    let contents = fs::read_to_string(resource_path(app, file_name))?;
+    serde_json::from_str(&contents)
+        .map_err(|err| Error::Invalid(file_name.to_string(), err.to_string()))
+}
+
+#[tauri::command]
+pub async fn get_mirrors(app: AppHandle, beta: bool) -> Result<Mirrors> {
+    load_mirrors(&app, beta)
+}
+
+#[tauri::command]
+pub async fn get_remote_urls(app: AppHandle, beta: bool) -> Result<RemoteUrls> {
+    load_remote_urls(&app, beta)
+}
+
+#[tauri::command]
+pub async fn validate_mirrors(app: AppHandle, beta: bool) -> Result<MirrorValidation> {
+    let mirrors = load_mirrors(&app, beta)?;
+    let conf = RcloneConf::read(&ensure_merged_conf(&app)?)?;
+    Ok(mirrors.validate_against(&conf))
+}
+
+// Replaces the app data copy, anything that doesn't parse is rejected and the current copy kept
+#[tauri::command]
+pub async fn save_mirrors(app: AppHandle, beta: bool, contents: &str) -> Result<MirrorValidation> {
+    let file_name = mirrors_file_name(beta);
+    let mirrors = Mirrors::parse(file_name, contents)?;
+
+    let path = app_data_path(&app, file_name);
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, &path)?;
+
+    let conf = RcloneConf::read(&ensure_merged_conf(&app)?)?;
+    Ok(mirrors.validate_against(&conf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONF: &str = "[googledrive]\ntype = drive\n\n[qiniu]\ntype = s3\n";
+
+    fn mirrors(test_url: &str, rclone_name: &str) -> String {
+        serde_json::json!({
+            "mirrorGroups": [{
+                "name": "normal",
+                "testUrl": test_url,
+                "remotes": [{ "name": "Google Drive", "rcloneName": rclone_name }]
+            }]
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn bundled_mirrors_match_the_bundled_conf() {
+        let conf = RcloneConf::parse(include_str!("../resources/tools/rclone/rclone.conf"));
+        for (file_name, contents) in [
+            ("mirrors.json", include_str!("../resources/mirrors.json")),
+            (
+                "mirrors-beta.json",
+                include_str!("../resources/mirrors-beta.json"),
+            ),
+        ] {
+            let mirrors = Mirrors::parse(file_name, contents).unwrap();
+            assert!(mirrors.validate_against(&conf).valid, "{}", file_name);
+        }
+    }
+
+    #[test]
+    fn parse_rejects_empty_groups() {
+        assert!(Mirrors::parse("mirrors.json", r#"{ "mirrorGroups": [] }"#).is_err());
+        assert!(Mirrors::parse(
+            "mirrors.json",
+            r#"{ "mirrorGroups": [{ "name": "normal", "testUrl": "https://google.com", "remotes": [] }] }"#
+        )
+        .is_err());
+        assert!(Mirrors::parse(
+            "mirrors.json",
+            r#"{ "mirrorGroups": [{ "name": "normal" }] }"#
+        )
+        .is_err());
+        assert!(Mirrors::parse(
+            "mirrors.json",
+            &mirrors("https://google.com", "googledrive")
+        )
+        .is_ok());
+    }
+
+    #[test]
+    fn validate_against_reports_unknown_remotes() {
+        let conf = RcloneConf::parse(CONF);
+        let validation = Mirrors::parse("mirrors.json", &mirrors("https://google.com", "onedrive"))
+            .unwrap()
+            .validate_against(&conf);
+        assert!(!validation.valid);
+        assert_eq!(validation.issues.len(), 1);
+        assert_eq!(validation.issues[0].remote.as_deref(), Some("onedrive"));
+    }
+
+    #[test]
+    fn validate_against_reports_invalid_test_urls() {
+        let conf = RcloneConf::parse(CONF);
+        let validation = Mirrors::parse("mirrors.json", &mirrors("google.com", "googledrive"))
+            .unwrap()
+            .validate_against(&conf);
+        assert!(!validation.valid);
+        assert_eq!(validation.issues.len(), 1);
+        assert_eq!(validation.issues[0].remote, None);
+        assert_eq!(
+            validation.issues[0].message,
+            "testUrl 'google.com' is not a valid url"
+        );
+    }
+}
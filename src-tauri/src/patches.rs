@@ -48,6 +48,9 @@ pub struct PpuSpu {
 
 type YamlData = HashMap<String, PpuSpu>;
 
+// Hash of the Full Boost executable the AddUnitSupport patch is for
+pub const PPU_HASH: &str = "PPU-a787b532b03b2ebc3970a1d405639b05bec1a506";
+
 #[tauri::command]
 pub async fn check_patch_activated(patch_path: &str) -> Result<bool, ()> {
     let path: &Path = Path::new(&patch_path);
@@ -90,7 +93,7 @@ pub async fn check_patch_activated(patch_path: &str) -> Result<bool, ()> {
             )
             .expect("huh");
 
-            let ppu_spu_hash = "PPU-a787b532b03b2ebc3970a1d405639b05bec1a506";
+            let ppu_spu_hash = PPU_HASH;
             if let Some(existing_entry) = serde_parse.get(ppu_spu_hash) {
                 if existing_entry == &entry_v1 || existing_entry == &entry_v2 {
                     return Ok(true);
@@ -143,7 +146,7 @@ pub async fn activate_patch(patch_path: &str) -> Result<bool, ()> {
             )
             .expect("huh");
 
-            let ppu_spu_hash = "PPU-a787b532b03b2ebc3970a1d405639b05bec1a506";
+            let ppu_spu_hash = PPU_HASH;
             if let Some(mut existing_entry) = serde_parse.get(ppu_spu_hash) {
                 existing_entry = &new_entry;
             } else {
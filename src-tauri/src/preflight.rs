@@ -0,0 +1,283 @@
+use std::collections::HashMap;
+
+use serde::{ser::Serializer, Serialize};
+use sysinfo::System;
+use tauri::AppHandle;
+
+use crate::file_check::find_eboot;
+use crate::file_metadata::{get_cached_checksums, get_cached_metadata};
+use crate::initialize::check_initialized;
+use crate::metadata::{load_metadata, replace_game_id, GAME_IDS};
+use crate::patches::check_patch_activated;
+use crate::rpcs3::{min_rpcs3_version, validate_rpcs3, ValidationStatus};
+use crate::rpcs3_directory::{resolve_rpcs3_directory, Rpcs3Directory};
+
+// The digital version needs the licence of the Full Boost DLC-free PSN release
+const RAP_PATH: &str = "dev_hdd0/home/00000001/exdata/JP0700-NPJB00512_00-FULLBOOST000100A.rap";
+const RAP_MD5: &str = "4c266afc905455958a38a9c5c8590634";
+// Any firmware install has it, rpcs3 can't boot anything without it
+const FIRMWARE_FILE: &str = "dev_flash/sys/external/liblv2.sprx";
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Directory(#[from] crate::rpcs3_directory::Error),
+    #[error("{0} is not a Full Boost game id")]
+    UnknownGame(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreflightCheck {
+    Rpcs3Valid,
+    Rpcs3Version,
+    Firmware,
+    GamePresent,
+    RapFile,
+    ModFiles,
+    PsarcCache,
+    PatchActivated,
+    Rpcs3NotRunning,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreflightStatus {
+    Passed,
+    Failed,
+    // Doesn't apply to this game, or couldn't be checked because an earlier check failed
+    Skipped,
+}
+
+// What the frontend should offer to fix a failed check
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FixAction {
+    SelectRpcs3,
+    UpdateRpcs3,
+    InstallFirmware,
+    InstallGame,
+    InstallRap,
+    UpdateModFiles,
+    Initialize,
+    ActivatePatch,
+    StopRpcs3,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightItem {
+    pub check: PreflightCheck,
+    pub status: PreflightStatus,
+    pub detail: Option<String>,
+    pub fix: Option<FixAction>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightReport {
+    pub game_id: String,
+    pub ready: bool,
+    pub items: Vec<PreflightItem>,
+}
+
+impl PreflightItem {
+    fn new(check: PreflightCheck, passed: bool, fix: FixAction) -> PreflightItem {
+        PreflightItem {
+            check,
+            status: match passed {
+                true => PreflightStatus::Passed,
+                false => PreflightStatus::Failed,
+            },
+            detail: None,
+            fix: (!passed).then_some(fix),
+        }
+    }
+
+    fn skipped(check: PreflightCheck, detail: &str) -> PreflightItem {
+        PreflightItem {
+            check,
+            status: PreflightStatus::Skipped,
+            detail: Some(detail.to_string()),
+            fix: None,
+        }
+    }
+
+    fn with_detail(mut self, detail: Option<String>) -> PreflightItem {
+        self.detail = detail;
+        self
+    }
+}
+
+// Mod files for the game whose md5 doesn't match metadata.json, missing ones included.
+// Goes through the metadata cache so only files modified since the last check are hashed again.
+async fn outdated_mod_files(
+    app: &AppHandle,
+    rpcs3_directory: &Rpcs3Directory,
+    beta: bool,
+    game_id: &str,
+) -> std::result::Result<Vec<String>, String> {
+    let metadata = load_metadata(app, beta)?;
+    let files: Vec<_> = metadata
+        .files_for(game_id)
+        .map(|file| {
+            let path = rpcs3_directory.join(replace_game_id(&file.path, game_id));
+            (file, path.display().to_string())
+        })
+        .collect();
+    let checksums: HashMap<String, String> = get_cached_metadata(
+        app,
+        files.iter().map(|(_, path)| path.clone()).collect(),
+        false,
+    )
+    .await
+    .map_err(|_| "failed to read the file metadata cache".to_string())?
+    .into_iter()
+    .map(|file_metadata| (file_metadata.path, file_metadata.checksum))
+    .collect();
+
+    Ok(files
+        .into_iter()
+        .filter(|(file, path)| checksums.get(path) != Some(&file.md5))
+        .map(|(file, _)| file.name.clone())
+        .collect())
+}
+
+// Every prerequisite for launching a game in one report, failed items say how to fix them
+#[tauri::command]
+pub async fn preflight_check(
+    app: AppHandle,
+    full_path: &str,
+    game_id: &str,
+    beta: bool,
+    rpcs3_directory_override: Option<String>,
+) -> Result<PreflightReport> {
+    if !GAME_IDS.contains(&game_id) {
+        return Err(Error::UnknownGame(game_id.to_string()));
+    }
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())?;
+    let mut items: Vec<PreflightItem> = Vec::new();
+
+    let min_version = min_rpcs3_version(&app, beta);
+    let validation = validate_rpcs3(full_path, min_version.as_ref()).await;
+    let is_rpcs3 = validation.valid || validation.status == ValidationStatus::Outdated;
+    items.push(
+        PreflightItem::new(PreflightCheck::Rpcs3Valid, is_rpcs3, FixAction::SelectRpcs3)
+            .with_detail((!is_rpcs3).then(|| format!("{:?}", validation.status))),
+    );
+    items.push(match (is_rpcs3, validation.status) {
+        (false, _) => PreflightItem::skipped(PreflightCheck::Rpcs3Version, "rpcs3 is not valid"),
+        (true, ValidationStatus::Running) => {
+            PreflightItem::skipped(PreflightCheck::Rpcs3Version, "rpcs3 is running")
+        }
+        (true, ValidationStatus::UnknownVersion) => PreflightItem::skipped(
+            PreflightCheck::Rpcs3Version,
+            "rpcs3 didn't report its version",
+        ),
+        (true, status) => PreflightItem::new(
+            PreflightCheck::Rpcs3Version,
+            status != ValidationStatus::Outdated,
+            FixAction::UpdateRpcs3,
+        )
+        .with_detail(validation.version.as_ref().map(|version| {
+            match &validation.min_version {
+                Some(min_version) => format!("{} (minimum {})", version.raw, min_version.raw),
+                None => version.raw.clone(),
+            }
+        })),
+    });
+
+    items.push(PreflightItem::new(
+        PreflightCheck::Firmware,
+        rpcs3_directory.join(FIRMWARE_FILE).is_file(),
+        FixAction::InstallFirmware,
+    ));
+
+    let eboot = find_eboot(&app, &rpcs3_directory, game_id).await;
+    items.push(
+        PreflightItem::new(
+            PreflightCheck::GamePresent,
+            eboot.is_some(),
+            FixAction::InstallGame,
+        )
+        .with_detail(eboot.map(|eboot| eboot.display().to_string())),
+    );
+
+    items.push(match game_id {
+        "NPJB00512" => {
+            let rap_path = rpcs3_directory.join(RAP_PATH).display().to_string();
+            let valid_rap = get_cached_checksums(&app, vec![rap_path.clone()])
+                .await
+                .get(&rap_path)
+                .is_some_and(|checksum| checksum == RAP_MD5);
+            PreflightItem::new(PreflightCheck::RapFile, valid_rap, FixAction::InstallRap)
+        }
+        _ => PreflightItem::skipped(
+            PreflightCheck::RapFile,
+            "only the digital version needs a RAP",
+        ),
+    });
+
+    items.push(
+        match outdated_mod_files(&app, &rpcs3_directory, beta, game_id).await {
+            Ok(outdated) => PreflightItem::new(
+                PreflightCheck::ModFiles,
+                outdated.is_empty(),
+                FixAction::UpdateModFiles,
+            )
+            .with_detail((!outdated.is_empty()).then(|| outdated.join(", "))),
+            Err(err) => {
+                PreflightItem::new(PreflightCheck::ModFiles, false, FixAction::UpdateModFiles)
+                    .with_detail(Some(err))
+            }
+        },
+    );
+
+    let initialized = check_initialized(app.clone(), full_path, rpcs3_directory_override.clone())
+        .await
+        .unwrap_or(false);
+    items.push(PreflightItem::new(
+        PreflightCheck::PsarcCache,
+        initialized,
+        FixAction::Initialize,
+    ));
+
+    let patch_activated =
+        check_patch_activated(&rpcs3_directory.patch_config_path().display().to_string())
+            .await
+            .unwrap_or(false);
+    items.push(PreflightItem::new(
+        PreflightCheck::PatchActivated,
+        patch_activated,
+        FixAction::ActivatePatch,
+    ));
+
+    // Any rpcs3, a second instance refuses to start no matter which installation it is
+    let system = System::new_all();
+    let running = system.processes_by_name("rpcs3").next().is_some();
+    items.push(PreflightItem::new(
+        PreflightCheck::Rpcs3NotRunning,
+        !running,
+        FixAction::StopRpcs3,
+    ));
+
+    Ok(PreflightReport {
+        game_id: game_id.to_string(),
+        ready: items
+            .iter()
+            .all(|item| item.status != PreflightStatus::Failed),
+        items,
+    })
+}
@@ -1,180 +1,732 @@
-use std::fs::File;
-use std::io::{LineWriter, Write};
+use std::collections::{HashMap, VecDeque};
 use std::path::PathBuf;
 use std::process::Stdio;
 
-use configparser::ini::Ini;
-use tauri::path::BaseDirectory;
+use serde::{ser::Serializer, Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
-use crate::os::{get_os, OS};
+use crate::rclone_conf::ensure_merged_conf;
+use crate::remote_hooks::{get_remote_overrides, override_env_name};
+use crate::tools::{ensure_tool, Tool};
 
-pub async fn rclone(
-    app: &AppHandle,
-    command: &str,
-    remote: &str,
-    remote_path: &str,
-    target_path: &str,
-    additional_flags: &str,
-    exclude_items: Vec<String>,
-    listener_id: &str,
-) -> Result<bool, ()> {
-    let rclone_name = match get_os() {
-        OS::Windows => "rclone-win.exe",
-        OS::Linux => "rclone-linux",
-        OS::Macos => "rclone-mac",
-    };
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Conf(#[from] crate::rclone_conf::Error),
+    #[error(transparent)]
+    Tool(#[from] crate::tools::Error),
+    #[error("invalid rclone option: {0}")]
+    InvalidOption(String),
+    #[error("remote pre-flight failed: {0}")]
+    Hook(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+// Number of stderr lines kept in RcloneResult for display
+const STDERR_TAIL_LINES: usize = 20;
 
-    let rclone_path = app
-        .path()
-        .resolve(
-            format!("tools/rclone/{}", rclone_name),
-            BaseDirectory::AppData,
+// Flags that are set through the typed fields of RcloneOptions (or by rclone() itself),
+// these are rejected in extra_args so the same flag can't be passed twice with different values
+const RESERVED_FLAGS: [&str; 17] = [
+    "--transfers",
+    "--checkers",
+    "--contimeout",
+    "--timeout",
+    "--retries",
+    "--low-level-retries",
+    "--bwlimit",
+    "--fast-list",
+    "--delete-before",
+    "--delete-during",
+    "--delete-after",
+    "--dry-run",
+    "--config",
+    "--filter",
+    "--filter-from",
+    "--exclude-from",
+    "--include-from",
+];
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RcloneErrorReason {
+    AuthExpired,
+    QuotaExceeded,
+    NotFound,
+    NetworkTimeout,
+    DiskFull,
+    PermissionDenied,
+    // The local target path is missing, not the remote files
+    TargetNotFound,
+    Unknown,
+}
+
+impl RcloneErrorReason {
+    // Whether retrying the same operation against another mirror could succeed.
+    // Disk and permission errors are on the local side, every mirror would fail the same way.
+    pub fn should_failover(&self) -> bool {
+        match self {
+            RcloneErrorReason::AuthExpired
+            | RcloneErrorReason::QuotaExceeded
+            | RcloneErrorReason::NotFound
+            | RcloneErrorReason::NetworkTimeout => true,
+            RcloneErrorReason::DiskFull
+            | RcloneErrorReason::PermissionDenied
+            | RcloneErrorReason::TargetNotFound
+            | RcloneErrorReason::Unknown => false,
+        }
+    }
+
+    fn is_local(&self) -> bool {
+        matches!(
+            self,
+            RcloneErrorReason::DiskFull
+                | RcloneErrorReason::PermissionDenied
+                | RcloneErrorReason::TargetNotFound
         )
-        .expect("failed to resolve resource");
+    }
+}
 
-    if get_os() == OS::Linux {
-        async_process::Command::new("chmod")
-            .arg("+x")
-            .arg(&rclone_path)
-            .output()
-            .await
-            .expect("chmod failed!");
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RcloneResult {
+    pub success: bool,
+    pub exit_code: Option<i32>,
+    pub stderr_tail: Vec<String>,
+    pub error_reason: Option<RcloneErrorReason>,
+    pub should_failover: bool,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DeleteMode {
+    Before,
+    During,
+    After,
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FilterKind {
+    Include,
+    Exclude,
+}
+
+// A single rclone filter rule, passed as "--filter '+ pattern'" or "--filter '- pattern'".
+// Rules are evaluated by rclone in the order they are given.
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FilterRule {
+    pub kind: FilterKind,
+    pub pattern: String,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RcloneOptions {
+    pub transfers: Option<u32>,
+    pub checkers: Option<u32>,
+    // Connect timeout, in seconds
+    pub contimeout: Option<u64>,
+    // IO idle timeout, in seconds
+    pub timeout: Option<u64>,
+    pub retries: Option<u32>,
+    pub low_level_retries: Option<u32>,
+    // Passed as is, e.g. "10M", "off" or a timetable like "08:00,512k 19:00,off"
+    pub bwlimit: Option<String>,
+    #[serde(default)]
+    pub fast_list: bool,
+    pub delete_mode: Option<DeleteMode>,
+    #[serde(default)]
+    pub dry_run: bool,
+    // Any other flags, one argv entry per item, e.g. ["--stats", "1s", "--stats-file-name-length", "0"]
+    #[serde(default)]
+    pub extra_args: Vec<String>,
+}
+
+impl RcloneOptions {
+    pub fn validate(&self) -> Result<()> {
+        if self.transfers == Some(0) {
+            return Err(Error::InvalidOption("transfers must be at least 1".to_string()));
+        }
+        if self.checkers == Some(0) {
+            return Err(Error::InvalidOption("checkers must be at least 1".to_string()));
+        }
+
+        if let Some(bwlimit) = &self.bwlimit {
+            let valid = !bwlimit.trim().is_empty()
+                && bwlimit
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || " .:,-|".contains(c));
+            if !valid {
+                return Err(Error::InvalidOption(format!("invalid bwlimit '{}'", bwlimit)));
+            }
+        }
+
+        for arg in self.extra_args.iter() {
+            if arg.trim().is_empty() {
+                return Err(Error::InvalidOption("extra args must not be empty".to_string()));
+            }
+
+            // Both "--flag value" and "--flag=value" forms are checked
+            let flag = arg.split('=').next().unwrap_or(arg);
+            if RESERVED_FLAGS.contains(&flag) {
+                return Err(Error::InvalidOption(format!(
+                    "{} must be set through its own option",
+                    flag
+                )));
+            }
+        }
+
+        Ok(())
     }
 
-    let rclone_conf_path = app
-        .path()
-        .resolve("tools/rclone.conf", BaseDirectory::AppData)
-        .expect("failed to resolve resource");
+    pub fn to_args(&self) -> Vec<String> {
+        let mut args: Vec<String> = Vec::new();
 
-    let exclusion_list_path = app
-        .path()
-        .resolve("exclude_files.txt", BaseDirectory::Temp)
-        .unwrap();
+        if let Some(transfers) = self.transfers {
+            args.push("--transfers".to_string());
+            args.push(transfers.to_string());
+        }
+        if let Some(checkers) = self.checkers {
+            args.push("--checkers".to_string());
+            args.push(checkers.to_string());
+        }
+        if let Some(contimeout) = self.contimeout {
+            args.push("--contimeout".to_string());
+            args.push(format!("{}s", contimeout));
+        }
+        if let Some(timeout) = self.timeout {
+            args.push("--timeout".to_string());
+            args.push(format!("{}s", timeout));
+        }
+        if let Some(retries) = self.retries {
+            args.push("--retries".to_string());
+            args.push(retries.to_string());
+        }
+        if let Some(low_level_retries) = self.low_level_retries {
+            args.push("--low-level-retries".to_string());
+            args.push(low_level_retries.to_string());
+        }
+        if let Some(bwlimit) = &self.bwlimit {
+            args.push("--bwlimit".to_string());
+            args.push(bwlimit.clone());
+        }
+        if self.fast_list {
+            args.push("--fast-list".to_string());
+        }
+        if let Some(delete_mode) = self.delete_mode {
+            args.push(
+                match delete_mode {
+                    DeleteMode::Before => "--delete-before",
+                    DeleteMode::During => "--delete-during",
+                    DeleteMode::After => "--delete-after",
+                }
+                .to_string(),
+            );
+        }
+        if self.dry_run {
+            args.push("--dry-run".to_string());
+        }
 
-    // Create a new exclusion list file
-    let file = File::create(&exclusion_list_path).unwrap();
-    let mut writer = LineWriter::new(file);
+        args.extend(self.extra_args.iter().cloned());
+        args
+    }
+}
 
-    for line in exclude_items.iter() {
-        writer.write_all(format!("{}\n", line).as_bytes()).unwrap()
+// Same presets as src/lib/rclone.ts, for updates that are driven from the backend
+const PROGRESS_ARGS: [&str; 5] = ["--verbose", "--stats", "1s", "--stats-file-name-length", "0"];
+
+impl RcloneOptions {
+    pub fn copy() -> RcloneOptions {
+        RcloneOptions {
+            contimeout: Some(60),
+            timeout: Some(300),
+            retries: Some(3),
+            low_level_retries: Some(10),
+            fast_list: true,
+            extra_args: ["--no-update-modtime"]
+                .into_iter()
+                .chain(PROGRESS_ARGS)
+                .map(|arg| arg.to_string())
+                .collect(),
+            ..Default::default()
+        }
     }
 
-    // Refetch cookie for teracloud
-    if remote == "teracloud" {
-        // parse the conf
-        let mut config = Ini::new();
-        config.load(&rclone_conf_path).unwrap();
+    pub fn sync() -> RcloneOptions {
+        RcloneOptions {
+            transfers: Some(4),
+            checkers: Some(8),
+            contimeout: Some(60),
+            timeout: Some(300),
+            retries: Some(3),
+            low_level_retries: Some(10),
+            fast_list: true,
+            delete_mode: Some(DeleteMode::During),
+            extra_args: PROGRESS_ARGS.iter().map(|arg| arg.to_string()).collect(),
+            ..Default::default()
+        }
+    }
 
-        // https://wani.teracloud.jp/ds/dav/11f24a9855df6b18/ --> WebDav link
-        let url = config.get(remote, "url").unwrap();
+    pub fn sync_ignore_size() -> RcloneOptions {
+        let mut options = RcloneOptions::sync();
+        options.extra_args.insert(0, "--ignore-size".to_string());
+        options
+    }
+}
 
-        // https://wani.teracloud.jp/v2/api/share/public/11f24a9855df6b18 --> Share link
-        let share_url = url.replace("/ds/dav/", "/v2/api/share/public/");
+impl FilterRule {
+    pub fn excludes(patterns: &[String]) -> Vec<FilterRule> {
+        patterns
+            .iter()
+            .map(|pattern| FilterRule {
+                kind: FilterKind::Exclude,
+                pattern: pattern.clone(),
+            })
+            .collect()
+    }
+}
+
+pub fn filter_args(filters: &[FilterRule]) -> Result<Vec<String>> {
+    let mut args: Vec<String> = Vec::new();
+
+    for filter in filters.iter() {
+        if filter.pattern.trim().is_empty() || filter.pattern.contains('\n') {
+            return Err(Error::InvalidOption(format!(
+                "invalid filter pattern '{}'",
+                filter.pattern
+            )));
+        }
+
+        let prefix = match filter.kind {
+            FilterKind::Include => "+",
+            FilterKind::Exclude => "-",
+        };
+        args.push("--filter".to_string());
+        args.push(format!("{} {}", prefix, filter.pattern));
+    }
 
-        let client = reqwest::Client::new();
-        let res = client
-            .post(share_url)
-            .header("Content-Type", "application/x-www-form-urlencoded")
-            .send()
+    Ok(args)
+}
+
+// Local errors are matched first when a single line mentions several problems.
+// The remote is always the source and target_path the destination, a missing file or directory
+// only counts as NotFound when the line is about the remote side.
+fn classify_error_line(line: &str, target_path: &str) -> Option<RcloneErrorReason> {
+    let line = line.to_lowercase().replace('\\', "/");
+    let matches = |patterns: &[&str]| patterns.iter().any(|pattern| line.contains(pattern));
+    let target_path = target_path.to_lowercase().replace('\\', "/");
+    let target_path = target_path.trim_end_matches('/');
+    let is_target_line = matches(&["destination"])
+        || (!target_path.is_empty() && target_path != "." && line.contains(target_path));
+
+    if matches(&["no space left on device", "not enough space on the disk", "disk quota exceeded"]) {
+        Some(RcloneErrorReason::DiskFull)
+    } else if matches(&["permission denied", "access is denied", "operation not permitted"]) {
+        Some(RcloneErrorReason::PermissionDenied)
+    } else if matches(&[
+        "no such file or directory",
+        "cannot find the path specified",
+        "cannot find the file specified",
+    ]) || (is_target_line && matches(&["directory not found", "file not found"]))
+    {
+        Some(RcloneErrorReason::TargetNotFound)
+    } else if matches(&[
+        "invalid_grant",
+        "token expired",
+        "couldn't fetch token",
+        "401 unauthorized",
+        "error 401",
+        "authentication failed",
+    ]) {
+        Some(RcloneErrorReason::AuthExpired)
+    } else if matches(&[
+        "quotaexceeded",
+        "ratelimitexceeded",
+        "rate limit exceeded",
+        "too many requests",
+        "error 429",
+    ]) {
+        Some(RcloneErrorReason::QuotaExceeded)
+    } else if matches(&["error 404", "404 not found"])
+        || (matches(&["source"]) && matches(&["directory not found", "file not found"]))
+        || matches(&["object not found"])
+    {
+        Some(RcloneErrorReason::NotFound)
+    } else if matches(&[
+        "i/o timeout",
+        "timed out",
+        "timeout awaiting",
+        "client.timeout exceeded",
+        "context deadline exceeded",
+        "connection reset",
+        "connection refused",
+        "no such host",
+        "network is unreachable",
+        "tls handshake",
+    ]) {
+        Some(RcloneErrorReason::NetworkTimeout)
+    } else {
+        None
+    }
+}
+
+// A local error on any line wins, a full disk is often logged after the retry and timeout noise it
+// caused. Otherwise the first remote error is kept.
+fn pick_error_reason(
+    current: Option<RcloneErrorReason>,
+    line_reason: Option<RcloneErrorReason>,
+) -> Option<RcloneErrorReason> {
+    match (current, line_reason) {
+        (Some(current), _) if current.is_local() => Some(current),
+        (_, Some(line_reason)) if line_reason.is_local() => Some(line_reason),
+        (None, line_reason) => line_reason,
+        (current, _) => current,
+    }
+}
+
+// Executable, merged conf and pre-flight overrides of a remote, shared by every rclone invocation
+pub struct RcloneContext {
+    pub conf_path: PathBuf,
+    executable: PathBuf,
+    remote: String,
+    overrides: HashMap<String, String>,
+}
+
+impl RcloneContext {
+    pub async fn prepare(app: &AppHandle, remote: &str) -> Result<RcloneContext> {
+        let executable = ensure_tool(app, Tool::Rclone).await?;
+
+        // Central rclone.conf with the user's own remotes and overrides merged in
+        let conf_path = ensure_merged_conf(app)?;
+
+        // Cookies, tokens or signed urls that the remote needs before it can be used
+        let overrides = get_remote_overrides(app, remote, &conf_path)
             .await
-            .expect("api request failed!");
-
-        let cookie = res
-            .headers()
-            .get("Set-Cookie")
-            .unwrap()
-            .to_str()
-            .unwrap()
-            .to_owned();
-        config
-            .set(
-                remote,
-                "headers",
-                Option::from(format!("Cookie,\"{}\"", cookie)),
-            )
-            .unwrap();
-        config
-            .write(&rclone_conf_path)
-            .expect("rclone config save failed");
-    }
-
-    let arg_pairs: Vec<&str> = additional_flags.split("--").collect();
-
-    let mut cmd = Command::new(rclone_path);
-
-    cmd.arg(command)
-        .arg("--progress")
-        .arg("--exclude-from")
-        .arg(
-            &exclusion_list_path
-                .clone()
-                .into_os_string()
-                .to_str()
-                .unwrap(),
-        );
+            .map_err(Error::Hook)?;
 
-    for pair in arg_pairs {
-        let trimmed = pair.trim();
-        if !trimmed.is_empty() {
-            let parts: Vec<&str> = trimmed.split(' ').collect();
-            cmd.arg(format!("--{}", parts[0]));
-            if parts.len() > 1 {
-                cmd.arg(parts[1]);
-            }
+        Ok(RcloneContext {
+            conf_path,
+            executable,
+            remote: remote.to_string(),
+            overrides,
+        })
+    }
+
+    pub fn command(&self, subcommand: &str) -> Command {
+        let mut cmd = Command::new(&self.executable);
+        cmd.arg(subcommand).arg("--config").arg(&self.conf_path);
+        cmd
+    }
+
+    // Kept separate from command() so the command can be printed before credentials are added
+    pub fn apply_overrides(&self, cmd: &mut Command) {
+        for (key, value) in self.overrides.iter() {
+            cmd.env(override_env_name(&self.remote, key), value);
         }
     }
+}
+
+pub async fn rclone(
+    app: &AppHandle,
+    command: &str,
+    remote: &str,
+    remote_path: &str,
+    target_path: &str,
+    options: RcloneOptions,
+    filters: Vec<FilterRule>,
+    listener_id: &str,
+) -> Result<RcloneResult> {
+    options.validate()?;
+    let filter_args = filter_args(&filters)?;
 
-    cmd.arg(format!("{}", remote_path))
-        .arg(format!("{}", target_path));
+    let context = RcloneContext::prepare(app, remote).await?;
+    let mut cmd = context.command(command);
+
+    cmd.arg("--progress")
+        .args(filter_args)
+        .args(options.to_args())
+        .arg(remote_path)
+        .arg(target_path);
 
     println!("{}", format!("{:?}", cmd).replace("\"", ""));
+    context.apply_overrides(&mut cmd);
 
     // Specify that we want the command's standard output piped back to us.
     // By default, standard input/output/error will be inherited from the
     // current process (for example, this means that standard input will
     // come from the keyboard and standard output/error will go directly to
     // the terminal if this process is invoked from the command line).
-    cmd.stdout(Stdio::piped());
+    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
 
-    let mut child = cmd.spawn().expect("failed to spawn command");
+    let mut child = cmd.spawn()?;
 
     let stdout = child
         .stdout
         .take()
         .expect("child did not have a handle to stdout");
+    let stderr = child
+        .stderr
+        .take()
+        .expect("child did not have a handle to stderr");
 
-    let mut reader = BufReader::new(stdout).lines();
+    // Drain stderr on its own task, otherwise rclone can block on a full pipe while we wait on stdout.
+    // Only the tail is kept, the error reason is picked from every line.
+    let target_path = target_path.to_string();
+    let stderr_task = tokio::spawn(async move {
+        let mut reader = BufReader::new(stderr).lines();
+        let mut tail: VecDeque<String> = VecDeque::with_capacity(STDERR_TAIL_LINES);
+        let mut error_reason: Option<RcloneErrorReason> = None;
 
-    // Ensure the child process is spawned in the runtime, so it can
-    // make progress on its own while we await for any output.
-    tokio::spawn(async move {
-        let status = child
-            .wait()
-            .await
-            .expect("child process encountered an error");
+        while let Ok(Some(line)) = reader.next_line().await {
+            println!("{}", line);
+            error_reason =
+                pick_error_reason(error_reason, classify_error_line(&line, &target_path));
+            if tail.len() == STDERR_TAIL_LINES {
+                tail.pop_front();
+            }
+            tail.push_back(line);
+        }
 
-        println!("child status was: {}", status);
+        (Vec::from(tail), error_reason)
     });
 
-    let mut execution_success = true;
+    let mut reader = BufReader::new(stdout).lines();
+
     let event_name = format!("rclone_{}", listener_id).to_string();
     app.emit(&event_name, "start")
         .expect("failed to emit progress!");
-    
-    while let Some(line) = reader.next_line().await.unwrap_or(Some(String::new())) {
+
+    while let Ok(Some(line)) = reader.next_line().await {
         app.emit(&event_name, format!("\r{}", line))
             .expect("failed to emit progress!");
         println!("\r{}", line);
-
-        if line.to_lowercase().contains("errors:") {
-            execution_success = false
-        }
     }
     app.emit(&event_name, "end")
         .expect("failed to emit progress!");
 
-    Ok(execution_success)
+    let status = child.wait().await?;
+    println!("child status was: {}", status);
+
+    let (stderr_tail, stderr_reason) = stderr_task.await.unwrap_or_default();
+    let success = status.success();
+    // Exit codes 3 and 4 don't say whether the remote or the target is missing, without a
+    // classified stderr line the reason stays unknown and nothing fails over
+    let error_reason = if success {
+        None
+    } else {
+        Some(stderr_reason.unwrap_or(RcloneErrorReason::Unknown))
+    };
+
+    Ok(RcloneResult {
+        success,
+        exit_code: status.code(),
+        stderr_tail,
+        should_failover: error_reason.map_or(false, |reason| reason.should_failover()),
+        error_reason,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn to_args_follows_the_typed_fields() {
+        let options = RcloneOptions {
+            transfers: Some(4),
+            contimeout: Some(60),
+            bwlimit: Some("10M".to_string()),
+            fast_list: true,
+            delete_mode: Some(DeleteMode::During),
+            dry_run: true,
+            extra_args: vec!["--stats".to_string(), "1s".to_string()],
+            ..Default::default()
+        };
+        assert_eq!(
+            options.to_args(),
+            [
+                "--transfers",
+                "4",
+                "--contimeout",
+                "60s",
+                "--bwlimit",
+                "10M",
+                "--fast-list",
+                "--delete-during",
+                "--dry-run",
+                "--stats",
+                "1s"
+            ]
+        );
+        assert!(RcloneOptions::default().to_args().is_empty());
+    }
+
+    #[test]
+    fn validate_rejects_reserved_flags_in_extra_args() {
+        for arg in ["--transfers", "--transfers=8", "--dry-run"] {
+            let options = RcloneOptions {
+                extra_args: vec![arg.to_string()],
+                ..Default::default()
+            };
+            assert!(options.validate().is_err(), "{} was accepted", arg);
+        }
+        assert!(RcloneOptions::sync().validate().is_ok());
+        assert!(RcloneOptions::copy().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_checks_counts_and_bwlimit() {
+        let zero_transfers = RcloneOptions {
+            transfers: Some(0),
+            ..Default::default()
+        };
+        assert!(zero_transfers.validate().is_err());
+
+        let bwlimit = |bwlimit: &str| RcloneOptions {
+            bwlimit: Some(bwlimit.to_string()),
+            ..Default::default()
+        };
+        assert!(bwlimit("08:00,512k 19:00,off").validate().is_ok());
+        assert!(bwlimit("10M|1M").validate().is_ok());
+        assert!(bwlimit(" ").validate().is_err());
+        assert!(bwlimit("10M; rm -rf").validate().is_err());
+    }
+
+    #[test]
+    fn filter_args_keep_rule_order() {
+        let filters = vec![
+            FilterRule {
+                kind: FilterKind::Include,
+                pattern: "/USRDIR/**".to_string(),
+            },
+            FilterRule {
+                kind: FilterKind::Exclude,
+                pattern: "*".to_string(),
+            },
+        ];
+        assert_eq!(
+            filter_args(&filters).unwrap(),
+            ["--filter", "+ /USRDIR/**", "--filter", "- *"]
+        );
+        assert!(filter_args(&FilterRule::excludes(&["a\nb".to_string()])).is_err());
+    }
+
+    #[test]
+    fn classify_error_line_matches_known_errors() {
+        let cases = [
+            (
+                "ERROR : file.psarc: Failed to copy: write /mnt/file.psarc: no space left on device",
+                Some(RcloneErrorReason::DiskFull),
+            ),
+            (
+                "ERROR : open C:\\rpcs3\\file.psarc: Access is denied.",
+                Some(RcloneErrorReason::PermissionDenied),
+            ),
+            (
+                "Failed to copy: couldn't fetch token: invalid_grant",
+                Some(RcloneErrorReason::AuthExpired),
+            ),
+            (
+                "googleapi: Error 403: User Rate Limit Exceeded, rateLimitExceeded",
+                Some(RcloneErrorReason::QuotaExceeded),
+            ),
+            (
+                "ERROR : : error reading source root directory: directory not found",
+                Some(RcloneErrorReason::NotFound),
+            ),
+            (
+                "Get \"https://example.com\": dial tcp: i/o timeout",
+                Some(RcloneErrorReason::NetworkTimeout),
+            ),
+            ("INFO  : Transferred: 1 / 1, 100%", None),
+        ];
+        for (line, reason) in cases {
+            assert_eq!(classify_error_line(line, "/mnt/rpcs3"), reason, "{}", line);
+        }
+    }
+
+    #[test]
+    fn missing_target_is_a_local_error() {
+        let cases = [
+            (
+                "ERROR : Local file system at /mnt/rpcs3/dev_hdd0: error reading destination root directory: directory not found",
+                "/mnt/rpcs3/dev_hdd0",
+            ),
+            (
+                "ERROR : open C:\\rpcs3\\dev_hdd0\\game: The system cannot find the path specified.",
+                "C:\\rpcs3\\dev_hdd0\\game",
+            ),
+            ("ERROR : stat /mnt/rpcs3: no such file or directory", "/mnt/rpcs3"),
+            ("ERROR : /mnt/rpcs3/USRDIR: directory not found", "/mnt/rpcs3/"),
+        ];
+        for (line, target_path) in cases {
+            let reason = classify_error_line(line, target_path);
+            assert_eq!(reason, Some(RcloneErrorReason::TargetNotFound), "{}", line);
+            assert!(!reason.unwrap().should_failover());
+        }
+        assert_eq!(
+            classify_error_line("ERROR : : directory not found", "/mnt/rpcs3"),
+            None
+        );
+    }
+
+    #[test]
+    fn timeouts_need_a_network_error() {
+        let cases = [
+            "net/http: timeout awaiting response headers",
+            "read tcp 10.0.0.2:51234->1.2.3.4:443: i/o timeout",
+            "context deadline exceeded (Client.Timeout exceeded while awaiting headers)",
+        ];
+        for line in cases {
+            assert_eq!(
+                classify_error_line(line, "/mnt/rpcs3"),
+                Some(RcloneErrorReason::NetworkTimeout),
+                "{}",
+                line
+            );
+        }
+        assert_eq!(
+            classify_error_line("INFO  : --timeout 5m0s, --contimeout 1m0s", "/mnt/rpcs3"),
+            None
+        );
+    }
+
+    #[test]
+    fn local_errors_outrank_earlier_remote_errors() {
+        let reason = [
+            "Get \"https://example.com\": dial tcp: i/o timeout",
+            "ERROR : Attempt 1/3 failed with 1 errors",
+            "ERROR : write /mnt/file.psarc: no space left on device",
+            "ERROR : 404 Not Found",
+        ]
+        .into_iter()
+        .fold(None, |current, line| {
+            pick_error_reason(current, classify_error_line(line, "/mnt/rpcs3"))
+        });
+        assert_eq!(reason, Some(RcloneErrorReason::DiskFull));
+        assert!(!reason.unwrap().should_failover());
+
+        let reason = ["error 429", "i/o timeout"]
+            .into_iter()
+            .fold(None, |current, line| {
+                pick_error_reason(current, classify_error_line(line, "/mnt/rpcs3"))
+            });
+        assert_eq!(reason, Some(RcloneErrorReason::QuotaExceeded));
+    }
 }
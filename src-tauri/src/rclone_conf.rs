@@ -0,0 +1,485 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use futures_util::future::join_all;
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::rclone::RcloneContext;
+
+const DEFAULT_TEST_TIMEOUT_SECS: u64 = 15;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("invalid rclone.conf: {0}")]
+    InvalidConf(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+pub struct ConfSection {
+    pub name: String,
+    pub entries: Vec<(String, String)>,
+}
+
+impl ConfSection {
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|(entry_key, _)| entry_key == key)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn to_map(&self) -> HashMap<String, String> {
+        self.entries.iter().cloned().collect()
+    }
+}
+
+// Minimal reader/writer for rclone.conf.
+// Values are kept verbatim, generic ini parsers treat ';' as an inline comment and cut cookie headers short.
+#[derive(Default)]
+pub struct RcloneConf {
+    pub sections: Vec<ConfSection>,
+    // Lines that were skipped, rclone itself ignores them with a warning
+    pub warnings: Vec<String>,
+}
+
+impl RcloneConf {
+    pub fn parse(contents: &str) -> RcloneConf {
+        let mut conf = RcloneConf::default();
+
+        for (index, raw_line) in contents.lines().enumerate() {
+            let line = raw_line.trim();
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+
+            if line.starts_with('[') && line.ends_with(']') {
+                conf.sections.push(ConfSection {
+                    name: line[1..line.len() - 1].trim().to_string(),
+                    entries: Vec::new(),
+                });
+                continue;
+            }
+
+            let (Some((key, value)), Some(section)) = (line.split_once('='), conf.sections.last_mut())
+            else {
+                conf.warnings.push(format!(
+                    "line {}: expected 'key = value' inside a [remote] section",
+                    index + 1
+                ));
+                continue;
+            };
+            section
+                .entries
+                .push((key.trim().to_string(), value.trim().to_string()));
+        }
+
+        conf
+    }
+
+    // For confs that are about to be saved, nothing should be silently dropped from those
+    pub fn parse_strict(contents: &str) -> Result<RcloneConf> {
+        let conf = RcloneConf::parse(contents);
+        match conf.warnings.first() {
+            Some(warning) => Err(Error::InvalidConf(warning.clone())),
+            None => Ok(conf),
+        }
+    }
+
+    pub fn read(path: &Path) -> Result<RcloneConf> {
+        if !path.exists() {
+            return Ok(RcloneConf::default());
+        }
+        let conf = RcloneConf::parse(&fs::read_to_string(path)?);
+        for warning in conf.warnings.iter() {
+            println!("Ignoring {} of {}", warning, path.display());
+        }
+        Ok(conf)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&ConfSection> {
+        self.sections.iter().find(|section| section.name == name)
+    }
+
+    // Keys of sections that exist in both are replaced one by one, so an override only needs the keys it changes
+    pub fn merge(&mut self, other: &RcloneConf) {
+        for other_section in other.sections.iter() {
+            match self
+                .sections
+                .iter_mut()
+                .find(|section| section.name == other_section.name)
+            {
+                Some(section) => {
+                    for (key, value) in other_section.entries.iter() {
+                        match section.entries.iter_mut().find(|(entry_key, _)| entry_key == key) {
+                            Some(entry) => entry.1 = value.clone(),
+                            None => section.entries.push((key.clone(), value.clone())),
+                        }
+                    }
+                }
+                None => self.sections.push(ConfSection {
+                    name: other_section.name.clone(),
+                    entries: other_section.entries.clone(),
+                }),
+            }
+        }
+    }
+
+    pub fn to_conf_string(&self) -> String {
+        let mut output = String::new();
+        for section in self.sections.iter() {
+            output.push_str(&format!("[{}]\n", section.name));
+            for (key, value) in section.entries.iter() {
+                output.push_str(&format!("{} = {}\n", key, value));
+            }
+            output.push('\n');
+        }
+        output
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RemoteSource {
+    Central,
+    User,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RcloneRemote {
+    pub name: String,
+    pub remote_type: String,
+    pub source: RemoteSource,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RemoteTestResult {
+    pub name: String,
+    pub success: bool,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+// Distributed with the app and refreshed from remote.json, overwritten on every update
+fn central_conf_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .resolve("tools/rclone/rclone.conf", BaseDirectory::AppData)
+        .expect("failed to resolve resource")
+}
+
+// Kept outside of tools/ so initialize_resources never copies over it
+fn user_conf_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .resolve("rclone.user.conf", BaseDirectory::AppData)
+        .expect("failed to resolve resource")
+}
+
+fn merged_conf_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .resolve("tools/rclone/rclone.merged.conf", BaseDirectory::AppData)
+        .expect("failed to resolve resource")
+}
+
+// Size and modification time of a source conf, None when it doesn't exist
+#[derive(PartialEq, Debug, Serialize, Deserialize)]
+struct SourceFingerprint {
+    len: u64,
+    modified_ns: u128,
+}
+
+impl SourceFingerprint {
+    fn of(path: &Path) -> Option<SourceFingerprint> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified_ns = metadata
+            .modified()
+            .ok()?
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_nanos());
+        Some(SourceFingerprint {
+            len: metadata.len(),
+            modified_ns,
+        })
+    }
+}
+
+// Fingerprints of the central and user conf the merged conf was built from
+type MergedSources = [Option<SourceFingerprint>; 2];
+
+fn read_merged_sources(path: &Path) -> Option<MergedSources> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+// Rebuilds the merged conf whenever the central or user conf changed, appeared or was deleted
+// since it was last written
+pub fn ensure_merged_conf(app: &AppHandle) -> Result<PathBuf> {
+    let central_path = central_conf_path(app);
+    let user_path = user_conf_path(app);
+    let merged_path = merged_conf_path(app);
+    let sources_path = merged_path.with_extension("sources.json");
+
+    let sources: MergedSources = [
+        SourceFingerprint::of(&central_path),
+        SourceFingerprint::of(&user_path),
+    ];
+    if merged_path.exists() && read_merged_sources(&sources_path).as_ref() == Some(&sources) {
+        return Ok(merged_path);
+    }
+
+    let mut conf = RcloneConf::read(&central_path)?;
+    conf.merge(&RcloneConf::read(&user_path)?);
+
+    // Write to a temporary file first, a concurrent rclone call should never see a half written conf
+    if let Some(parent) = merged_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = merged_path.with_extension("tmp");
+    fs::write(&temp_path, conf.to_conf_string())?;
+    fs::rename(&temp_path, &merged_path)?;
+    fs::write(
+        &sources_path,
+        serde_json::to_string(&sources).expect("Error parsing to Json."),
+    )?;
+
+    Ok(merged_path)
+}
+
+#[tauri::command]
+pub async fn list_rclone_remotes(app: AppHandle) -> Result<Vec<RcloneRemote>> {
+    let user_conf = RcloneConf::read(&user_conf_path(&app))?;
+    let merged_conf = RcloneConf::read(&ensure_merged_conf(&app)?)?;
+
+    let remotes = merged_conf
+        .sections
+        .iter()
+        .map(|section| RcloneRemote {
+            name: section.name.clone(),
+            remote_type: section.get("type").unwrap_or_default().to_string(),
+            source: match user_conf.get(&section.name) {
+                Some(_) => RemoteSource::User,
+                None => RemoteSource::Central,
+            },
+        })
+        .collect();
+
+    Ok(remotes)
+}
+
+#[tauri::command]
+pub async fn get_user_rclone_conf(app: AppHandle) -> Result<String> {
+    let user_path = user_conf_path(&app);
+    if !user_path.exists() {
+        return Ok(String::new());
+    }
+    Ok(fs::read_to_string(user_path)?)
+}
+
+#[tauri::command]
+pub async fn save_user_rclone_conf(app: AppHandle, contents: &str) -> Result<()> {
+    let user_conf = RcloneConf::parse_strict(contents)?;
+
+    // New remotes need a type, sections that only override keys of a central remote inherit it
+    let central_conf = RcloneConf::read(&central_conf_path(&app))?;
+    for section in user_conf.sections.iter() {
+        if section.get("type").is_none() && central_conf.get(&section.name).is_none() {
+            return Err(Error::InvalidConf(format!(
+                "remote '{}' is missing a type",
+                section.name
+            )));
+        }
+    }
+
+    fs::write(user_conf_path(&app), contents)?;
+    ensure_merged_conf(&app)?;
+
+    Ok(())
+}
+
+// Adds or replaces a single remote in the user conf, keeping everything else the user wrote
+pub fn save_user_remote(app: &AppHandle, section: ConfSection) -> Result<()> {
+    let user_path = user_conf_path(app);
+    let mut user_conf = RcloneConf::read(&user_path)?;
+    user_conf.sections.retain(|existing| existing.name != section.name);
+    user_conf.sections.push(section);
+
+    fs::write(user_path, user_conf.to_conf_string())?;
+    ensure_merged_conf(app)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn test_rclone_remote(
+    app: AppHandle,
+    remote: &str,
+    timeout_secs: Option<u64>,
+) -> Result<RemoteTestResult> {
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TEST_TIMEOUT_SECS));
+    Ok(test_remote(&app, remote, timeout).await)
+}
+
+#[tauri::command]
+pub async fn test_rclone_remotes(
+    app: AppHandle,
+    timeout_secs: Option<u64>,
+) -> Result<Vec<RemoteTestResult>> {
+    let conf = RcloneConf::read(&ensure_merged_conf(&app)?)?;
+    let timeout = Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_TEST_TIMEOUT_SECS));
+
+    let tests = conf
+        .sections
+        .iter()
+        .map(|section| test_remote(&app, &section.name, timeout));
+
+    Ok(join_all(tests).await)
+}
+
+async fn test_remote(app: &AppHandle, remote: &str, timeout: Duration) -> RemoteTestResult {
+    let started = Instant::now();
+    let result = run_lsd(app, remote, timeout).await;
+
+    RemoteTestResult {
+        name: remote.to_string(),
+        success: result.is_ok(),
+        duration_ms: started.elapsed().as_millis() as u64,
+        error: result.err(),
+    }
+}
+
+async fn run_lsd(app: &AppHandle, remote: &str, timeout: Duration) -> std::result::Result<(), String> {
+    let context = RcloneContext::prepare(app, remote)
+        .await
+        .map_err(|err| err.to_string())?;
+
+    let mut cmd = context.command("lsd");
+    cmd.arg(format!("{}:", remote))
+        .arg("--contimeout")
+        .arg(format!("{}s", timeout.as_secs()))
+        .arg("--retries")
+        .arg("1")
+        .arg("--low-level-retries")
+        .arg("1")
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
+    context.apply_overrides(&mut cmd);
+
+    let output = tokio::time::timeout(timeout, cmd.output())
+        .await
+        .map_err(|_| format!("timed out after {}s", timeout.as_secs()))?
+        .map_err(|err| err.to_string())?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        Err(stderr.lines().last().unwrap_or_default().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CENTRAL_CONF: &str = "
+# mirrors
+[gdrive]
+type = drive
+scope = drive.readonly
+token = {\"access_token\":\"a\"}
+
+[http_mirror]
+type = http
+url = https://example.com/fullboost/
+headers = Cookie,session=abc;path=/
+";
+
+    #[test]
+    fn parse_keeps_values_verbatim() {
+        let conf = RcloneConf::parse(CENTRAL_CONF);
+        assert_eq!(conf.sections.len(), 2);
+        let http = conf.get("http_mirror").unwrap();
+        assert_eq!(http.get("url"), Some("https://example.com/fullboost/"));
+        assert_eq!(http.get("headers"), Some("Cookie,session=abc;path=/"));
+        assert_eq!(
+            conf.get("gdrive").unwrap().get("token"),
+            Some("{\"access_token\":\"a\"}")
+        );
+    }
+
+    #[test]
+    fn parse_skips_unknown_lines() {
+        let conf = RcloneConf::parse("type = drive\n[gdrive]\nnot a key value\ntype = drive\n");
+        assert_eq!(conf.sections.len(), 1);
+        assert_eq!(conf.get("gdrive").unwrap().to_map().len(), 1);
+        assert_eq!(conf.warnings.len(), 2);
+        assert!(conf.warnings[1].starts_with("line 3:"));
+    }
+
+    #[test]
+    fn parse_strict_rejects_unknown_lines() {
+        assert!(RcloneConf::parse_strict("type = drive\n[gdrive]\n").is_err());
+        assert!(RcloneConf::parse_strict("[gdrive]\nnot a key value\n").is_err());
+        assert!(RcloneConf::parse_strict(CENTRAL_CONF).is_ok());
+    }
+
+    #[test]
+    fn fingerprints_notice_changed_and_deleted_sources() {
+        let directory =
+            std::env::temp_dir().join(format!("rclone_conf_test_{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("rclone.user.conf");
+
+        fs::write(&path, "[local]\ntype = local\n").unwrap();
+        let written = SourceFingerprint::of(&path);
+        assert_eq!(written.as_ref().map(|fingerprint| fingerprint.len), Some(21));
+
+        fs::write(&path, "[local]\ntype = local\ncopy_links = true\n").unwrap();
+        assert_ne!(SourceFingerprint::of(&path), written);
+
+        fs::remove_file(&path).unwrap();
+        assert_eq!(SourceFingerprint::of(&path), None);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn merge_overrides_keys_one_by_one() {
+        let mut conf = RcloneConf::parse(CENTRAL_CONF);
+        let user = RcloneConf::parse("[gdrive]\ntoken = mine\n\n[local]\ntype = local\n");
+        conf.merge(&user);
+
+        let gdrive = conf.get("gdrive").unwrap();
+        assert_eq!(gdrive.get("token"), Some("mine"));
+        assert_eq!(gdrive.get("scope"), Some("drive.readonly"));
+        assert_eq!(conf.get("local").unwrap().get("type"), Some("local"));
+    }
+
+    #[test]
+    fn to_conf_string_round_trips() {
+        let conf = RcloneConf::parse(CENTRAL_CONF);
+        let reparsed = RcloneConf::parse(&conf.to_conf_string());
+        assert_eq!(
+            reparsed.get("http_mirror").unwrap().to_map(),
+            conf.get("http_mirror").unwrap().to_map()
+        );
+        assert_eq!(reparsed.sections.len(), conf.sections.len());
+    }
+}
@@ -0,0 +1,307 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::rclone::{filter_args, Error, FilterRule, RcloneContext};
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum PreviewMode {
+    // rclone check --combined, with one_way only files missing or different locally are reported
+    #[serde(rename_all = "camelCase")]
+    Check { one_way: bool },
+    // rclone sync --dry-run, reports exactly what a sync with the same filters would do
+    DryRun,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreviewEntry {
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UpdatePreview {
+    pub added: Vec<PreviewEntry>,
+    pub changed: Vec<PreviewEntry>,
+    pub deleted: Vec<PreviewEntry>,
+    pub errors: Vec<String>,
+    pub download_size: u64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct LsJsonEntry {
+    path: String,
+    size: i64,
+}
+
+#[derive(Deserialize)]
+struct JsonLogLine {
+    level: String,
+    msg: String,
+    #[serde(default)]
+    object: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Change {
+    Added,
+    Changed,
+    Deleted,
+}
+
+// Runs rclone without touching the target, then sizes every change from a listing of the remote
+// (for files that would be downloaded) or from the local file (for files that would be deleted).
+#[tauri::command]
+pub async fn preview_update(
+    app: AppHandle,
+    remote: &str,
+    remote_path: &str,
+    target_path: &str,
+    mode: PreviewMode,
+    filters: Vec<FilterRule>,
+) -> Result<UpdatePreview> {
+    let context = RcloneContext::prepare(&app, remote).await?;
+    let filter_args = filter_args(&filters)?;
+
+    let (changes, mut errors) = match mode {
+        PreviewMode::Check { one_way } => {
+            run_check(&context, remote_path, target_path, &filter_args, one_way).await?
+        }
+        PreviewMode::DryRun => run_dry_run(&context, remote_path, target_path, &filter_args).await?,
+    };
+
+    let (remote_sizes, size_errors) =
+        list_remote_sizes(&context, remote_path, &filter_args).await?;
+    errors.extend(size_errors);
+
+    let mut preview = UpdatePreview {
+        errors,
+        ..Default::default()
+    };
+    for (change, path) in changes {
+        let size = match change {
+            Change::Deleted => std::fs::metadata(Path::new(target_path).join(&path))
+                .map(|metadata| metadata.len())
+                .unwrap_or(0),
+            Change::Added | Change::Changed => remote_sizes.get(&path).copied().unwrap_or(0),
+        };
+        let entry = PreviewEntry { path, size };
+
+        match change {
+            Change::Added => {
+                preview.download_size += size;
+                preview.added.push(entry);
+            }
+            Change::Changed => {
+                preview.download_size += size;
+                preview.changed.push(entry);
+            }
+            Change::Deleted => preview.deleted.push(entry),
+        }
+    }
+
+    Ok(preview)
+}
+
+// https://rclone.org/commands/rclone_check/
+async fn run_check(
+    context: &RcloneContext,
+    remote_path: &str,
+    target_path: &str,
+    filter_args: &[String],
+    one_way: bool,
+) -> Result<(Vec<(Change, String)>, Vec<String>)> {
+    let mut cmd = context.command("check");
+    cmd.args(filter_args).arg("--combined").arg("-");
+    if one_way {
+        cmd.arg("--one-way");
+    }
+    cmd.arg(remote_path).arg(target_path);
+
+    context.apply_overrides(&mut cmd);
+
+    // check exits with an error whenever there are differences, so only the combined report is used
+    let output = cmd.stdin(Stdio::null()).output().await?;
+    let (changes, mut errors) = parse_combined(&String::from_utf8_lossy(&output.stdout));
+
+    if changes.is_empty() && errors.is_empty() && !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        errors.push(stderr.lines().last().unwrap_or_default().to_string());
+    }
+
+    Ok((changes, errors))
+}
+
+async fn run_dry_run(
+    context: &RcloneContext,
+    remote_path: &str,
+    target_path: &str,
+    filter_args: &[String],
+) -> Result<(Vec<(Change, String)>, Vec<String>)> {
+    let mut cmd = context.command("sync");
+    cmd.args(filter_args)
+        .arg("--dry-run")
+        .arg("--use-json-log")
+        .arg("--verbose")
+        .arg(remote_path)
+        .arg(target_path);
+
+    context.apply_overrides(&mut cmd);
+
+    let output = cmd.stdin(Stdio::null()).output().await?;
+    Ok(parse_dry_run_log(
+        &String::from_utf8_lossy(&output.stderr),
+        Path::new(target_path),
+    ))
+}
+
+// "+ path" only on the remote, "- path" only locally, "* path" different, "! path" error, "= path" identical
+fn parse_combined(report: &str) -> (Vec<(Change, String)>, Vec<String>) {
+    let mut changes: Vec<(Change, String)> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    for line in report.lines() {
+        let Some((marker, path)) = line.split_once(' ') else {
+            continue;
+        };
+        match marker {
+            "+" => changes.push((Change::Added, path.to_string())),
+            "*" => changes.push((Change::Changed, path.to_string())),
+            "-" => changes.push((Change::Deleted, path.to_string())),
+            "!" => errors.push(path.to_string()),
+            _ => {}
+        }
+    }
+    (changes, errors)
+}
+
+// A skipped copy is a change when the file already exists in the target, an addition otherwise
+fn parse_dry_run_log(log: &str, target_path: &Path) -> (Vec<(Change, String)>, Vec<String>) {
+    let mut changes: Vec<(Change, String)> = Vec::new();
+    let mut errors: Vec<String> = Vec::new();
+    for line in log.lines() {
+        let Ok(log) = serde_json::from_str::<JsonLogLine>(line) else {
+            continue;
+        };
+
+        // NOTICE: path: Skipped copy as --dry-run is set (size 1.2Mi)
+        if log.msg.starts_with("Skipped copy") {
+            let change = match target_path.join(&log.object).exists() {
+                true => Change::Changed,
+                false => Change::Added,
+            };
+            changes.push((change, log.object));
+        } else if log.msg.starts_with("Skipped delete") {
+            changes.push((Change::Deleted, log.object));
+        } else if log.level == "error" || log.level == "critical" {
+            errors.push(format!("{}: {}", log.object, log.msg));
+        }
+    }
+
+    (changes, errors)
+}
+
+// Sizes stay 0 when the listing fails, the reason ends up in the preview errors
+async fn list_remote_sizes(
+    context: &RcloneContext,
+    remote_path: &str,
+    filter_args: &[String],
+) -> Result<(HashMap<String, u64>, Vec<String>)> {
+    let mut cmd = context.command("lsjson");
+    cmd.args(filter_args)
+        .arg("--recursive")
+        .arg("--files-only")
+        .arg("--no-mimetype")
+        .arg("--no-modtime")
+        .arg(remote_path);
+    context.apply_overrides(&mut cmd);
+
+    let output = cmd.stdin(Stdio::null()).output().await?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Ok((
+            HashMap::new(),
+            vec![format!(
+                "failed to list remote sizes: {}",
+                stderr.lines().last().unwrap_or_default()
+            )],
+        ));
+    }
+
+    match serde_json::from_slice::<Vec<LsJsonEntry>>(&output.stdout) {
+        Ok(entries) => Ok((
+            entries
+                .into_iter()
+                .map(|entry| (entry.path, entry.size.max(0) as u64))
+                .collect(),
+            Vec::new(),
+        )),
+        Err(err) => Ok((
+            HashMap::new(),
+            vec![format!("failed to read remote sizes: {}", err)],
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_combined_sorts_markers() {
+        let report = "= PS3_GAME/USRDIR/same.psarc\n\
+                      + PS3_GAME/USRDIR/new file.psarc\n\
+                      * PS3_GAME/USRDIR/EBOOT.BIN\n\
+                      - PS3_GAME/USRDIR/old.psarc\n\
+                      ! PS3_GAME/USRDIR/broken.psarc\n\
+                      2024/01/01 00:00:00 NOTICE: 1 differences found\n";
+        let (changes, errors) = parse_combined(report);
+        assert_eq!(
+            changes,
+            [
+                (Change::Added, "PS3_GAME/USRDIR/new file.psarc".to_string()),
+                (Change::Changed, "PS3_GAME/USRDIR/EBOOT.BIN".to_string()),
+                (Change::Deleted, "PS3_GAME/USRDIR/old.psarc".to_string()),
+            ]
+        );
+        assert_eq!(errors, ["PS3_GAME/USRDIR/broken.psarc"]);
+    }
+
+    #[test]
+    fn parse_dry_run_log_reads_skipped_operations() {
+        let target =
+            std::env::temp_dir().join(format!("rclone_preview_test_{}", std::process::id()));
+        std::fs::create_dir_all(&target).unwrap();
+        std::fs::write(target.join("EBOOT.BIN"), "old").unwrap();
+
+        let log = [
+            r#"{"level":"notice","msg":"Skipped copy as --dry-run is set (size 3)","object":"EBOOT.BIN"}"#,
+            r#"{"level":"notice","msg":"Skipped copy as --dry-run is set (size 1.2Mi)","object":"new.psarc"}"#,
+            r#"{"level":"notice","msg":"Skipped delete as --dry-run is set (size 10)","object":"old.psarc"}"#,
+            r#"{"level":"error","msg":"Failed to copy: unexpected EOF","object":"broken.psarc"}"#,
+            r#"{"level":"info","msg":"There was nothing to transfer"}"#,
+            "2024/01/01 00:00:00 NOTICE: not a json line",
+        ]
+        .join("\n");
+        let (changes, errors) = parse_dry_run_log(&log, &target);
+        assert_eq!(
+            changes,
+            [
+                (Change::Changed, "EBOOT.BIN".to_string()),
+                (Change::Added, "new.psarc".to_string()),
+                (Change::Deleted, "old.psarc".to_string()),
+            ]
+        );
+        assert_eq!(errors, ["broken.psarc: Failed to copy: unexpected EOF"]);
+
+        std::fs::remove_dir_all(&target).unwrap();
+    }
+}
@@ -0,0 +1,520 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use base64::Engine;
+use minisign_verify::{PublicKey, Signature};
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use reqwest::StatusCode;
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+
+use crate::metadata::{metadata_file_name, Metadata};
+use crate::mirrors::{load_remote_urls, mirrors_file_name, Mirrors, RemoteUrls};
+use crate::rclone_conf::RcloneConf;
+
+// Same key as plugins.updater.pubkey in tauri.conf.json, files are signed with the updater's private key
+const REMOTE_PUBKEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXk6IEMzMEY0RjU1MEIzNjc1OEMKUldTTWRUWUxWVThQdzgxcHZaL2hPaExkcmJXWHNFandSVUh2Vnl1T205OG0rYUJ2aUh5V01MdXUK";
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const FETCH_TIMEOUT: Duration = Duration::from_secs(15);
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Request(#[from] reqwest::Error),
+    #[error(transparent)]
+    Mirrors(#[from] crate::mirrors::Error),
+    #[error("{0} returned {1}")]
+    Status(String, StatusCode),
+    #[error("signature verification failed for {0}: {1}")]
+    Signature(String, String),
+    #[error("invalid {0}: {1}")]
+    Invalid(String, String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RemoteFile {
+    Metadata,
+    Mirrors,
+    RcloneConf,
+}
+
+impl RemoteFile {
+    fn url<'a>(&self, urls: &'a RemoteUrls) -> &'a str {
+        match self {
+            RemoteFile::Metadata => &urls.metadata,
+            RemoteFile::Mirrors => &urls.mirrors,
+            RemoteFile::RcloneConf => &urls.rclone_conf,
+        }
+    }
+
+    fn file_name(&self, beta: bool) -> &'static str {
+        match (self, beta) {
+            (RemoteFile::Metadata, _) => metadata_file_name(beta),
+            (RemoteFile::Mirrors, _) => mirrors_file_name(beta),
+            (RemoteFile::RcloneConf, true) => "rclone-beta.conf",
+            (RemoteFile::RcloneConf, false) => "rclone.conf",
+        }
+    }
+
+    // Where the rest of the app reads the file from
+    fn target_path(&self, app: &AppHandle, beta: bool) -> PathBuf {
+        let path = match self {
+            RemoteFile::RcloneConf => "tools/rclone/rclone.conf",
+            _ => self.file_name(beta),
+        };
+        app.path()
+            .resolve(path, BaseDirectory::AppData)
+            .expect("failed to resolve resource")
+    }
+
+    fn validate(&self, file_name: &str, contents: &str) -> Result<()> {
+        match self {
+            RemoteFile::Metadata => serde_json::from_str::<Metadata>(contents)
+                .map(|_| ())
+                .map_err(|err| Error::Invalid(file_name.to_string(), err.to_string())),
+            RemoteFile::Mirrors => Mirrors::parse(file_name, contents)
+                .map(|_| ())
+                .map_err(Error::from),
+            RemoteFile::RcloneConf => RcloneConf::parse_strict(contents)
+                .map(|_| ())
+                .map_err(|err| Error::Invalid(file_name.to_string(), err.to_string())),
+        }
+    }
+}
+
+// Validators of the last verified copy, kept next to it in app data/remote_cache
+#[derive(Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CacheEntry {
+    url: String,
+    etag: Option<String>,
+    last_modified: Option<String>,
+    fetched_at: Option<String>,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FetchStatus {
+    Updated,
+    NotModified,
+    // The remote wasn't asked, the last known good copy (if any) was put back
+    Cached,
+    Failed,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FetchResult {
+    pub file: RemoteFile,
+    pub status: FetchStatus,
+    pub fetched_at: Option<String>,
+    pub error: Option<String>,
+}
+
+struct CachePaths {
+    body: PathBuf,
+    entry: PathBuf,
+}
+
+fn cache_paths(app: &AppHandle, file_name: &str) -> CachePaths {
+    let directory = app
+        .path()
+        .resolve("remote_cache", BaseDirectory::AppData)
+        .expect("failed to resolve resource");
+    CachePaths {
+        body: directory.join(file_name),
+        entry: directory.join(format!("{}.cache.json", file_name)),
+    }
+}
+
+fn read_entry(path: &Path) -> Option<CacheEntry> {
+    serde_json::from_str(&fs::read_to_string(path).ok()?).ok()
+}
+
+// Temporary file first, a reader should never see half of a file
+fn write_atomic(path: &Path, contents: &[u8]) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("tmp");
+    fs::write(&temp_path, contents)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+// Same scheme as the updater plugin: both the key and the .sig file are base64 encoded minisign text
+fn verify_signature(url: &str, data: &[u8], signature: &str, public_key: &str) -> Result<()> {
+    let decode = |value: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(value.trim())
+            .ok()
+            .and_then(|decoded| String::from_utf8(decoded).ok())
+            .ok_or(Error::Signature(url.to_string(), "not base64".to_string()))
+    };
+    let signature_error =
+        |err: minisign_verify::Error| Error::Signature(url.to_string(), err.to_string());
+
+    let public_key = PublicKey::decode(&decode(public_key)?).map_err(signature_error)?;
+    let signature = Signature::decode(&decode(signature)?).map_err(signature_error)?;
+    public_key
+        .verify(data, &signature, true)
+        .map_err(signature_error)
+}
+
+// Ok(None) when the server says the cached copy is still current
+async fn fetch_verified(
+    client: &reqwest::Client,
+    url: &str,
+    cached: Option<&CacheEntry>,
+) -> Result<Option<(String, CacheEntry)>> {
+    let mut request = client.get(url);
+    if let Some(cached) = cached {
+        if let Some(etag) = &cached.etag {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(last_modified) = &cached.last_modified {
+            request = request.header(IF_MODIFIED_SINCE, last_modified);
+        }
+    }
+
+    let response = request.send().await?;
+    if response.status() == StatusCode::NOT_MODIFIED && cached.is_some() {
+        return Ok(None);
+    }
+    if !response.status().is_success() {
+        return Err(Error::Status(url.to_string(), response.status()));
+    }
+
+    let header = |name| {
+        response
+            .headers()
+            .get(name)
+            .and_then(|value: &reqwest::header::HeaderValue| value.to_str().ok())
+            .map(|value| value.to_string())
+    };
+    let entry = CacheEntry {
+        url: url.to_string(),
+        etag: header(ETAG),
+        last_modified: header(LAST_MODIFIED),
+        fetched_at: Some(chrono::Utc::now().to_rfc3339()),
+    };
+    let body = response.bytes().await?;
+
+    let signature_url = format!("{}.sig", url);
+    let signature_response = client.get(&signature_url).send().await?;
+    if !signature_response.status().is_success() {
+        return Err(Error::Status(signature_url, signature_response.status()));
+    }
+    verify_signature(url, &body, &signature_response.text().await?, REMOTE_PUBKEY)?;
+
+    let contents = String::from_utf8(body.to_vec())
+        .map_err(|err| Error::Invalid(url.to_string(), err.to_string()))?;
+    Ok(Some((contents, entry)))
+}
+
+// Validated before anything is written, a broken download never replaces the last good copy
+fn store_verified(
+    file: RemoteFile,
+    file_name: &str,
+    contents: &str,
+    entry: &CacheEntry,
+    paths: &CachePaths,
+    target_path: &Path,
+) -> Result<()> {
+    file.validate(file_name, contents)?;
+    write_atomic(&paths.body, contents.as_bytes())?;
+    write_atomic(
+        &paths.entry,
+        serde_json::to_string(entry).unwrap_or_default().as_bytes(),
+    )?;
+    write_atomic(target_path, contents.as_bytes())
+}
+
+// initialize_resources puts the bundled copies back on every start, so the last verified copy
+// is restored from remote_cache whenever the remote can't be used.
+fn restore_cached(
+    app: &AppHandle,
+    file: RemoteFile,
+    beta: bool,
+    url: Option<&str>,
+) -> Result<Option<CacheEntry>> {
+    let file_name = file.file_name(beta);
+    restore_from(
+        file,
+        file_name,
+        &cache_paths(app, file_name),
+        &file.target_path(app, beta),
+        url,
+    )
+}
+
+fn restore_from(
+    file: RemoteFile,
+    file_name: &str,
+    paths: &CachePaths,
+    target_path: &Path,
+    url: Option<&str>,
+) -> Result<Option<CacheEntry>> {
+    let Some(entry) = read_entry(&paths.entry) else {
+        return Ok(None);
+    };
+    // remote.json changed with an app update, the cached copy is from somewhere else
+    if url.is_some_and(|url| url != entry.url) {
+        return Ok(None);
+    }
+    let Ok(contents) = fs::read_to_string(&paths.body) else {
+        return Ok(None);
+    };
+    file.validate(file_name, &contents)?;
+    write_atomic(target_path, contents.as_bytes())?;
+    Ok(Some(entry))
+}
+
+async fn refresh(
+    app: &AppHandle,
+    client: &reqwest::Client,
+    file: RemoteFile,
+    beta: bool,
+) -> Result<FetchResult> {
+    let urls = load_remote_urls(app, beta)?;
+    let url = file.url(&urls);
+    let file_name = file.file_name(beta);
+    let paths = cache_paths(app, file_name);
+
+    // Conditional headers only make sense when the copy they describe is still there
+    let cached = read_entry(&paths.entry).filter(|entry| entry.url == url && paths.body.exists());
+
+    match fetch_verified(client, url, cached.as_ref()).await? {
+        None => {
+            let entry = restore_cached(app, file, beta, Some(url))?;
+            Ok(FetchResult {
+                file,
+                status: FetchStatus::NotModified,
+                fetched_at: entry.and_then(|entry| entry.fetched_at),
+                error: None,
+            })
+        }
+        Some((contents, entry)) => {
+            store_verified(
+                file,
+                file_name,
+                &contents,
+                &entry,
+                &paths,
+                &file.target_path(app, beta),
+            )?;
+            Ok(FetchResult {
+                file,
+                status: FetchStatus::Updated,
+                fetched_at: entry.fetched_at,
+                error: None,
+            })
+        }
+    }
+}
+
+pub async fn update_remote_file(
+    app: &AppHandle,
+    file: RemoteFile,
+    beta: bool,
+    fetch_remote: bool,
+) -> FetchResult {
+    let fallback = |error: Option<String>| {
+        let restored = restore_cached(app, file, beta, None);
+        let fetched_at = match &restored {
+            Ok(entry) => entry.as_ref().and_then(|entry| entry.fetched_at.clone()),
+            Err(err) => {
+                println!(
+                    "Failed to restore the cached {}: {}",
+                    file.file_name(beta),
+                    err
+                );
+                None
+            }
+        };
+        FetchResult {
+            file,
+            status: match error {
+                Some(_) => FetchStatus::Failed,
+                None => FetchStatus::Cached,
+            },
+            fetched_at,
+            error,
+        }
+    };
+
+    if !fetch_remote {
+        return fallback(None);
+    }
+
+    let client = match reqwest::Client::builder()
+        .connect_timeout(CONNECT_TIMEOUT)
+        .timeout(FETCH_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(err) => return fallback(Some(err.to_string())),
+    };
+
+    match refresh(app, &client, file, beta).await {
+        Ok(result) => result,
+        Err(err) => {
+            println!("Failed to update {}: {}", file.file_name(beta), err);
+            fallback(Some(err.to_string()))
+        }
+    }
+}
+
+// A failed fetch never touches the current copy, the result says what happened
+#[tauri::command]
+pub async fn fetch_remote_file(
+    app: AppHandle,
+    file: RemoteFile,
+    beta: bool,
+    fetch_remote: bool,
+) -> FetchResult {
+    update_remote_file(&app, file, beta, fetch_remote).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // minisign key and signature of SIGNED_DATA made for these tests, not the release key
+    const TEST_PUBKEY: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IG1pbmlzaWduIHB1YmxpYyBrZXkgMDgwNzA2MDUwNDAzMDIwMQpSV1FCQWdNRUJRWUhDQU9oQjcvenpoQytIWERkR09kTHdKbG41Tll3bTZVTlh4M2NobVFTVlRHNAo=";
+    const TEST_SIGNATURE: &str = "dW50cnVzdGVkIGNvbW1lbnQ6IHNpZ25hdHVyZSBmcm9tIHRhdXJpIHNlY3JldCBrZXkKUlVRQkFnTUVCUVlIQ0FrUkdTSXlmdmIvU05JVjBnRTl0bHZBd2NkSzdrRUlvSUl4QlpaSlVVM1ZUdGFJdXVRTU1RSmNmc0dpbTBCTjdOeHV5STI3ejJWS2hicVBDenY2OWc0PQp0cnVzdGVkIGNvbW1lbnQ6IHRpbWVzdGFtcDoxNzAwMDAwMDAwCWZpbGU6bWlycm9ycy5qc29uCnNJZ2pTRjNkNUZha0t4WmtiZklsVnJRWmhoVmRkQWtKM1pWOURyT1hoVDRneFZRdDAvRC9OZ2ZMTjRCV2VMbnVlcW4xbUR3MUdMU08zQWIxS3J4K0R3PT0K";
+    const SIGNED_DATA: &[u8] = br#"{"mirrorGroups":[]}"#;
+    const URL: &str = "https://example.com/mirrors.json";
+    const MIRRORS: &str = r#"{"mirrorGroups":[{"name":"normal","testUrl":"https://google.com","remotes":[{"name":"Google Drive","rcloneName":"googledrive"}]}]}"#;
+
+    fn temp_cache(name: &str) -> (PathBuf, CachePaths) {
+        let directory =
+            std::env::temp_dir().join(format!("remote_fetch_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        fs::create_dir_all(&directory).unwrap();
+        let paths = CachePaths {
+            body: directory.join("remote_cache/mirrors.json"),
+            entry: directory.join("remote_cache/mirrors.json.cache.json"),
+        };
+        (directory, paths)
+    }
+
+    fn entry(url: &str) -> CacheEntry {
+        CacheEntry {
+            url: url.to_string(),
+            etag: Some("\"abc\"".to_string()),
+            fetched_at: Some("2024-01-01T00:00:00+00:00".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn signatures_are_checked_against_the_key() {
+        assert!(verify_signature(URL, SIGNED_DATA, TEST_SIGNATURE, TEST_PUBKEY).is_ok());
+        assert!(
+            verify_signature(URL, b"{\"mirrorGroups\":[1]}", TEST_SIGNATURE, TEST_PUBKEY).is_err()
+        );
+        // Signed with another key than the release one
+        assert!(verify_signature(URL, SIGNED_DATA, TEST_SIGNATURE, REMOTE_PUBKEY).is_err());
+        assert!(matches!(
+            verify_signature(URL, SIGNED_DATA, "not a signature", TEST_PUBKEY),
+            Err(Error::Signature(_, _))
+        ));
+    }
+
+    #[test]
+    fn invalid_downloads_are_never_written() {
+        let (directory, paths) = temp_cache("invalid");
+        let target_path = directory.join("mirrors.json");
+        fs::write(&target_path, MIRRORS).unwrap();
+
+        let stored = store_verified(
+            RemoteFile::Mirrors,
+            "mirrors.json",
+            r#"{"mirrorGroups":[]}"#,
+            &entry(URL),
+            &paths,
+            &target_path,
+        );
+        assert!(stored.is_err());
+        assert_eq!(fs::read_to_string(&target_path).unwrap(), MIRRORS);
+        assert!(!paths.body.exists());
+        assert!(!paths.entry.exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn the_last_good_copy_is_restored() {
+        let (directory, paths) = temp_cache("restore");
+        let target_path = directory.join("mirrors.json");
+        store_verified(
+            RemoteFile::Mirrors,
+            "mirrors.json",
+            MIRRORS,
+            &entry(URL),
+            &paths,
+            &target_path,
+        )
+        .unwrap();
+
+        // initialize_resources put the bundled copy back
+        fs::write(&target_path, "bundled").unwrap();
+        let restored = restore_from(
+            RemoteFile::Mirrors,
+            "mirrors.json",
+            &paths,
+            &target_path,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            restored.and_then(|entry| entry.fetched_at).as_deref(),
+            Some("2024-01-01T00:00:00+00:00")
+        );
+        assert_eq!(fs::read_to_string(&target_path).unwrap(), MIRRORS);
+
+        // Cached from a url remote.json no longer points to
+        fs::write(&target_path, "bundled").unwrap();
+        let restored = restore_from(
+            RemoteFile::Mirrors,
+            "mirrors.json",
+            &paths,
+            &target_path,
+            Some("https://example.org/mirrors.json"),
+        )
+        .unwrap();
+        assert!(restored.is_none());
+        assert_eq!(fs::read_to_string(&target_path).unwrap(), "bundled");
+
+        // A cached copy that no longer validates isn't restored either
+        fs::write(&paths.body, "{").unwrap();
+        assert!(restore_from(
+            RemoteFile::Mirrors,
+            "mirrors.json",
+            &paths,
+            &target_path,
+            None
+        )
+        .is_err());
+        assert_eq!(fs::read_to_string(&target_path).unwrap(), "bundled");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
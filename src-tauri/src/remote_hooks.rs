@@ -0,0 +1,354 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::sync::Mutex;
+
+use crate::mirrors::load_mirrors;
+use crate::rclone_conf::RcloneConf;
+
+// Refresh a bit before the upstream expiry so a long transfer doesn't start with an almost expired value
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+type RemoteSection = HashMap<String, String>;
+
+// Selected per remote through the "hook" key of a remote in mirrors.json, e.g.
+// { "name": "Teracloud", "rcloneName": "teracloud", "hook": { "type": "webDavShareCookie" } }
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum HookConfig {
+    WebDavShareCookie,
+    #[serde(rename_all = "camelCase")]
+    OauthTokenRefresh {
+        token_url: String,
+    },
+    #[serde(rename_all = "camelCase")]
+    SignedUrl {
+        endpoint: String,
+    },
+}
+
+// Config values to override for a remote, passed to rclone as RCLONE_CONFIG_{REMOTE}_{KEY}
+// so the shared rclone.conf doesn't have to be rewritten on every call.
+#[derive(Clone)]
+pub struct HookOutput {
+    pub overrides: HashMap<String, String>,
+    pub expires_in: Duration,
+}
+
+#[async_trait]
+pub trait RemoteHook: Send + Sync {
+    async fn prepare(&self, remote: &str, section: &RemoteSection) -> Result<HookOutput, String>;
+}
+
+struct CachedOverrides {
+    overrides: HashMap<String, String>,
+    expires_at: Instant,
+}
+
+// Managed in app state, keyed by rclone remote name.
+// The lock is held while a hook runs so parallel copies of the same remote only refresh once.
+#[derive(Default)]
+pub struct RemoteHookCache(Mutex<HashMap<String, CachedOverrides>>);
+
+fn section_value(section: &RemoteSection, key: &str) -> Result<String, String> {
+    section
+        .get(key)
+        .cloned()
+        .ok_or(format!("missing '{}' in rclone.conf", key))
+}
+
+// tcs_ticket=65b7eb423ad4;Version=1;Path=/ds/dav/;Max-Age=3600;Secure
+fn cookie_max_age(cookie: &str) -> Option<u64> {
+    cookie
+        .split(';')
+        .filter_map(|attribute| attribute.trim().strip_prefix("Max-Age="))
+        .find_map(|value| value.parse::<u64>().ok())
+}
+
+// Public WebDAV shares (e.g. teracloud) only accept requests carrying a short-lived ticket cookie,
+// which is handed out by the share API.
+pub struct WebDavShareCookie;
+
+#[async_trait]
+impl RemoteHook for WebDavShareCookie {
+    async fn prepare(&self, _remote: &str, section: &RemoteSection) -> Result<HookOutput, String> {
+        // https://wani.teracloud.jp/ds/dav/11f24a9855df6b18/ --> WebDav link
+        let url = section_value(section, "url")?;
+
+        // https://wani.teracloud.jp/v2/api/share/public/11f24a9855df6b18 --> Share link
+        let share_url = url.replace("/ds/dav/", "/v2/api/share/public/");
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(share_url)
+            .header("Content-Type", "application/x-www-form-urlencoded")
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?;
+
+        let cookie = response
+            .headers()
+            .get("Set-Cookie")
+            .and_then(|header| header.to_str().ok())
+            .ok_or("share api did not return a cookie".to_string())?
+            .to_owned();
+
+        Ok(HookOutput {
+            overrides: HashMap::from([("headers".to_string(), format!("Cookie,\"{}\"", cookie))]),
+            expires_in: Duration::from_secs(cookie_max_age(&cookie).unwrap_or(3600)),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+struct OauthTokenResponse {
+    access_token: String,
+    token_type: String,
+    expires_in: u64,
+}
+
+// Refreshes the access token with the refresh token from rclone.conf,
+// the remote needs its own client_id and client_secret for this to work.
+pub struct OauthTokenRefresh {
+    pub token_url: String,
+}
+
+#[async_trait]
+impl RemoteHook for OauthTokenRefresh {
+    async fn prepare(&self, _remote: &str, section: &RemoteSection) -> Result<HookOutput, String> {
+        let token: serde_json::Value = serde_json::from_str(&section_value(section, "token")?)
+            .map_err(|err| err.to_string())?;
+        let refresh_token = token["refresh_token"]
+            .as_str()
+            .ok_or("missing refresh_token in rclone.conf token".to_string())?
+            .to_string();
+        let client_id = section_value(section, "client_id")?;
+        let client_secret = section_value(section, "client_secret")?;
+
+        let client = reqwest::Client::new();
+        let response = client
+            .post(&self.token_url)
+            .form(&[
+                ("grant_type", "refresh_token"),
+                ("refresh_token", refresh_token.as_str()),
+                ("client_id", client_id.as_str()),
+                ("client_secret", client_secret.as_str()),
+            ])
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?
+            .error_for_status()
+            .map_err(|err| err.to_string())?;
+
+        let body = response.text().await.map_err(|err| err.to_string())?;
+        let refreshed: OauthTokenResponse =
+            serde_json::from_str(&body).map_err(|err| err.to_string())?;
+
+        let expiry = chrono::Utc::now() + chrono::Duration::seconds(refreshed.expires_in as i64);
+        let new_token = serde_json::json!({
+            "access_token": refreshed.access_token,
+            "token_type": refreshed.token_type,
+            "refresh_token": refresh_token,
+            "expiry": expiry.to_rfc3339(),
+        });
+
+        Ok(HookOutput {
+            overrides: HashMap::from([("token".to_string(), new_token.to_string())]),
+            expires_in: Duration::from_secs(refreshed.expires_in),
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SignedUrlResponse {
+    url: String,
+    expires_in: u64,
+}
+
+// Asks a signing endpoint for a time limited url and points the remote at it,
+// for http remotes backed by object storage that doesn't allow anonymous reads.
+pub struct SignedUrl {
+    pub endpoint: String,
+}
+
+#[async_trait]
+impl RemoteHook for SignedUrl {
+    async fn prepare(&self, remote: &str, _section: &RemoteSection) -> Result<HookOutput, String> {
+        let client = reqwest::Client::new();
+        let response = client
+            .get(&self.endpoint)
+            .query(&[("remote", remote)])
+            .timeout(REQUEST_TIMEOUT)
+            .send()
+            .await
+            .map_err(|err| err.to_string())?
+            .error_for_status()
+            .map_err(|err| err.to_string())?;
+
+        let body = response.text().await.map_err(|err| err.to_string())?;
+        let signed: SignedUrlResponse =
+            serde_json::from_str(&body).map_err(|err| err.to_string())?;
+
+        Ok(HookOutput {
+            overrides: HashMap::from([("url".to_string(), signed.url)]),
+            expires_in: Duration::from_secs(signed.expires_in),
+        })
+    }
+}
+
+fn build_hook(config: &HookConfig) -> Box<dyn RemoteHook> {
+    match config {
+        HookConfig::WebDavShareCookie => Box::new(WebDavShareCookie),
+        HookConfig::OauthTokenRefresh { token_url } => Box::new(OauthTokenRefresh {
+            token_url: token_url.clone(),
+        }),
+        HookConfig::SignedUrl { endpoint } => Box::new(SignedUrl {
+            endpoint: endpoint.clone(),
+        }),
+    }
+}
+
+// Looks for the remote in both the normal and beta mirrors.json, the hook is the same regardless of channel
+fn find_hook_config(app: &AppHandle, remote: &str) -> Option<HookConfig> {
+    [false, true]
+        .into_iter()
+        .filter_map(|beta| load_mirrors(app, beta).ok())
+        .find_map(|mirrors| {
+            mirrors
+                .remotes()
+                .find(|(_, mirror_remote)| mirror_remote.rclone_name == remote)
+                .and_then(|(_, mirror_remote)| mirror_remote.hook.clone())
+        })
+}
+
+// Remotes without an explicit hook still get one based on their type in rclone.conf
+fn default_hook_config(section: &RemoteSection) -> Option<HookConfig> {
+    let remote_type = section.get("type").cloned().unwrap_or_default();
+    let url = section.get("url").cloned().unwrap_or_default();
+
+    if remote_type == "webdav" && url.contains("/ds/dav/") {
+        return Some(HookConfig::WebDavShareCookie);
+    }
+
+    None
+}
+
+pub async fn get_remote_overrides(
+    app: &AppHandle,
+    remote: &str,
+    rclone_conf_path: &Path,
+) -> Result<HashMap<String, String>, String> {
+    let conf = RcloneConf::read(rclone_conf_path).map_err(|err| err.to_string())?;
+    let Some(section) = conf.get(remote).map(|section| section.to_map()) else {
+        return Ok(HashMap::new());
+    };
+
+    let Some(hook_config) = find_hook_config(app, remote).or_else(|| default_hook_config(&section))
+    else {
+        return Ok(HashMap::new());
+    };
+
+    let cache = app.state::<RemoteHookCache>();
+    let mut cache = cache.0.lock().await;
+    if let Some(cached) = cache.get(remote) {
+        if cached.expires_at > Instant::now() {
+            return Ok(cached.overrides.clone());
+        }
+    }
+
+    let output = build_hook(&hook_config).prepare(remote, &section).await?;
+    cache.insert(
+        remote.to_string(),
+        CachedOverrides {
+            overrides: output.overrides.clone(),
+            expires_at: Instant::now() + output.expires_in.saturating_sub(EXPIRY_MARGIN),
+        },
+    );
+
+    Ok(output.overrides)
+}
+
+// https://rclone.org/docs/#config-file
+pub fn override_env_name(remote: &str, key: &str) -> String {
+    format!("RCLONE_CONFIG_{}_{}", remote, key)
+        .to_uppercase()
+        .replace('-', "_")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cookie_max_age() {
+        assert_eq!(
+            cookie_max_age("tcs_ticket=65b7eb423ad4;Version=1;Path=/ds/dav/;Max-Age=3600;Secure"),
+            Some(3600)
+        );
+        assert_eq!(
+            cookie_max_age("tcs_ticket=65b7; Max-Age=120; Secure"),
+            Some(120)
+        );
+        assert_eq!(cookie_max_age("tcs_ticket=65b7;Max-Age=soon"), None);
+        assert_eq!(cookie_max_age("tcs_ticket=65b7;Path=/"), None);
+    }
+
+    #[test]
+    fn deserializes_hook_configs() {
+        let hook: HookConfig = serde_json::from_str(r#"{ "type": "webDavShareCookie" }"#).unwrap();
+        assert!(matches!(hook, HookConfig::WebDavShareCookie));
+
+        let hook: HookConfig = serde_json::from_str(
+            r#"{ "type": "oauthTokenRefresh", "tokenUrl": "https://example.com/token" }"#,
+        )
+        .unwrap();
+        assert!(
+            matches!(hook, HookConfig::OauthTokenRefresh { token_url } if token_url == "https://example.com/token")
+        );
+
+        let hook: HookConfig = serde_json::from_str(
+            r#"{ "type": "signedUrl", "endpoint": "https://example.com/sign" }"#,
+        )
+        .unwrap();
+        assert!(
+            matches!(hook, HookConfig::SignedUrl { endpoint } if endpoint == "https://example.com/sign")
+        );
+
+        assert!(serde_json::from_str::<HookConfig>(r#"{ "type": "oauthTokenRefresh" }"#).is_err());
+        assert!(serde_json::from_str::<HookConfig>(r#"{ "type": "ftpLogin" }"#).is_err());
+    }
+
+    #[test]
+    fn teracloud_webdav_gets_the_cookie_hook_by_default() {
+        let section = RemoteSection::from([
+            ("type".to_string(), "webdav".to_string()),
+            (
+                "url".to_string(),
+                "https://wani.teracloud.jp/ds/dav/11f24a9855df6b18/".to_string(),
+            ),
+        ]);
+        assert!(matches!(
+            default_hook_config(&section),
+            Some(HookConfig::WebDavShareCookie)
+        ));
+
+        let section = RemoteSection::from([("type".to_string(), "drive".to_string())]);
+        assert!(default_hook_config(&section).is_none());
+    }
+
+    #[test]
+    fn override_env_names_follow_rclone() {
+        assert_eq!(
+            override_env_name("tera-cloud", "headers"),
+            "RCLONE_CONFIG_TERA_CLOUD_HEADERS"
+        );
+    }
+}
@@ -1,65 +1,314 @@
-use async_process::Command;
-use same_file::is_same_file;
+use std::cmp::Ordering;
 use std::path::Path;
-use sysinfo::System;
+use std::process::Stdio;
+use std::time::Duration;
+
+use same_file::is_same_file;
+use serde::Serialize;
+use sysinfo::{Pid, Process, Signal, System};
 use tauri::utils::platform::current_exe;
+use tauri::AppHandle;
+use tokio::process::Command;
 
+use crate::metadata::load_metadata;
 use crate::os::{get_os, OS};
+use crate::rpcs3_directory::{is_flatpak, FLATPAK_APP_ID};
 
-// Two ways of checking.
-// If the process is running, check if the process's executing path is the same as the path passed in.
-// If it is not running, try run the --version command, and see if it fails.
-#[tauri::command]
-pub async fn validate_rpcs3_executable(full_path: &str) -> Result<bool, ()> {
-    let exe_path = current_exe().unwrap();
-    let rpcs3_path = Path::new(full_path);
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+const STOP_POLL_INTERVAL: Duration = Duration::from_millis(250);
 
-    // Check if rpcs3_path is the same as exe_path
-    if (!rpcs3_path.exists() || is_same_file(&rpcs3_path, &exe_path).unwrap_or(false)) {
-        return Ok(false);
+// "RPCS3 v0.0.31-16271-8ef9fa2c Alpha | HEAD" from --version, or "0.0.31-16271-8ef9fa2c" from flatpak.
+// Release builds don't always have a build number or commit.
+#[derive(Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rpcs3Version {
+    pub major: u32,
+    pub minor: u32,
+    pub patch: u32,
+    pub build: Option<u32>,
+    pub commit: Option<String>,
+    pub raw: String,
+}
+
+impl Rpcs3Version {
+    pub fn parse(text: &str) -> Option<Rpcs3Version> {
+        text.split_whitespace().find_map(|token| {
+            let version = token.strip_prefix('v').unwrap_or(token);
+            let mut parts = version.split('-');
+            let numbers: Vec<u32> = parts
+                .next()?
+                .split('.')
+                .map(|number| number.parse().ok())
+                .collect::<Option<_>>()?;
+            let [major, minor, patch] = numbers[..] else {
+                return None;
+            };
+            let build = parts.next().and_then(|build| build.parse().ok());
+            let commit = parts
+                .next()
+                .filter(|commit| commit.chars().all(|c| c.is_ascii_hexdigit()))
+                .map(str::to_string);
+            Some(Rpcs3Version {
+                major,
+                minor,
+                patch,
+                build,
+                commit,
+                raw: token.to_string(),
+            })
+        })
+    }
+
+    // The file version on Windows, either the same text as --version or four numbers
+    // like "0.0.31.16271" with the build as the last one
+    fn parse_file_version(text: &str) -> Option<Rpcs3Version> {
+        Rpcs3Version::parse(text).or_else(|| {
+            let text = text.trim();
+            let numbers: Vec<u32> = text
+                .split('.')
+                .map(|number| number.parse().ok())
+                .collect::<Option<_>>()?;
+            let [major, minor, patch, build] = numbers[..] else {
+                return None;
+            };
+            Some(Rpcs3Version {
+                major,
+                minor,
+                patch,
+                build: (build != 0).then_some(build),
+                commit: None,
+                raw: text.to_string(),
+            })
+        })
     }
 
+    // Build numbers are only compared when both sides have one, a release build
+    // without one isn't considered older than a build of the same version.
+    pub fn is_older_than(&self, other: &Rpcs3Version) -> bool {
+        match (self.major, self.minor, self.patch).cmp(&(other.major, other.minor, other.patch)) {
+            Ordering::Less => true,
+            Ordering::Greater => false,
+            Ordering::Equal => match (self.build, other.build) {
+                (Some(build), Some(other_build)) => build < other_build,
+                _ => false,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ValidationStatus {
+    Valid,
+    NotFound,
+    // The path points at the launcher itself
+    LauncherExecutable,
+    NotExecutable,
+    // This executable is running, it won't answer --version so the version isn't checked
+    Running,
+    // It runs but --version printed nothing usable and, on Windows, the file has no version resource
+    UnknownVersion,
+    Outdated,
+    UnsupportedOs,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rpcs3Validation {
+    pub valid: bool,
+    // Usable but the version wasn't compared against the minimum, the frontend warns about it
+    pub verified: bool,
+    pub status: ValidationStatus,
+    pub version: Option<Rpcs3Version>,
+    pub min_version: Option<Rpcs3Version>,
+}
+
+impl Rpcs3Validation {
+    fn new(
+        status: ValidationStatus,
+        version: Option<Rpcs3Version>,
+        min_version: Option<&Rpcs3Version>,
+    ) -> Rpcs3Validation {
+        Rpcs3Validation {
+            valid: matches!(
+                status,
+                ValidationStatus::Valid
+                    | ValidationStatus::Running
+                    | ValidationStatus::UnknownVersion
+            ),
+            verified: matches!(status, ValidationStatus::Valid | ValidationStatus::Outdated),
+            status,
+            version,
+            min_version: min_version.cloned(),
+        }
+    }
+}
+
+// AppImages are matched through APPIMAGE, Flatpak processes run from inside the sandbox
+// and are matched through FLATPAK_ID instead.
+fn is_same_executable(process: &Process, rpcs3_path: &Path) -> bool {
+    let exe_matches = process
+        .exe()
+        .is_some_and(|exe| is_same_file(rpcs3_path, exe).unwrap_or(false));
+    let appimage_matches = process
+        .environ()
+        .iter()
+        .filter_map(|variable| variable.strip_prefix("APPIMAGE="))
+        .any(|path| is_same_file(rpcs3_path, path).unwrap_or(false));
+    let flatpak_matches = is_flatpak(rpcs3_path)
+        && process
+            .environ()
+            .iter()
+            .any(|variable| variable.strip_prefix("FLATPAK_ID=") == Some(FLATPAK_APP_ID));
+    exe_matches || appimage_matches || flatpak_matches
+}
+
+fn matching_processes(system: &System, rpcs3_path: &Path) -> Vec<Pid> {
+    system
+        .processes_by_name("rpcs3")
+        .filter(|process| is_same_executable(process, rpcs3_path))
+        .map(|process| process.pid())
+        .collect()
+}
+
+// Any rpcs3 process, not just the first one
+pub fn is_executable_running(rpcs3_path: &Path) -> bool {
     let s = System::new_all();
-    for process in s.processes_by_name("rpcs3") {
-        let exe_path = process.exe().unwrap_or(Path::new(""));
-        let is_same_file = is_same_file(rpcs3_path, exe_path).unwrap_or(false);
-        return Ok(is_same_file);
+    !matching_processes(&s, rpcs3_path).is_empty()
+}
+
+// None when it couldn't be started at all, otherwise whatever it printed
+async fn version_output(full_path: &str) -> Option<String> {
+    let mut cmd = match is_flatpak(Path::new(full_path)) {
+        // Starting the sandbox just for --version is slow, flatpak already knows the version
+        true => {
+            let mut cmd = Command::new("flatpak");
+            cmd.arg("info").arg(FLATPAK_APP_ID);
+            cmd
+        }
+        false => {
+            let mut cmd = Command::new(full_path);
+            cmd.arg("--version");
+            cmd
+        }
+    };
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(VERSION_TIMEOUT, cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    // flatpak info prints "Version: 0.0.31-16271-8ef9fa2c" among other lines
+    Some(
+        stdout
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("Version:"))
+            .map(str::to_string)
+            .unwrap_or(stdout),
+    )
+}
+
+// rpcs3.exe started without a console doesn't always print anything for --version,
+// the version resource of the file can be read without running it
+async fn file_version(full_path: &str) -> Option<Rpcs3Version> {
+    let script = format!(
+        "$info = (Get-Item -LiteralPath '{}').VersionInfo; $info.ProductVersion; $info.FileVersion",
+        full_path.replace('\'', "''")
+    );
+    let mut cmd = Command::new("powershell");
+    cmd.args(["-NoProfile", "-NonInteractive", "-Command", &script])
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(VERSION_TIMEOUT, cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(Rpcs3Version::parse_file_version)
+}
+
+pub fn min_rpcs3_version(app: &AppHandle, beta: bool) -> Option<Rpcs3Version> {
+    let metadata = load_metadata(app, beta)
+        .map_err(|err| println!("Failed to load the minimum rpcs3 version: {}", err))
+        .ok()?;
+    Rpcs3Version::parse(&metadata.min_rpcs3_version?)
+}
+
+pub async fn validate_rpcs3(
+    full_path: &str,
+    min_version: Option<&Rpcs3Version>,
+) -> Rpcs3Validation {
+    let exe_path = current_exe().unwrap();
+    let rpcs3_path = Path::new(full_path);
+
+    if !rpcs3_path.exists() {
+        return Rpcs3Validation::new(ValidationStatus::NotFound, None, min_version);
+    }
+    if is_same_file(rpcs3_path, &exe_path).unwrap_or(false) {
+        return Rpcs3Validation::new(ValidationStatus::LauncherExecutable, None, min_version);
+    }
+    if is_executable_running(rpcs3_path) {
+        return Rpcs3Validation::new(ValidationStatus::Running, None, min_version);
     }
 
-    let valid_rpcs3 = match get_os() {
-        OS::Windows => match Command::new(full_path).arg("--version").output().await {
-            Ok(_) => true,
-            Err(_) => false,
-        },
+    match get_os() {
+        OS::Windows => {}
         OS::Linux => {
-            let chmod_valid = match Command::new("chmod")
+            let chmod_valid = Command::new("chmod")
                 .arg("+x")
                 .arg(full_path)
                 .output()
                 .await
-            {
-                Ok(_) => true,
-                Err(_) => false,
-            };
-
-            let rpcs3_valid = match Command::new(full_path).arg("--version").output().await {
-                Ok(_) => true,
-                Err(_) => false,
-            };
-
-            chmod_valid && rpcs3_valid
+                .is_ok();
+            if !chmod_valid {
+                return Rpcs3Validation::new(ValidationStatus::NotExecutable, None, min_version);
+            }
         }
         OS::Macos => {
             println!("macOS is not supported");
-            false
+            return Rpcs3Validation::new(ValidationStatus::UnsupportedOs, None, min_version);
         }
         _ => {
             println!("Unsupported OS type");
-            false
+            return Rpcs3Validation::new(ValidationStatus::UnsupportedOs, None, min_version);
         }
+    }
+
+    let Some(output) = version_output(full_path).await else {
+        return Rpcs3Validation::new(ValidationStatus::NotExecutable, None, min_version);
+    };
+    let version = match (Rpcs3Version::parse(&output), get_os()) {
+        (Some(version), _) => Some(version),
+        (None, OS::Windows) => file_version(full_path).await,
+        (None, _) => None,
+    };
+    let Some(version) = version else {
+        return Rpcs3Validation::new(ValidationStatus::UnknownVersion, None, min_version);
+    };
+    let status = match min_version.is_some_and(|min_version| version.is_older_than(min_version)) {
+        true => ValidationStatus::Outdated,
+        false => ValidationStatus::Valid,
     };
+    Rpcs3Validation::new(status, Some(version), min_version)
+}
 
-    Ok(valid_rpcs3)
+// Checks the path, whether it's already running, and the version from --version
+// against minRpcs3Version in metadata.json.
+#[tauri::command]
+pub async fn validate_rpcs3_executable(
+    app: AppHandle,
+    full_path: &str,
+    beta: bool,
+) -> Result<Rpcs3Validation, ()> {
+    let min_version = min_rpcs3_version(&app, beta);
+    Ok(validate_rpcs3(full_path, min_version.as_ref()).await)
 }
 
 #[tauri::command]
@@ -70,3 +319,163 @@ pub async fn check_rpcs3_running() -> Result<bool, ()> {
     }
     Ok(false)
 }
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StoppedProcess {
+    pub pid: u32,
+    pub executable_path: Option<String>,
+    // Whether the graceful request could be sent at all
+    pub graceful_requested: bool,
+    pub killed: bool,
+    pub stopped: bool,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StopReport {
+    pub processes: Vec<StoppedProcess>,
+    pub all_stopped: bool,
+}
+
+// SIGTERM on Linux and macOS. On Windows taskkill without /F posts WM_CLOSE,
+// which rpcs3 handles like closing the window.
+async fn request_graceful_stop(process: &Process) -> bool {
+    match get_os() {
+        OS::Windows => Command::new("taskkill")
+            .arg("/PID")
+            .arg(process.pid().as_u32().to_string())
+            .output()
+            .await
+            .is_ok_and(|output| output.status.success()),
+        _ => process.kill_with(Signal::Term).unwrap_or(false),
+    }
+}
+
+// Asks every rpcs3 process running from full_path to close and kills the ones still there
+// after the timeout, e.g. a frozen --no-gui window. Other rpcs3 installations are left alone.
+#[tauri::command]
+pub async fn stop_rpcs3(full_path: &str, timeout_ms: Option<u64>) -> Result<StopReport, ()> {
+    let rpcs3_path = Path::new(full_path);
+    let timeout = timeout_ms
+        .map(Duration::from_millis)
+        .unwrap_or(STOP_TIMEOUT);
+    let mut system = System::new_all();
+
+    let mut stopped: Vec<StoppedProcess> = Vec::new();
+    for pid in matching_processes(&system, rpcs3_path) {
+        let Some(process) = system.process(pid) else {
+            continue;
+        };
+        stopped.push(StoppedProcess {
+            pid: pid.as_u32(),
+            executable_path: process.exe().map(|exe| exe.display().to_string()),
+            graceful_requested: request_graceful_stop(process).await,
+            killed: false,
+            stopped: false,
+        });
+    }
+
+    let deadline = tokio::time::Instant::now() + timeout;
+    loop {
+        system.refresh_processes();
+        for entry in stopped.iter_mut() {
+            entry.stopped = system.process(Pid::from_u32(entry.pid)).is_none();
+        }
+        if stopped.iter().all(|entry| entry.stopped) || tokio::time::Instant::now() >= deadline {
+            break;
+        }
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+    }
+
+    let mut killed_any = false;
+    for entry in stopped.iter_mut().filter(|entry| !entry.stopped) {
+        if let Some(process) = system.process(Pid::from_u32(entry.pid)) {
+            println!("rpcs3 ({}) didn't close in time, killing it", entry.pid);
+            entry.killed = process.kill();
+            killed_any = true;
+        }
+    }
+    if killed_any {
+        tokio::time::sleep(STOP_POLL_INTERVAL).await;
+        system.refresh_processes();
+        for entry in stopped.iter_mut() {
+            entry.stopped = system.process(Pid::from_u32(entry.pid)).is_none();
+        }
+    }
+
+    Ok(StopReport {
+        all_stopped: stopped.iter().all(|entry| entry.stopped),
+        processes: stopped,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn version(text: &str) -> Rpcs3Version {
+        Rpcs3Version::parse(text).expect(text)
+    }
+
+    #[test]
+    fn parse_reads_version_output() {
+        let parsed = version("RPCS3 v0.0.31-16271-8ef9fa2c Alpha | HEAD");
+        assert_eq!((parsed.major, parsed.minor, parsed.patch), (0, 0, 31));
+        assert_eq!(parsed.build, Some(16271));
+        assert_eq!(parsed.commit.as_deref(), Some("8ef9fa2c"));
+        assert_eq!(parsed.raw, "v0.0.31-16271-8ef9fa2c");
+
+        let flatpak = version("0.0.31-16271-8ef9fa2c");
+        assert_eq!(flatpak.build, Some(16271));
+
+        let release = version("RPCS3 v0.0.32 Alpha");
+        assert_eq!(release.build, None);
+        assert_eq!(release.commit, None);
+    }
+
+    #[test]
+    fn parse_rejects_output_without_a_version() {
+        assert!(Rpcs3Version::parse("").is_none());
+        assert!(Rpcs3Version::parse("RPCS3 Alpha | HEAD").is_none());
+        assert!(Rpcs3Version::parse("v0.31").is_none());
+    }
+
+    #[test]
+    fn parse_file_version_accepts_windows_version_resources() {
+        let dotted = Rpcs3Version::parse_file_version("0.0.31.16271").unwrap();
+        assert_eq!((dotted.major, dotted.minor, dotted.patch), (0, 0, 31));
+        assert_eq!(dotted.build, Some(16271));
+
+        let release = Rpcs3Version::parse_file_version("0.0.32.0").unwrap();
+        assert_eq!(release.build, None);
+
+        let product = Rpcs3Version::parse_file_version("0.0.31-16271-8ef9fa2c").unwrap();
+        assert_eq!(product.commit.as_deref(), Some("8ef9fa2c"));
+
+        assert!(Rpcs3Version::parse_file_version("").is_none());
+        assert!(Rpcs3Version::parse_file_version("1.2").is_none());
+    }
+
+    #[test]
+    fn only_compared_versions_are_verified() {
+        let verified = |status| Rpcs3Validation::new(status, None, None).verified;
+        assert!(verified(ValidationStatus::Valid));
+        assert!(verified(ValidationStatus::Outdated));
+        assert!(!verified(ValidationStatus::Running));
+        assert!(!verified(ValidationStatus::UnknownVersion));
+
+        let running = Rpcs3Validation::new(ValidationStatus::Running, None, None);
+        assert!(running.valid && !running.verified);
+    }
+
+    #[test]
+    fn is_older_than_compares_builds_only_when_both_have_one() {
+        let minimum = version("0.0.29-15000");
+        assert!(version("0.0.28-16000").is_older_than(&minimum));
+        assert!(version("0.0.29-14999").is_older_than(&minimum));
+        assert!(!version("0.0.29-15000").is_older_than(&minimum));
+        assert!(!version("0.0.30").is_older_than(&minimum));
+        assert!(!version("0.0.29").is_older_than(&minimum));
+    }
+}
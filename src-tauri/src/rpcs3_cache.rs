@@ -0,0 +1,327 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+use walkdir::WalkDir;
+
+use crate::file_check::find_eboot;
+use crate::file_metadata::get_cached_checksums;
+use crate::metadata::GAME_IDS;
+use crate::os::{get_os, OS};
+use crate::rpcs3::is_executable_running;
+use crate::rpcs3_directory::{
+    resolve_rpcs3_directory, DirectoryRule, Rpcs3Directory, FLATPAK_APP_ID,
+};
+
+// md5 of the EBOOT.BIN last seen for each installed path
+const EBOOT_HASHES_FILE: &str = "eboot_hashes.json";
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error(transparent)]
+    Directory(#[from] crate::rpcs3_directory::Error),
+    #[error("{0} is not a Full Boost game id")]
+    UnknownGame(String),
+    #[error("rpcs3 is running, close it before clearing the cache")]
+    Rpcs3Running,
+    #[error("EBOOT.BIN of {0} was not found")]
+    EbootNotFound(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CacheKind {
+    // ppu-{hash}-EBOOT.BIN, the LLVM modules compiled for one executable
+    Ppu,
+    // spu-*.dat, the recompiled SPU programs
+    Spu,
+    // shaders_cache
+    Shaders,
+}
+
+impl CacheKind {
+    fn of(entry_name: &str) -> Option<CacheKind> {
+        match entry_name {
+            "shaders_cache" => Some(CacheKind::Shaders),
+            name if name.starts_with("ppu-") => Some(CacheKind::Ppu),
+            name if name.starts_with("spu") => Some(CacheKind::Spu),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheUsage {
+    pub game_id: String,
+    // Every cache/{SERIAL} that exists, rpcs3 moved it out of its own directory on Linux and macOS
+    pub directories: Vec<PathBuf>,
+    pub ppu_bytes: u64,
+    pub spu_bytes: u64,
+    pub shader_bytes: u64,
+    pub other_bytes: u64,
+    pub total_bytes: u64,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EbootCacheCheck {
+    pub game_id: String,
+    pub md5: String,
+    // False the first time an EBOOT is seen, there's nothing to compare with
+    pub changed: bool,
+    // Only set when the cache was cleared because the EBOOT changed
+    pub cleared: Option<CacheUsage>,
+}
+
+// rpcs3's cache directory, where it also writes RPCS3.log. Its own directory always comes first.
+pub fn cache_homes(app: &AppHandle, rpcs3_directory: &Rpcs3Directory) -> Vec<PathBuf> {
+    let mut homes = vec![rpcs3_directory.path.clone()];
+    let Ok(home) = app.path().home_dir() else {
+        return homes;
+    };
+    match (get_os(), rpcs3_directory.rule) {
+        (OS::Linux, DirectoryRule::Flatpak) => homes.push(
+            home.join(".var/app")
+                .join(FLATPAK_APP_ID)
+                .join("cache/rpcs3"),
+        ),
+        (OS::Linux, _) => {
+            let cache_home = std::env::var_os("XDG_CACHE_HOME")
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute())
+                .unwrap_or_else(|| home.join(".cache"));
+            homes.push(cache_home.join("rpcs3"));
+        }
+        (OS::Macos, _) => homes.push(home.join("Library/Caches/rpcs3")),
+        _ => {}
+    }
+    homes
+}
+
+fn game_cache_directories(
+    app: &AppHandle,
+    rpcs3_directory: &Rpcs3Directory,
+    game_id: &str,
+) -> Vec<PathBuf> {
+    cache_homes(app, rpcs3_directory)
+        .into_iter()
+        .map(|home| home.join("cache").join(game_id))
+        .filter(|path| path.is_dir())
+        .collect()
+}
+
+fn size_of(path: &Path) -> u64 {
+    WalkDir::new(path)
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
+
+fn cache_usage(app: &AppHandle, rpcs3_directory: &Rpcs3Directory, game_id: &str) -> CacheUsage {
+    let directories = game_cache_directories(app, rpcs3_directory, game_id);
+    let mut usage = CacheUsage {
+        game_id: game_id.to_string(),
+        ..Default::default()
+    };
+    for directory in &directories {
+        for entry in fs::read_dir(directory).into_iter().flatten().flatten() {
+            let size = size_of(&entry.path());
+            match CacheKind::of(&entry.file_name().to_string_lossy()) {
+                Some(CacheKind::Ppu) => usage.ppu_bytes += size,
+                Some(CacheKind::Spu) => usage.spu_bytes += size,
+                Some(CacheKind::Shaders) => usage.shader_bytes += size,
+                None => usage.other_bytes += size,
+            }
+            usage.total_bytes += size;
+        }
+    }
+    usage.directories = directories;
+    usage
+}
+
+// Removes the entries of the given kinds, anything rpcs3 adds that isn't known is left alone
+fn clear_cache(
+    app: &AppHandle,
+    rpcs3_directory: &Rpcs3Directory,
+    game_id: &str,
+    kinds: &[CacheKind],
+) -> Result<CacheUsage> {
+    for directory in game_cache_directories(app, rpcs3_directory, game_id) {
+        for entry in fs::read_dir(&directory)?.flatten() {
+            let Some(kind) = CacheKind::of(&entry.file_name().to_string_lossy()) else {
+                continue;
+            };
+            if !kinds.contains(&kind) {
+                continue;
+            }
+            let path = entry.path();
+            println!("Removing cache {}", path.display());
+            match path.is_dir() {
+                true => fs::remove_dir_all(&path)?,
+                false => fs::remove_file(&path)?,
+            }
+        }
+    }
+    Ok(cache_usage(app, rpcs3_directory, game_id))
+}
+
+fn check_game_id(game_id: &str) -> Result<()> {
+    match GAME_IDS.contains(&game_id) {
+        true => Ok(()),
+        false => Err(Error::UnknownGame(game_id.to_string())),
+    }
+}
+
+fn eboot_hashes_path(app: &AppHandle) -> PathBuf {
+    app.path()
+        .resolve(EBOOT_HASHES_FILE, BaseDirectory::AppData)
+        .expect("failed to resolve resource")
+}
+
+fn load_eboot_hashes(app: &AppHandle) -> Result<BTreeMap<String, String>> {
+    let path = eboot_hashes_path(app);
+    match path.exists() {
+        true => Ok(serde_json::from_str(&fs::read_to_string(&path)?)?),
+        false => Ok(BTreeMap::new()),
+    }
+}
+
+fn save_eboot_hashes(app: &AppHandle, hashes: &BTreeMap<String, String>) -> Result<()> {
+    let path = eboot_hashes_path(app);
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(hashes)?)?;
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_cache_usage(
+    app: AppHandle,
+    full_path: &str,
+    rpcs3_directory_override: Option<String>,
+) -> Result<Vec<CacheUsage>> {
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())?;
+    Ok(GAME_IDS
+        .iter()
+        .map(|game_id| cache_usage(&app, &rpcs3_directory, game_id))
+        .collect())
+}
+
+#[tauri::command]
+pub async fn clear_game_cache(
+    app: AppHandle,
+    full_path: &str,
+    game_id: &str,
+    kinds: Vec<CacheKind>,
+    rpcs3_directory_override: Option<String>,
+) -> Result<CacheUsage> {
+    check_game_id(game_id)?;
+    if is_executable_running(Path::new(full_path)) {
+        return Err(Error::Rpcs3Running);
+    }
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())?;
+    clear_cache(&app, &rpcs3_directory, game_id, &kinds)
+}
+
+// Meant to run before every launch. A modded EBOOT update leaves the PPU and SPU caches of the
+// old one behind, with auto_clear they are removed as soon as a different EBOOT is seen.
+#[tauri::command]
+pub async fn check_eboot_cache(
+    app: AppHandle,
+    full_path: &str,
+    game_id: &str,
+    auto_clear: bool,
+    rpcs3_directory_override: Option<String>,
+) -> Result<EbootCacheCheck> {
+    check_game_id(game_id)?;
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, full_path, rpcs3_directory_override.as_deref())?;
+    let eboot_path = find_eboot(&app, &rpcs3_directory, game_id)
+        .await
+        .ok_or(Error::EbootNotFound(game_id.to_string()))?;
+    let key = eboot_path.display().to_string();
+    // Only hashed again when the EBOOT was modified since the metadata cache last saw it
+    let md5 = get_cached_checksums(&app, vec![key.clone()])
+        .await
+        .remove(&key)
+        .ok_or(Error::EbootNotFound(game_id.to_string()))?;
+
+    let mut hashes = load_eboot_hashes(&app)?;
+    let changed = hashes.get(&key).is_some_and(|previous| previous != &md5);
+
+    let cleared = match changed && auto_clear {
+        true if is_executable_running(Path::new(full_path)) => return Err(Error::Rpcs3Running),
+        true => {
+            println!(
+                "EBOOT.BIN of {} changed, clearing its PPU and SPU caches",
+                game_id
+            );
+            Some(clear_cache(
+                &app,
+                &rpcs3_directory,
+                game_id,
+                &[CacheKind::Ppu, CacheKind::Spu],
+            )?)
+        }
+        false => None,
+    };
+
+    // Without auto_clear the change is only reported, the next check compares with the new EBOOT
+    hashes.insert(key, md5.clone());
+    save_eboot_hashes(&app, &hashes)?;
+
+    Ok(EbootCacheCheck {
+        game_id: game_id.to_string(),
+        md5,
+        changed,
+        cleared,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_cache_entries() {
+        assert_eq!(
+            CacheKind::of("ppu-Kq2Ry1nzYs8AzwBLtJdNE8a1bOc-EBOOT.BIN"),
+            Some(CacheKind::Ppu)
+        );
+        assert_eq!(CacheKind::of("spu-v1-tane.dat"), Some(CacheKind::Spu));
+        assert_eq!(CacheKind::of("spu.dat"), Some(CacheKind::Spu));
+        assert_eq!(CacheKind::of("shaders_cache"), Some(CacheKind::Shaders));
+    }
+
+    #[test]
+    fn leaves_unknown_entries_alone() {
+        assert_eq!(CacheKind::of("ppu.dat"), None);
+        assert_eq!(CacheKind::of("shaders_cache_old"), None);
+        assert_eq!(CacheKind::of("RPCS3.log"), None);
+    }
+}
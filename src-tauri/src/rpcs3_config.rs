@@ -0,0 +1,655 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::de::DeserializeOwned;
+use serde::{ser::Serializer, Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+
+use crate::metadata::GAME_IDS;
+use crate::rpcs3_directory::Rpcs3Directory;
+
+const MAX_BACKUPS: usize = 10;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Yaml(#[from] serde_yaml::Error),
+    #[error("{0} is not a Full Boost serial")]
+    UnsupportedSerial(String),
+    #[error("{0} is not a valid rpcs3 config: {1}")]
+    InvalidConfig(String, String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+// How a value is stored in config.yml. rpcs3 writes every scalar unquoted, so e.g. a frame limit
+// of 60 comes back as a number even though the setting is a list of names.
+#[derive(Clone, Copy)]
+enum Kind {
+    Text,
+    Number,
+    Flag,
+}
+
+// A typed view over one top level section of config.yml, keys that aren't listed are never touched
+trait ConfigSection: Serialize + DeserializeOwned + Default {
+    const NAME: &'static str;
+    // (field in the struct, key in config.yml, kind)
+    const KEYS: &'static [(&'static str, &'static str, Kind)];
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct CoreSettings {
+    pub ppu_decoder: Option<String>,
+    pub ppu_threads: Option<u64>,
+    pub spu_decoder: Option<String>,
+    pub preferred_spu_threads: Option<u64>,
+    pub spu_block_size: Option<String>,
+    pub max_spurs_threads: Option<u64>,
+}
+
+impl ConfigSection for CoreSettings {
+    const NAME: &'static str = "Core";
+    const KEYS: &'static [(&'static str, &'static str, Kind)] = &[
+        ("ppuDecoder", "PPU Decoder", Kind::Text),
+        ("ppuThreads", "PPU Threads", Kind::Number),
+        ("spuDecoder", "SPU Decoder", Kind::Text),
+        ("preferredSpuThreads", "Preferred SPU Threads", Kind::Number),
+        ("spuBlockSize", "SPU Block Size", Kind::Text),
+        ("maxSpursThreads", "Max SPURS Threads", Kind::Number),
+    ];
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VideoSettings {
+    pub renderer: Option<String>,
+    pub resolution: Option<String>,
+    pub resolution_scale: Option<u64>,
+    pub frame_limit: Option<String>,
+    pub vsync: Option<bool>,
+    pub aspect_ratio: Option<String>,
+    pub shader_mode: Option<String>,
+}
+
+impl ConfigSection for VideoSettings {
+    const NAME: &'static str = "Video";
+    const KEYS: &'static [(&'static str, &'static str, Kind)] = &[
+        ("renderer", "Renderer", Kind::Text),
+        ("resolution", "Resolution", Kind::Text),
+        ("resolutionScale", "Resolution Scale", Kind::Number),
+        ("frameLimit", "Frame limit", Kind::Text),
+        ("vsync", "VSync", Kind::Flag),
+        ("aspectRatio", "Aspect ratio", Kind::Text),
+        ("shaderMode", "Shader Mode", Kind::Text),
+    ];
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AudioSettings {
+    pub renderer: Option<String>,
+    pub audio_format: Option<String>,
+    pub master_volume: Option<u64>,
+    pub enable_buffering: Option<bool>,
+    pub buffer_duration: Option<u64>,
+    pub time_stretching: Option<bool>,
+}
+
+impl ConfigSection for AudioSettings {
+    const NAME: &'static str = "Audio";
+    const KEYS: &'static [(&'static str, &'static str, Kind)] = &[
+        ("renderer", "Renderer", Kind::Text),
+        ("audioFormat", "Audio Format", Kind::Text),
+        ("masterVolume", "Master Volume", Kind::Number),
+        ("enableBuffering", "Enable Buffering", Kind::Flag),
+        (
+            "bufferDuration",
+            "Desired Audio Buffer Duration",
+            Kind::Number,
+        ),
+        ("timeStretching", "Enable time stretching", Kind::Flag),
+    ];
+}
+
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NetworkSettings {
+    pub internet_enabled: Option<String>,
+    pub ip_address: Option<String>,
+    pub bind_address: Option<String>,
+    pub dns_address: Option<String>,
+    pub upnp_enabled: Option<bool>,
+    pub psn_status: Option<String>,
+}
+
+impl ConfigSection for NetworkSettings {
+    const NAME: &'static str = "Net";
+    const KEYS: &'static [(&'static str, &'static str, Kind)] = &[
+        ("internetEnabled", "Internet enabled", Kind::Text),
+        ("ipAddress", "IP address", Kind::Text),
+        ("bindAddress", "Bind address", Kind::Text),
+        ("dnsAddress", "DNS address", Kind::Text),
+        ("upnpEnabled", "UPNP Enabled", Kind::Flag),
+        ("psnStatus", "PSN status", Kind::Text),
+    ];
+}
+
+// Every field is optional: reading leaves out what the file doesn't have,
+// writing only changes what is set.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Rpcs3Settings {
+    pub core: CoreSettings,
+    pub video: VideoSettings,
+    pub audio: AudioSettings,
+    pub network: NetworkSettings,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigFile {
+    pub path: String,
+    pub exists: bool,
+    // For a game, the global config with the custom config on top, the way rpcs3 applies them
+    pub settings: Rpcs3Settings,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfigWrite {
+    pub path: String,
+    // None when there was nothing to back up yet
+    pub backup_path: Option<String>,
+    pub settings: Rpcs3Settings,
+}
+
+fn to_json(value: &Value, kind: Kind) -> Option<serde_json::Value> {
+    match (kind, value) {
+        (Kind::Text, Value::String(text)) => Some(serde_json::Value::from(text.as_str())),
+        (Kind::Text, Value::Number(number)) => Some(serde_json::Value::from(number.to_string())),
+        (Kind::Text, Value::Bool(flag)) => Some(serde_json::Value::from(flag.to_string())),
+        (Kind::Number, Value::Number(number)) => number.as_u64().map(serde_json::Value::from),
+        (Kind::Number, Value::String(text)) => {
+            text.parse::<u64>().ok().map(serde_json::Value::from)
+        }
+        (Kind::Flag, Value::Bool(flag)) => Some(serde_json::Value::from(*flag)),
+        (Kind::Flag, Value::String(text)) => text.parse::<bool>().ok().map(serde_json::Value::from),
+        _ => None,
+    }
+}
+
+fn to_yaml(value: &serde_json::Value, kind: Kind) -> Option<Value> {
+    match (kind, value) {
+        (Kind::Text, serde_json::Value::String(text)) => Some(Value::from(text.as_str())),
+        (Kind::Number, serde_json::Value::Number(number)) => number.as_u64().map(Value::from),
+        (Kind::Flag, serde_json::Value::Bool(flag)) => Some(Value::from(*flag)),
+        _ => None,
+    }
+}
+
+fn read_section<T: ConfigSection>(config: &Mapping) -> T {
+    let Some(Value::Mapping(section)) = config.get(T::NAME) else {
+        return T::default();
+    };
+
+    let mut object = serde_json::Map::new();
+    for (field, key, kind) in T::KEYS {
+        if let Some(value) = section.get(*key).and_then(|value| to_json(value, *kind)) {
+            object.insert(field.to_string(), value);
+        }
+    }
+    serde_json::from_value(serde_json::Value::Object(object)).unwrap_or_default()
+}
+
+fn write_section<T: ConfigSection>(config: &mut Mapping, settings: &T) {
+    let Ok(serde_json::Value::Object(object)) = serde_json::to_value(settings) else {
+        return;
+    };
+
+    let values: Vec<(&str, Value)> = T::KEYS
+        .iter()
+        .filter_map(|(field, key, kind)| {
+            object
+                .get(*field)
+                .and_then(|value| to_yaml(value, *kind))
+                .map(|value| (*key, value))
+        })
+        .collect();
+    if values.is_empty() {
+        return;
+    }
+
+    if !matches!(config.get(T::NAME), Some(Value::Mapping(_))) {
+        config.insert(Value::from(T::NAME), Value::Mapping(Mapping::new()));
+    }
+    if let Some(Value::Mapping(section)) = config.get_mut(T::NAME) {
+        for (key, value) in values {
+            section.insert(Value::from(key), value);
+        }
+    }
+}
+
+fn read_settings_from(config: &Mapping) -> Rpcs3Settings {
+    Rpcs3Settings {
+        core: read_section(config),
+        video: read_section(config),
+        audio: read_section(config),
+        network: read_section(config),
+    }
+}
+
+fn write_settings_to(config: &mut Mapping, settings: &Rpcs3Settings) {
+    write_section(config, &settings.core);
+    write_section(config, &settings.video);
+    write_section(config, &settings.audio);
+    write_section(config, &settings.network);
+}
+
+// Custom configs only hold what differs, rpcs3 applies them key by key over the global config
+fn overlay(base: &mut Mapping, custom: &Mapping) {
+    for (key, value) in custom.iter() {
+        match (base.get_mut(key), value) {
+            (Some(Value::Mapping(base_section)), Value::Mapping(custom_section)) => {
+                overlay(base_section, custom_section)
+            }
+            _ => {
+                base.insert(key.clone(), value.clone());
+            }
+        }
+    }
+}
+
+// Only the Full Boost versions get a per-game config, anything else is left to the rpcs3 GUI
+fn check_serial(serial: &str) -> Result<()> {
+    match GAME_IDS.contains(&serial) {
+        true => Ok(()),
+        false => Err(Error::UnsupportedSerial(serial.to_string())),
+    }
+}
+
+// Newer rpcs3 versions keep their yml files in config/, older ones in the root of the directory
+fn config_candidates(rpcs3_directory: &Rpcs3Directory, relative_path: &str) -> [PathBuf; 2] {
+    [
+        rpcs3_directory.path.join("config").join(relative_path),
+        rpcs3_directory.path.join(relative_path),
+    ]
+}
+
+fn existing_or_default(rpcs3_directory: &Rpcs3Directory, relative_path: &str) -> PathBuf {
+    let [config_folder, root] = config_candidates(rpcs3_directory, relative_path);
+    match (config_folder.exists(), root.exists()) {
+        (false, true) => root,
+        (false, false) if !rpcs3_directory.path.join("config").is_dir() => root,
+        _ => config_folder,
+    }
+}
+
+pub fn config_path(rpcs3_directory: &Rpcs3Directory) -> PathBuf {
+    existing_or_default(rpcs3_directory, "config.yml")
+}
+
+pub fn custom_config_path(rpcs3_directory: &Rpcs3Directory, serial: &str) -> PathBuf {
+    existing_or_default(
+        rpcs3_directory,
+        &format!("custom_configs/config_{}.yml", serial),
+    )
+}
+
+// A missing file is an empty config, a broken one is an error so it never gets written over
+fn load_config(path: &Path) -> Result<Mapping> {
+    if !path.exists() {
+        return Ok(Mapping::new());
+    }
+    let contents = fs::read_to_string(path)?;
+    if contents.trim().is_empty() {
+        return Ok(Mapping::new());
+    }
+    match serde_yaml::from_str::<Value>(&contents) {
+        Ok(Value::Mapping(mapping)) => Ok(mapping),
+        Ok(_) => Err(Error::InvalidConfig(
+            path.display().to_string(),
+            "not a mapping".to_string(),
+        )),
+        Err(err) => Err(Error::InvalidConfig(
+            path.display().to_string(),
+            err.to_string(),
+        )),
+    }
+}
+
+// Copies into rpcs3/.moddedboost/config_backups, keeping the last MAX_BACKUPS of every file
+fn backup_config(rpcs3_directory: &Rpcs3Directory, path: &Path) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let file_name = path
+        .file_name()
+        .map(|file_name| file_name.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let backup_directory = rpcs3_directory
+        .path
+        .join(".moddedboost")
+        .join("config_backups");
+    fs::create_dir_all(&backup_directory)?;
+
+    let timestamp = chrono::Local::now().format("%Y%m%d%H%M%S%3f");
+    let backup_path = backup_directory.join(format!("{}.{}.bak", file_name, timestamp));
+    fs::copy(path, &backup_path)?;
+
+    let prefix = format!("{}.", file_name);
+    let mut backups: Vec<PathBuf> = fs::read_dir(&backup_directory)?
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|backup| {
+            backup
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with(&prefix))
+        })
+        .collect();
+    // The timestamp sorts chronologically, oldest first
+    backups.sort();
+    let excess = backups.len().saturating_sub(MAX_BACKUPS);
+    for backup in backups.iter().take(excess) {
+        let _ = fs::remove_file(backup);
+    }
+
+    Ok(Some(backup_path))
+}
+
+fn save_config(path: &Path, config: &Mapping) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp_path = path.with_extension("yml.tmp");
+    fs::write(&temp_path, serde_yaml::to_string(config)?)?;
+    fs::rename(&temp_path, path)?;
+    Ok(())
+}
+
+pub fn read_settings(rpcs3_directory: &Rpcs3Directory, serial: Option<&str>) -> Result<ConfigFile> {
+    let global_path = config_path(rpcs3_directory);
+    let mut config = load_config(&global_path)?;
+
+    let path = match serial {
+        Some(serial) => {
+            check_serial(serial)?;
+            let custom_path = custom_config_path(rpcs3_directory, serial);
+            overlay(&mut config, &load_config(&custom_path)?);
+            custom_path
+        }
+        None => global_path,
+    };
+
+    Ok(ConfigFile {
+        path: path.display().to_string(),
+        exists: path.exists(),
+        settings: read_settings_from(&config),
+    })
+}
+
+// Writes the set fields into config.yml, or the game's custom config when a serial is given.
+// Everything else in the file, including keys this doesn't know about, stays as it was.
+pub fn write_settings(
+    rpcs3_directory: &Rpcs3Directory,
+    serial: Option<&str>,
+    settings: &Rpcs3Settings,
+) -> Result<ConfigWrite> {
+    if let Some(serial) = serial {
+        check_serial(serial)?;
+    }
+    let path = match serial {
+        Some(serial) => custom_config_path(rpcs3_directory, serial),
+        None => config_path(rpcs3_directory),
+    };
+
+    let mut config = load_config(&path)?;
+    let backup_path = backup_config(rpcs3_directory, &path)?;
+    write_settings_to(&mut config, settings);
+    save_config(&path, &config)?;
+
+    Ok(ConfigWrite {
+        path: path.display().to_string(),
+        backup_path: backup_path.map(|backup_path| backup_path.display().to_string()),
+        settings: read_settings(rpcs3_directory, serial)?.settings,
+    })
+}
+
+#[tauri::command]
+pub async fn get_rpcs3_settings(
+    rpcs3_directory: &str,
+    serial: Option<String>,
+) -> Result<ConfigFile> {
+    read_settings(
+        &Rpcs3Directory::at(Path::new(rpcs3_directory)),
+        serial.as_deref(),
+    )
+}
+
+#[tauri::command]
+pub async fn set_rpcs3_settings(
+    rpcs3_directory: &str,
+    serial: Option<String>,
+    settings: Rpcs3Settings,
+) -> Result<ConfigWrite> {
+    write_settings(
+        &Rpcs3Directory::at(Path::new(rpcs3_directory)),
+        serial.as_deref(),
+        &settings,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = include_str!("../tests/fixtures/config.yml");
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("rpcs3_config_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(path.join("config")).unwrap();
+        path
+    }
+
+    fn mapping(yaml: &str) -> Mapping {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn unknown_keys_are_preserved() {
+        let mut config = mapping(
+            "Video:\n  Renderer: Vulkan\n  MSAA: Auto\n  Vulkan:\n    Adapter: \"\"\nLog: {}\n",
+        );
+        let settings = Rpcs3Settings {
+            video: VideoSettings {
+                vsync: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        write_settings_to(&mut config, &settings);
+
+        assert_eq!(
+            config,
+            mapping(
+                "Video:\n  Renderer: Vulkan\n  MSAA: Auto\n  Vulkan:\n    Adapter: \"\"\n  VSync: true\nLog: {}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn unset_sections_are_not_created() {
+        let mut config = mapping("Log: {}\n");
+        write_settings_to(&mut config, &Rpcs3Settings::default());
+        assert_eq!(config, mapping("Log: {}\n"));
+    }
+
+    #[test]
+    fn custom_config_overlays_key_by_key() {
+        let mut config = mapping(
+            "Core:\n  PPU Threads: 2\n  SPU Block Size: Safe\nVideo:\n  Renderer: Vulkan\n  Vulkan:\n    Adapter: A\n    Force FIFO present mode: false\n",
+        );
+        overlay(
+            &mut config,
+            &mapping("Core:\n  PPU Threads: 4\nVideo:\n  Vulkan:\n    Adapter: B\nLog: {}\n"),
+        );
+
+        assert_eq!(
+            config,
+            mapping(
+                "Core:\n  PPU Threads: 4\n  SPU Block Size: Safe\nVideo:\n  Renderer: Vulkan\n  Vulkan:\n    Adapter: B\n    Force FIFO present mode: false\nLog: {}\n"
+            )
+        );
+    }
+
+    #[test]
+    fn values_are_coerced_to_their_kind() {
+        // rpcs3 writes a numeric frame limit unquoted, numbers and flags can come back quoted
+        let mut config = mapping(
+            "Core:\n  PPU Threads: \"4\"\nVideo:\n  Frame limit: 60\n  VSync: \"true\"\n  Resolution Scale: -1\n",
+        );
+        let settings = read_settings_from(&config);
+        assert_eq!(settings.core.ppu_threads, Some(4));
+        assert_eq!(settings.video.frame_limit.as_deref(), Some("60"));
+        assert_eq!(settings.video.vsync, Some(true));
+        assert_eq!(settings.video.resolution_scale, None);
+
+        write_settings_to(&mut config, &settings);
+        let saved = serde_yaml::to_string(&config).unwrap();
+        let settings = read_settings_from(&mapping(&saved));
+        assert_eq!(settings.core.ppu_threads, Some(4));
+        assert_eq!(settings.video.frame_limit.as_deref(), Some("60"));
+        assert_eq!(settings.video.vsync, Some(true));
+    }
+
+    #[test]
+    fn real_config_round_trips() {
+        let directory = temp_directory("round_trip");
+        let path = directory.join("config").join("config.yml");
+        fs::write(&path, FIXTURE).unwrap();
+        let rpcs3_directory = Rpcs3Directory::at(&directory);
+
+        let read = read_settings(&rpcs3_directory, None).unwrap();
+        assert_eq!(read.path, path.display().to_string());
+        assert_eq!(
+            read.settings.core.ppu_decoder.as_deref(),
+            Some("Recompiler (LLVM)")
+        );
+        assert_eq!(read.settings.video.aspect_ratio.as_deref(), Some("16:9"));
+        assert_eq!(read.settings.audio.master_volume, Some(100));
+        assert_eq!(read.settings.network.ip_address.as_deref(), Some("0.0.0.0"));
+
+        // Writing back what was read changes nothing
+        write_settings(&rpcs3_directory, None, &read.settings).unwrap();
+        assert_eq!(load_config(&path).unwrap(), mapping(FIXTURE));
+
+        let mut settings = read.settings;
+        settings.video.frame_limit = Some("60".to_string());
+        let written = write_settings(&rpcs3_directory, None, &settings).unwrap();
+        assert_eq!(written.settings.video.frame_limit.as_deref(), Some("60"));
+
+        let mut expected = mapping(FIXTURE);
+        if let Some(Value::Mapping(video)) = expected.get_mut("Video") {
+            video.insert(Value::from("Frame limit"), Value::from("60"));
+        }
+        assert_eq!(load_config(&path).unwrap(), expected);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn game_settings_go_to_the_custom_config() {
+        let directory = temp_directory("custom");
+        fs::write(directory.join("config").join("config.yml"), FIXTURE).unwrap();
+        let rpcs3_directory = Rpcs3Directory::at(&directory);
+
+        let settings = Rpcs3Settings {
+            core: CoreSettings {
+                ppu_threads: Some(3),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let written = write_settings(&rpcs3_directory, Some("NPJB00512"), &settings).unwrap();
+        let custom_path = directory.join("config/custom_configs/config_NPJB00512.yml");
+        assert_eq!(written.path, custom_path.display().to_string());
+        assert_eq!(written.settings.core.ppu_threads, Some(3));
+        assert_eq!(written.settings.video.renderer.as_deref(), Some("Vulkan"));
+        assert_eq!(
+            load_config(&custom_path).unwrap(),
+            mapping("Core:\n  PPU Threads: 3\n")
+        );
+        assert_eq!(
+            load_config(&directory.join("config").join("config.yml")).unwrap(),
+            mapping(FIXTURE)
+        );
+
+        assert!(matches!(
+            write_settings(&rpcs3_directory, Some("BLUS30001"), &settings),
+            Err(Error::UnsupportedSerial(_))
+        ));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn broken_configs_are_not_written_over() {
+        let directory = temp_directory("broken");
+        let path = directory.join("config").join("config.yml");
+        fs::write(&path, "Core: [").unwrap();
+
+        let written = write_settings(
+            &Rpcs3Directory::at(&directory),
+            None,
+            &Rpcs3Settings::default(),
+        );
+        assert!(matches!(written, Err(Error::InvalidConfig(_, _))));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "Core: [");
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn old_backups_are_pruned() {
+        let directory = temp_directory("backups");
+        let path = directory.join("config").join("config.yml");
+        fs::write(&path, FIXTURE).unwrap();
+        let rpcs3_directory = Rpcs3Directory::at(&directory);
+
+        let mut backups = Vec::new();
+        for _ in 0..MAX_BACKUPS + 3 {
+            backups.push(backup_config(&rpcs3_directory, &path).unwrap().unwrap());
+            // Backups are named by the millisecond
+            std::thread::sleep(std::time::Duration::from_millis(2));
+        }
+
+        let backup_directory = directory.join(".moddedboost").join("config_backups");
+        let mut remaining: Vec<PathBuf> = fs::read_dir(&backup_directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        remaining.sort();
+        assert_eq!(remaining, backups[3..]);
+        assert_eq!(fs::read_to_string(&remaining[0]).unwrap(), FIXTURE);
+
+        assert_eq!(
+            backup_config(&rpcs3_directory, &directory.join("missing.yml")).unwrap(),
+            None
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
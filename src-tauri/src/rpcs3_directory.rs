@@ -0,0 +1,397 @@
+use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{ser::Serializer, Serialize};
+use tauri::{AppHandle, Manager};
+
+use crate::os::{get_os, OS};
+
+pub const FLATPAK_APP_ID: &str = "net.rpcs3.RPCS3";
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to find the home directory")]
+    HomeDirectory,
+    #[error("{0} has no parent directory")]
+    NoParent(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+// Which rule picked the directory, in the order they are tried
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DirectoryRule {
+    Override,
+    ExecutableDirectory,
+    Flatpak,
+    Portable,
+    XdgConfigHome,
+    HomeConfig,
+    ApplicationSupport,
+}
+
+// Where the emulated devices really are, rpcs3 lets them be moved anywhere through config/vfs.yml
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VfsPaths {
+    pub dev_hdd0: PathBuf,
+    pub dev_bdvd: PathBuf,
+    pub games: PathBuf,
+    // None when there's no vfs.yml and the defaults are used
+    pub vfs_file: Option<PathBuf>,
+}
+
+impl VfsPaths {
+    fn defaults(rpcs3_directory: &Path) -> VfsPaths {
+        VfsPaths {
+            dev_hdd0: rpcs3_directory.join("dev_hdd0"),
+            dev_bdvd: rpcs3_directory.join("dev_bdvd"),
+            games: rpcs3_directory.join("games"),
+            vfs_file: None,
+        }
+    }
+
+    // Entries look like `/dev_hdd0/: $(EmulatorDir)dev_hdd0/`, an empty $(EmulatorDir) is the rpcs3 directory.
+    // Older rpcs3 versions keep vfs.yml in the root instead of config/.
+    pub fn read(rpcs3_directory: &Path) -> VfsPaths {
+        let mut vfs = VfsPaths::defaults(rpcs3_directory);
+        let vfs_file = [
+            rpcs3_directory.join("config").join("vfs.yml"),
+            rpcs3_directory.join("vfs.yml"),
+        ]
+        .into_iter()
+        .find(|path| path.is_file());
+        let Some(vfs_file) = vfs_file else {
+            return vfs;
+        };
+
+        let mapping = match fs::read_to_string(&vfs_file)
+            .map_err(|err| err.to_string())
+            .and_then(|contents| {
+                serde_yaml::from_str::<HashMap<String, serde_yaml::Value>>(&contents)
+                    .map_err(|err| err.to_string())
+            }) {
+            Ok(mapping) => mapping,
+            Err(err) => {
+                println!("Failed to read {}: {}", vfs_file.display(), err);
+                return vfs;
+            }
+        };
+        let entry = |key: &str| mapping.get(key).and_then(|value| value.as_str());
+
+        let mut emulator_directory = entry("$(EmulatorDir)")
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| rpcs3_directory.display().to_string());
+        if !emulator_directory.ends_with(['/', '\\']) {
+            emulator_directory.push('/');
+        }
+        // Relative locations are relative to the rpcs3 directory, join keeps absolute ones as they are
+        let expand = |value: &str| {
+            rpcs3_directory.join(value.replace("$(EmulatorDir)", &emulator_directory))
+        };
+
+        if let Some(dev_hdd0) = entry("/dev_hdd0/").filter(|value| !value.is_empty()) {
+            vfs.dev_hdd0 = expand(dev_hdd0);
+        }
+        if let Some(dev_bdvd) = entry("/dev_bdvd/").filter(|value| !value.is_empty()) {
+            vfs.dev_bdvd = expand(dev_bdvd);
+        }
+        if let Some(games) = entry("/games/").filter(|value| !value.is_empty()) {
+            vfs.games = expand(games);
+        }
+        vfs.vfs_file = Some(vfs_file);
+        vfs
+    }
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rpcs3Directory {
+    pub path: PathBuf,
+    pub rule: DirectoryRule,
+    pub exists: bool,
+    pub vfs: VfsPaths,
+}
+
+impl Rpcs3Directory {
+    fn new(path: PathBuf, rule: DirectoryRule) -> Rpcs3Directory {
+        Rpcs3Directory {
+            exists: path.is_dir(),
+            vfs: VfsPaths::read(&path),
+            path,
+            rule,
+        }
+    }
+
+    // A directory that was already resolved, e.g. handed over by the frontend
+    pub fn at(path: &Path) -> Rpcs3Directory {
+        Rpcs3Directory::new(path.to_path_buf(), DirectoryRule::Override)
+    }
+
+    // Paths relative to the rpcs3 directory as used in metadata.json, e.g. "dev_hdd0/game/{GAME_ID}",
+    // land on the relocated device when they start with one.
+    pub fn join(&self, relative_path: impl AsRef<str>) -> PathBuf {
+        let relative_path = relative_path.as_ref().trim_start_matches('/');
+        let (device, rest) = relative_path.split_once('/').unwrap_or((relative_path, ""));
+        let device_path = match device {
+            "dev_hdd0" => &self.vfs.dev_hdd0,
+            "dev_bdvd" => &self.vfs.dev_bdvd,
+            "games" => &self.vfs.games,
+            _ => return self.path.join(relative_path),
+        };
+        match rest.is_empty() {
+            true => device_path.clone(),
+            false => device_path.join(rest),
+        }
+    }
+
+    pub fn patch_config_path(&self) -> PathBuf {
+        self.path.join("config").join("patch_config.yml")
+    }
+}
+
+// The launcher flatpak exports is named after the app id, a path inside the installation works too
+pub fn is_flatpak(executable_path: &Path) -> bool {
+    executable_path
+        .file_name()
+        .is_some_and(|file_name| file_name == FLATPAK_APP_ID)
+        || executable_path
+            .components()
+            .any(|component| component.as_os_str() == FLATPAK_APP_ID)
+}
+
+// The launcher can be an AppImage itself, so APPIMAGE only counts when it is this executable
+fn is_appimage(executable_path: &Path, appimage_env: Option<&OsStr>) -> bool {
+    executable_path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("AppImage"))
+        || appimage_env.is_some_and(|appimage| Path::new(appimage) == executable_path)
+}
+
+// A portable AppImage points XDG_CONFIG_HOME at the config folder next to it,
+// rpcs3 then keeps its data in config/rpcs3 like it would in ~/.config/rpcs3.
+fn portable_directory(executable_path: &Path, appimage_env: Option<&OsStr>) -> Option<PathBuf> {
+    if !is_appimage(executable_path, appimage_env) {
+        return None;
+    }
+    let config = executable_path.parent()?.join("config");
+    config.is_dir().then(|| config.join("rpcs3"))
+}
+
+// Where rpcs3 keeps config/, dev_hdd0/, games.yml and the rest of its data for a given executable.
+// On Windows that is always the folder of rpcs3.exe, on Linux it depends on how rpcs3 was installed.
+pub fn resolve_rpcs3_directory(
+    app: &AppHandle,
+    executable_path: &str,
+    directory_override: Option<&str>,
+) -> Result<Rpcs3Directory> {
+    if let Some(directory_override) = directory_override.filter(|path| !path.trim().is_empty()) {
+        return Ok(Rpcs3Directory::new(
+            PathBuf::from(directory_override),
+            DirectoryRule::Override,
+        ));
+    }
+
+    let executable_path = Path::new(executable_path);
+    let home_dir = || app.path().home_dir().map_err(|_| Error::HomeDirectory);
+
+    match get_os() {
+        OS::Windows => {
+            let parent = executable_path
+                .parent()
+                .ok_or(Error::NoParent(executable_path.display().to_string()))?;
+            Ok(Rpcs3Directory::new(
+                parent.to_path_buf(),
+                DirectoryRule::ExecutableDirectory,
+            ))
+        }
+        OS::Linux => {
+            if is_flatpak(executable_path) {
+                return Ok(Rpcs3Directory::new(
+                    home_dir()?
+                        .join(".var/app")
+                        .join(FLATPAK_APP_ID)
+                        .join("config/rpcs3"),
+                    DirectoryRule::Flatpak,
+                ));
+            }
+
+            let appimage_env = std::env::var_os("APPIMAGE");
+            if let Some(portable) = portable_directory(executable_path, appimage_env.as_deref()) {
+                return Ok(Rpcs3Directory::new(portable, DirectoryRule::Portable));
+            }
+
+            // Relative values are invalid per the spec and ignored, same as rpcs3 does
+            let xdg_config_home = std::env::var_os("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .filter(|path| path.is_absolute());
+            match xdg_config_home {
+                Some(xdg_config_home) => Ok(Rpcs3Directory::new(
+                    xdg_config_home.join("rpcs3"),
+                    DirectoryRule::XdgConfigHome,
+                )),
+                None => Ok(Rpcs3Directory::new(
+                    home_dir()?.join(".config/rpcs3"),
+                    DirectoryRule::HomeConfig,
+                )),
+            }
+        }
+        OS::Macos => Ok(Rpcs3Directory::new(
+            home_dir()?.join("Library/Application Support/rpcs3"),
+            DirectoryRule::ApplicationSupport,
+        )),
+    }
+}
+
+#[tauri::command]
+pub async fn get_rpcs3_directory(
+    app: AppHandle,
+    executable_path: &str,
+    directory_override: Option<String>,
+) -> Result<Rpcs3Directory> {
+    resolve_rpcs3_directory(&app, executable_path, directory_override.as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let path =
+            std::env::temp_dir().join(format!("moddedboost_test_{}_{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    #[test]
+    fn portable_appimage_keeps_its_data_in_config_rpcs3() {
+        let directory = temp_directory("portable");
+        fs::create_dir_all(directory.join("config")).unwrap();
+
+        let appimage = directory.join("rpcs3-v0.0.32-x86_64.AppImage");
+        assert_eq!(
+            portable_directory(&appimage, None),
+            Some(directory.join("config").join("rpcs3"))
+        );
+
+        // Extracted or renamed AppImages are recognized through APPIMAGE
+        let renamed = directory.join("rpcs3");
+        assert_eq!(
+            portable_directory(&renamed, Some(renamed.as_os_str())),
+            Some(directory.join("config").join("rpcs3"))
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn config_folder_next_to_other_executables_is_not_portable() {
+        let directory = temp_directory("not_portable");
+        fs::create_dir_all(directory.join("config")).unwrap();
+
+        let binary = directory.join("rpcs3");
+        assert_eq!(portable_directory(&binary, None), None);
+        // APPIMAGE of the launcher itself
+        let launcher = Path::new("/opt/moddedboost.AppImage");
+        assert_eq!(
+            portable_directory(&binary, Some(launcher.as_os_str())),
+            None
+        );
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn appimage_without_config_folder_is_not_portable() {
+        let directory = temp_directory("appimage");
+        assert_eq!(
+            portable_directory(&directory.join("rpcs3.AppImage"), None),
+            None
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn vfs_defaults_to_the_rpcs3_directory() {
+        let directory = temp_directory("vfs_defaults");
+        let vfs = VfsPaths::read(&directory);
+        assert_eq!(vfs.dev_hdd0, directory.join("dev_hdd0"));
+        assert_eq!(vfs.games, directory.join("games"));
+        assert!(vfs.vfs_file.is_none());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn vfs_follows_relocated_devices() {
+        let directory = temp_directory("vfs_relocated");
+        fs::create_dir_all(directory.join("config")).unwrap();
+        fs::write(
+            directory.join("config").join("vfs.yml"),
+            "$(EmulatorDir): \"\"\n\
+             /dev_hdd0/: /mnt/ssd/dev_hdd0/\n\
+             /dev_bdvd/: $(EmulatorDir)dev_bdvd/\n\
+             /games/: \"\"\n",
+        )
+        .unwrap();
+
+        let rpcs3_directory = Rpcs3Directory::at(&directory);
+        let vfs = &rpcs3_directory.vfs;
+        assert_eq!(vfs.dev_hdd0, PathBuf::from("/mnt/ssd/dev_hdd0"));
+        assert_eq!(vfs.dev_bdvd, directory.join("dev_bdvd"));
+        assert_eq!(vfs.games, directory.join("games"));
+        assert_eq!(vfs.vfs_file, Some(directory.join("config").join("vfs.yml")));
+
+        assert_eq!(
+            rpcs3_directory.join("/dev_hdd0/game/NPJB00512/USRDIR"),
+            PathBuf::from("/mnt/ssd/dev_hdd0/game/NPJB00512/USRDIR")
+        );
+        assert_eq!(
+            rpcs3_directory.join("dev_hdd0"),
+            PathBuf::from("/mnt/ssd/dev_hdd0")
+        );
+        assert_eq!(
+            rpcs3_directory.join("config/patch_config.yml"),
+            directory.join("config/patch_config.yml")
+        );
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn vfs_emulator_dir_can_point_elsewhere() {
+        let directory = temp_directory("vfs_emulator_dir");
+        fs::write(
+            directory.join("vfs.yml"),
+            "$(EmulatorDir): /srv/rpcs3\n/dev_hdd0/: $(EmulatorDir)dev_hdd0/\n",
+        )
+        .unwrap();
+        let vfs = VfsPaths::read(&directory);
+        assert_eq!(vfs.dev_hdd0, PathBuf::from("/srv/rpcs3/dev_hdd0"));
+        assert_eq!(vfs.vfs_file, Some(directory.join("vfs.yml")));
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn flatpak_is_recognized_by_app_id() {
+        assert!(is_flatpak(Path::new(
+            "/var/lib/flatpak/exports/bin/net.rpcs3.RPCS3"
+        )));
+        assert!(is_flatpak(Path::new(
+            "/var/lib/flatpak/app/net.rpcs3.RPCS3/current/active/files/bin/rpcs3"
+        )));
+        assert!(!is_flatpak(Path::new("/usr/bin/rpcs3")));
+    }
+}
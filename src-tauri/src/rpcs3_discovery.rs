@@ -0,0 +1,471 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::Duration;
+
+use same_file::is_same_file;
+use serde::{ser::Serializer, Serialize};
+use sysinfo::System;
+use tauri::utils::platform::current_exe;
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+
+use crate::file_check::{find_game_versions, FullBoostVersions};
+use crate::os::{get_os, OS};
+use crate::rpcs3_directory::{self, resolve_rpcs3_directory, FLATPAK_APP_ID};
+
+const VERSION_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("failed to find the home directory")]
+    HomeDirectory,
+    #[error(transparent)]
+    Directory(#[from] rpcs3_directory::Error),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstallationKind {
+    Executable,
+    AppImage,
+    Flatpak,
+}
+
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum DiscoverySource {
+    Applications,
+    LocalBin,
+    AppImageLauncher,
+    Flatpak,
+    RunningProcess,
+    SearchPath,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rpcs3Installation {
+    // What goes into rpcs3Path, for Flatpak the exported launcher script
+    pub executable_path: String,
+    pub kind: InstallationKind,
+    pub source: DiscoverySource,
+    pub version: Option<String>,
+    pub config_directory: String,
+    pub config_directory_exists: bool,
+    pub full_boost: FullBoostVersions,
+    pub running: bool,
+}
+
+struct Candidate {
+    path: PathBuf,
+    kind: InstallationKind,
+    source: DiscoverySource,
+}
+
+fn is_rpcs3_file_name(path: &Path) -> Option<InstallationKind> {
+    let file_name = path.file_name()?.to_str()?.to_lowercase();
+    if !file_name.contains("rpcs3") {
+        return None;
+    }
+    match file_name.as_str() {
+        "rpcs3" | "rpcs3.exe" => Some(InstallationKind::Executable),
+        name if name.ends_with(".appimage") => Some(InstallationKind::AppImage),
+        _ => None,
+    }
+}
+
+// Only the top level of a directory, people don't nest AppImages
+fn scan_directory(directory: &Path, source: DiscoverySource) -> Vec<Candidate> {
+    let Ok(entries) = fs::read_dir(directory) else {
+        return Vec::new();
+    };
+    entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_file())
+        .filter_map(|path| is_rpcs3_file_name(&path).map(|kind| Candidate { path, kind, source }))
+        .collect()
+}
+
+// AppImageLauncher integrates AppImages into ~/Applications unless destination is set in its config
+fn appimagelauncher_directory(home: &Path) -> Option<PathBuf> {
+    let contents = fs::read_to_string(home.join(".config/appimagelauncher.cfg")).ok()?;
+    contents.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "destination").then(|| {
+            let value = value.trim();
+            match value.strip_prefix("~/") {
+                Some(relative) => home.join(relative),
+                None => PathBuf::from(value),
+            }
+        })
+    })
+}
+
+// The launcher scripts flatpak exports for the system and the user installation
+fn flatpak_candidates(home: &Path) -> Vec<Candidate> {
+    [
+        PathBuf::from("/var/lib/flatpak/exports/bin").join(FLATPAK_APP_ID),
+        home.join(".local/share/flatpak/exports/bin")
+            .join(FLATPAK_APP_ID),
+    ]
+    .into_iter()
+    .filter(|path| path.exists())
+    .map(|path| Candidate {
+        path,
+        kind: InstallationKind::Flatpak,
+        source: DiscoverySource::Flatpak,
+    })
+    .collect()
+}
+
+// A running AppImage executes from its squashfs mount, APPIMAGE points back at the file itself
+fn running_candidates(system: &System) -> Vec<Candidate> {
+    system
+        .processes_by_name("rpcs3")
+        .filter_map(|process| {
+            let appimage = process
+                .environ()
+                .iter()
+                .find_map(|variable| variable.strip_prefix("APPIMAGE="))
+                .map(PathBuf::from);
+            let (path, kind) = match appimage {
+                Some(path) => (path, InstallationKind::AppImage),
+                None => (process.exe()?.to_path_buf(), InstallationKind::Executable),
+            };
+            // Flatpak processes report paths inside the sandbox, the exported launcher is found separately
+            path.exists().then_some(Candidate {
+                path,
+                kind,
+                source: DiscoverySource::RunningProcess,
+            })
+        })
+        .collect()
+}
+
+// Paths from the config, either the executable itself or the folder it's in
+fn search_path_candidates(search_paths: &[String]) -> Vec<Candidate> {
+    search_paths
+        .iter()
+        .map(PathBuf::from)
+        .flat_map(|path| match path.is_dir() {
+            true => scan_directory(&path, DiscoverySource::SearchPath),
+            false => path
+                .exists()
+                .then(|| Candidate {
+                    kind: is_rpcs3_file_name(&path).unwrap_or(InstallationKind::Executable),
+                    path,
+                    source: DiscoverySource::SearchPath,
+                })
+                .into_iter()
+                .collect(),
+        })
+        .collect()
+}
+
+// The same file can be found through several sources, the first one wins.
+// The launcher itself is skipped, it can end up in the same folders.
+fn dedupe_candidates(candidates: Vec<Candidate>, own_exe: Option<&Path>) -> Vec<Candidate> {
+    let mut unique: Vec<Candidate> = Vec::new();
+    for candidate in candidates {
+        let is_own_exe =
+            own_exe.is_some_and(|own_exe| is_same_file(own_exe, &candidate.path).unwrap_or(false));
+        let is_duplicate = unique
+            .iter()
+            .any(|existing| is_same_file(&existing.path, &candidate.path).unwrap_or(false));
+        if !is_own_exe && !is_duplicate {
+            unique.push(candidate);
+        }
+    }
+    unique
+}
+
+async fn command_output(mut cmd: Command) -> Option<String> {
+    cmd.stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .kill_on_drop(true);
+    let output = tokio::time::timeout(VERSION_TIMEOUT, cmd.output())
+        .await
+        .ok()?
+        .ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+// First line of --version, e.g. "RPCS3 v0.0.31-16271-8ef9fa2c Alpha | HEAD"
+async fn installation_version(candidate: &Candidate) -> Option<String> {
+    let output = match candidate.kind {
+        // Starting the sandbox just for --version is slow, flatpak already knows the version
+        InstallationKind::Flatpak => {
+            let mut cmd = Command::new("flatpak");
+            cmd.arg("info").arg(FLATPAK_APP_ID);
+            let info = command_output(cmd).await?;
+            return info.lines().find_map(|line| {
+                line.trim()
+                    .strip_prefix("Version:")
+                    .map(|version| version.trim().to_string())
+            });
+        }
+        _ => {
+            let mut cmd = Command::new(&candidate.path);
+            cmd.arg("--version");
+            command_output(cmd).await?
+        }
+    };
+    output
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
+}
+
+fn is_running(system: &System, candidate: &Candidate) -> bool {
+    system.processes_by_name("rpcs3").any(|process| {
+        let appimage_matches = process
+            .environ()
+            .iter()
+            .filter_map(|variable| variable.strip_prefix("APPIMAGE="))
+            .any(|path| is_same_file(path, &candidate.path).unwrap_or(false));
+        let exe_matches = process
+            .exe()
+            .is_some_and(|exe| is_same_file(exe, &candidate.path).unwrap_or(false));
+        appimage_matches || exe_matches
+    })
+}
+
+#[tauri::command]
+pub async fn discover_rpcs3_installations(
+    app: AppHandle,
+    search_paths: Option<Vec<String>>,
+) -> Result<Vec<Rpcs3Installation>> {
+    let home = app.path().home_dir().map_err(|_| Error::HomeDirectory)?;
+    let system = System::new_all();
+
+    let mut candidates: Vec<Candidate> = Vec::new();
+    candidates.extend(search_path_candidates(&search_paths.unwrap_or_default()));
+    candidates.extend(running_candidates(&system));
+    if get_os() == OS::Linux {
+        let appimagelauncher =
+            appimagelauncher_directory(&home).unwrap_or_else(|| home.join("Applications"));
+        candidates.extend(scan_directory(
+            &appimagelauncher,
+            DiscoverySource::AppImageLauncher,
+        ));
+        candidates.extend(scan_directory(
+            &home.join("Applications"),
+            DiscoverySource::Applications,
+        ));
+        candidates.extend(scan_directory(
+            &home.join(".local/bin"),
+            DiscoverySource::LocalBin,
+        ));
+        candidates.extend(flatpak_candidates(&home));
+    }
+
+    let own_exe = current_exe().ok();
+    let mut installations: Vec<Rpcs3Installation> = Vec::new();
+    for candidate in dedupe_candidates(candidates, own_exe.as_deref()) {
+        let running = is_running(&system, &candidate);
+        // A running rpcs3 refuses a second instance, --version included
+        let version = match running {
+            true => None,
+            false => installation_version(&candidate).await,
+        };
+        // A path without a parent only rules out that candidate
+        let rpcs3_directory =
+            match resolve_rpcs3_directory(&app, &candidate.path.display().to_string(), None) {
+                Ok(rpcs3_directory) => rpcs3_directory,
+                Err(rpcs3_directory::Error::NoParent(_)) => continue,
+                Err(error) => return Err(error.into()),
+            };
+        let config_directory_exists = rpcs3_directory.exists;
+        let config_directory = rpcs3_directory.path.clone();
+        let full_boost = match config_directory_exists {
+            true => find_game_versions(&app, &rpcs3_directory).await,
+            false => FullBoostVersions {
+                BLJS10250: false,
+                NPJB00512: false,
+            },
+        };
+
+        installations.push(Rpcs3Installation {
+            executable_path: candidate.path.display().to_string(),
+            kind: candidate.kind,
+            source: candidate.source,
+            version,
+            config_directory: config_directory.display().to_string(),
+            config_directory_exists,
+            full_boost,
+            running,
+        });
+    }
+
+    // Installations that already have the game first, then the ones that at least respond
+    installations.sort_by_key(|installation| {
+        (
+            !(installation.full_boost.BLJS10250 || installation.full_boost.NPJB00512),
+            installation.version.is_none() && !installation.running,
+        )
+    });
+
+    Ok(installations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_directory(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "rpcs3_discovery_test_{}_{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        path
+    }
+
+    fn candidate(path: PathBuf, source: DiscoverySource) -> Candidate {
+        Candidate {
+            path,
+            kind: InstallationKind::Executable,
+            source,
+        }
+    }
+
+    #[test]
+    fn rpcs3_file_names_are_recognized() {
+        let kind = |name: &str| is_rpcs3_file_name(Path::new(name));
+        assert!(kind("/usr/bin/rpcs3") == Some(InstallationKind::Executable));
+        assert!(kind("C:/RPCS3/RPCS3.exe") == Some(InstallationKind::Executable));
+        assert!(kind("rpcs3-v0.0.32-x86_64.AppImage") == Some(InstallationKind::AppImage));
+        assert!(kind("rpcs3.desktop").is_none());
+        assert!(kind("Other.AppImage").is_none());
+    }
+
+    #[test]
+    fn only_rpcs3_files_are_scanned() {
+        let directory = temp_directory("scan");
+        fs::write(directory.join("rpcs3-v0.0.32-x86_64.AppImage"), "").unwrap();
+        fs::write(directory.join("rpcs3.png"), "").unwrap();
+        fs::create_dir_all(directory.join("rpcs3")).unwrap();
+
+        let candidates = scan_directory(&directory, DiscoverySource::Applications);
+        assert_eq!(candidates.len(), 1);
+        assert!(candidates[0].kind == InstallationKind::AppImage);
+        assert!(candidates[0].source == DiscoverySource::Applications);
+        assert!(scan_directory(&directory.join("missing"), DiscoverySource::LocalBin).is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn appimagelauncher_destination_is_read_from_its_config() {
+        let home = temp_directory("appimagelauncher");
+        assert_eq!(appimagelauncher_directory(&home), None);
+
+        fs::create_dir_all(home.join(".config")).unwrap();
+        let config = home.join(".config/appimagelauncher.cfg");
+        fs::write(&config, "[AppImageLauncher]\ndestination = ~/AppImages\n").unwrap();
+        assert_eq!(
+            appimagelauncher_directory(&home),
+            Some(home.join("AppImages"))
+        );
+
+        fs::write(&config, "[AppImageLauncher]\ndestination=/opt/appimages\n").unwrap();
+        assert_eq!(
+            appimagelauncher_directory(&home),
+            Some(PathBuf::from("/opt/appimages"))
+        );
+
+        fs::write(&config, "[AppImageLauncher]\nask_to_move=true\n").unwrap();
+        assert_eq!(appimagelauncher_directory(&home), None);
+
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn user_flatpak_launcher_is_found() {
+        let home = temp_directory("flatpak");
+        let exports = home.join(".local/share/flatpak/exports/bin");
+        fs::create_dir_all(&exports).unwrap();
+        fs::write(exports.join(FLATPAK_APP_ID), "").unwrap();
+
+        let candidates: Vec<Candidate> = flatpak_candidates(&home)
+            .into_iter()
+            .filter(|candidate| candidate.path.starts_with(&home))
+            .collect();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].path, exports.join(FLATPAK_APP_ID));
+        assert!(candidates[0].kind == InstallationKind::Flatpak);
+
+        fs::remove_dir_all(&home).unwrap();
+    }
+
+    #[test]
+    fn search_paths_accept_files_and_folders() {
+        let directory = temp_directory("search_paths");
+        fs::create_dir_all(directory.join("portable")).unwrap();
+        fs::write(directory.join("portable/rpcs3.exe"), "").unwrap();
+        fs::write(directory.join("custom-name"), "").unwrap();
+
+        let candidates = search_path_candidates(&[
+            directory.join("portable").display().to_string(),
+            directory.join("custom-name").display().to_string(),
+            directory.join("missing").display().to_string(),
+        ]);
+        let paths: Vec<&PathBuf> = candidates.iter().map(|candidate| &candidate.path).collect();
+        assert_eq!(
+            paths,
+            vec![
+                &directory.join("portable/rpcs3.exe"),
+                &directory.join("custom-name")
+            ]
+        );
+        // A file picked by hand is trusted even without the usual name
+        assert!(candidates[1].kind == InstallationKind::Executable);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn duplicates_and_the_launcher_are_dropped() {
+        let directory = temp_directory("dedupe");
+        let rpcs3 = directory.join("rpcs3");
+        let linked = directory.join("linked");
+        let launcher = directory.join("moddedboost");
+        fs::write(&rpcs3, "").unwrap();
+        fs::hard_link(&rpcs3, &linked).unwrap();
+        fs::write(&launcher, "").unwrap();
+
+        let unique = dedupe_candidates(
+            vec![
+                candidate(rpcs3.clone(), DiscoverySource::SearchPath),
+                candidate(launcher.clone(), DiscoverySource::LocalBin),
+                candidate(linked, DiscoverySource::RunningProcess),
+                candidate(rpcs3.clone(), DiscoverySource::Applications),
+            ],
+            Some(&launcher),
+        );
+        assert_eq!(unique.len(), 1);
+        assert_eq!(unique[0].path, rpcs3);
+        assert!(unique[0].source == DiscoverySource::SearchPath);
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
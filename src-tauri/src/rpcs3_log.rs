@@ -0,0 +1,459 @@
+use std::io::SeekFrom;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{ser::Serializer, Serialize};
+use tauri::{AppHandle, Manager};
+use tokio::fs::File;
+use tokio::io::{AsyncBufReadExt, AsyncSeekExt, BufReader};
+use tokio::sync::Mutex;
+
+use crate::game::current_session;
+use crate::patches::PPU_HASH;
+use crate::rpcs3_cache::cache_homes;
+use crate::rpcs3_directory::{resolve_rpcs3_directory, Rpcs3Directory};
+
+const LOG_EVENT: &str = "game://log";
+const TAIL_INTERVAL: Duration = Duration::from_millis(500);
+// A crashing game can log the same error thousands of times, the first ones are enough
+const MAX_FINDINGS: usize = 50;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error(transparent)]
+    Directory(#[from] crate::rpcs3_directory::Error),
+    #[error("RPCS3.log was not found")]
+    LogNotFound,
+    #[error("no game is running")]
+    NoSession,
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum FindingKind {
+    Fatal,
+    UnimplementedSyscall,
+    MissingFirmware,
+    // Access violations and illegal instructions on a PPU or SPU thread
+    ThreadException,
+    // Missing or wrong RAP file, the NPJB00512 licence
+    License,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogFinding {
+    pub kind: FindingKind,
+    pub line_number: usize,
+    pub line: String,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PatchResult {
+    pub hash: String,
+    pub description: Option<String>,
+    pub applied: bool,
+    pub line_number: usize,
+}
+
+#[derive(Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrashSummary {
+    pub log_path: String,
+    pub modified_at: Option<String>,
+    pub rpcs3_version: Option<String>,
+    // Hash of the executable that was booted, PPU_HASH for the Full Boost EBOOT
+    pub ppu_hash: Option<String>,
+    pub fatal: bool,
+    pub findings: Vec<LogFinding>,
+    // More findings than MAX_FINDINGS were found
+    pub truncated: bool,
+    pub patches: Vec<PatchResult>,
+    // None when the log doesn't mention the patch for PPU_HASH at all
+    pub mod_patch_applied: Option<bool>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogLine {
+    pub line: String,
+    pub kind: Option<FindingKind>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogTail {
+    pub session_id: String,
+    pub lines: Vec<LogLine>,
+}
+
+// Session id of the game whose log is being tailed, one tail at a time
+#[derive(Default)]
+pub struct LogTailState(Mutex<Option<String>>);
+
+// rpcs3 writes the log next to its config on Windows and into the cache directory elsewhere
+fn log_candidates(app: &AppHandle, rpcs3_directory: &Rpcs3Directory) -> Vec<PathBuf> {
+    cache_homes(app, rpcs3_directory)
+        .into_iter()
+        .map(|home| home.join("RPCS3.log"))
+        .collect()
+}
+
+// The most recently written one when there are several, e.g. after switching to a portable install
+pub fn find_log(app: &AppHandle, rpcs3_directory: &Rpcs3Directory) -> Option<PathBuf> {
+    log_candidates(app, rpcs3_directory)
+        .into_iter()
+        .filter_map(|path| {
+            let modified = path
+                .metadata()
+                .and_then(|metadata| metadata.modified())
+                .ok()?;
+            Some((modified, path))
+        })
+        .max_by_key(|(modified, _)| *modified)
+        .map(|(_, path)| path)
+}
+
+// Lines look like "·F 0:00:12.345678 {PPU[0x1000000] Thread (main_thread) [0x00a0b1c4]} VM: ...",
+// older versions leave out the dot and the timestamp.
+fn level(line: &str) -> Option<char> {
+    line.trim_start_matches('·').chars().next()
+}
+
+pub fn classify_line(line: &str) -> Option<FindingKind> {
+    let lower = line.to_lowercase();
+    let is_license = lower.contains(".rap")
+        || lower.contains("rap file")
+        || lower.contains("license")
+        || lower.contains("licence")
+        || lower.contains("npdrm");
+    if is_license && matches!(level(line), Some('F' | 'E')) {
+        return Some(FindingKind::License);
+    }
+    if lower.contains("firmware is not installed")
+        || lower.contains("install firmware")
+        || (lower.contains("/dev_flash/") && lower.contains("failed to load"))
+    {
+        return Some(FindingKind::MissingFirmware);
+    }
+    if lower.contains("unimplemented syscall") {
+        return Some(FindingKind::UnimplementedSyscall);
+    }
+    let is_thread = line.contains("{PPU[") || line.contains("{SPU[");
+    if is_thread
+        && (lower.contains("access violation")
+            || lower.contains("illegal instruction")
+            || lower.contains("exception"))
+    {
+        return Some(FindingKind::ThreadException);
+    }
+    match level(line) {
+        Some('F') => Some(FindingKind::Fatal),
+        _ => None,
+    }
+}
+
+fn quoted_value(line: &str, key: &str) -> Option<String> {
+    let start = line.find(&format!("{}='", key))? + key.len() + 2;
+    let end = line[start..].find('\'')?;
+    Some(line[start..start + end].to_string())
+}
+
+// "PAT: Applied patch (hash='PPU-...', description='...', ...)" or "Failed to apply patch ..."
+fn parse_patch_line(line: &str, line_number: usize) -> Option<PatchResult> {
+    if !line.contains("PAT:") {
+        return None;
+    }
+    let lower = line.to_lowercase();
+    let applied = lower.contains("applied patch");
+    if !applied && !lower.contains("failed to apply") {
+        return None;
+    }
+    Some(PatchResult {
+        hash: quoted_value(line, "hash")?,
+        description: quoted_value(line, "description"),
+        applied,
+        line_number,
+    })
+}
+
+pub async fn analyze_log(log_path: &Path) -> Result<CrashSummary> {
+    let file = File::open(log_path).await?;
+    let mut summary = CrashSummary {
+        log_path: log_path.display().to_string(),
+        modified_at: file
+            .metadata()
+            .await
+            .and_then(|metadata| metadata.modified())
+            .ok()
+            .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).to_rfc3339()),
+        ..Default::default()
+    };
+
+    let mut reader = BufReader::new(file);
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut line_number = 0;
+    while reader.read_until(b'\n', &mut buffer).await? > 0 {
+        line_number += 1;
+        let line = String::from_utf8_lossy(&buffer).trim_end().to_string();
+        buffer.clear();
+
+        if summary.rpcs3_version.is_none() {
+            if let Some(start) = line.find("RPCS3 v") {
+                summary.rpcs3_version = line[start + "RPCS3 ".len()..]
+                    .split_whitespace()
+                    .next()
+                    .map(str::to_string);
+            }
+        }
+        if let Some((_, hash)) = line.split_once("PPU executable hash:") {
+            summary.ppu_hash = hash.split_whitespace().next().map(str::to_string);
+        }
+        if let Some(patch) = parse_patch_line(&line, line_number) {
+            summary.patches.push(patch);
+        }
+        if level(&line) == Some('F') {
+            summary.fatal = true;
+        }
+        if let Some(kind) = classify_line(&line) {
+            match summary.findings.len() < MAX_FINDINGS {
+                true => summary.findings.push(LogFinding {
+                    kind,
+                    line_number,
+                    line,
+                }),
+                false => summary.truncated = true,
+            }
+        }
+    }
+
+    let mod_patches: Vec<&PatchResult> = summary
+        .patches
+        .iter()
+        .filter(|patch| patch.hash == PPU_HASH)
+        .collect();
+    summary.mod_patch_applied = match mod_patches.is_empty() {
+        true => None,
+        false => Some(mod_patches.iter().any(|patch| patch.applied)),
+    };
+    Ok(summary)
+}
+
+// Reads what was appended since the last poll, starting over when rpcs3 recreates the file.
+// A log older than the session is from the previous run and is skipped until rpcs3 replaces it.
+async fn read_new_lines(
+    log_path: &Path,
+    offset: &mut u64,
+    session_start: SystemTime,
+) -> std::io::Result<Vec<LogLine>> {
+    let metadata = tokio::fs::metadata(log_path).await?;
+    if metadata.modified()? < session_start {
+        return Ok(Vec::new());
+    }
+    if metadata.len() < *offset {
+        *offset = 0;
+    }
+
+    let mut file = File::open(log_path).await?;
+    file.seek(SeekFrom::Start(*offset)).await?;
+    let mut reader = BufReader::new(file);
+    let mut buffer: Vec<u8> = Vec::new();
+    let mut lines: Vec<LogLine> = Vec::new();
+    loop {
+        let read = reader.read_until(b'\n', &mut buffer).await?;
+        // A line rpcs3 is still writing is picked up on the next poll
+        if read == 0 || !buffer.ends_with(b"\n") {
+            break;
+        }
+        *offset += read as u64;
+        let line = String::from_utf8_lossy(&buffer).trim_end().to_string();
+        buffer.clear();
+        lines.push(LogLine {
+            kind: classify_line(&line),
+            line,
+        });
+    }
+    Ok(lines)
+}
+
+#[tauri::command]
+pub async fn analyze_rpcs3_log(
+    app: AppHandle,
+    executable_path: &str,
+    directory_override: Option<String>,
+) -> Result<CrashSummary> {
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, executable_path, directory_override.as_deref())?;
+    let log_path = find_log(&app, &rpcs3_directory).ok_or(Error::LogNotFound)?;
+    analyze_log(&log_path).await
+}
+
+// Emits game://log with the new lines until the running session exits, returns the log path
+#[tauri::command]
+pub async fn start_log_tail(
+    app: AppHandle,
+    executable_path: &str,
+    directory_override: Option<String>,
+) -> Result<String> {
+    let session = current_session(&app).await.ok_or(Error::NoSession)?;
+    let rpcs3_directory =
+        resolve_rpcs3_directory(&app, executable_path, directory_override.as_deref())?;
+    // Before the first launch the log may not exist yet, rpcs3 creates it where the first candidate is
+    let log_path = find_log(&app, &rpcs3_directory)
+        .or_else(|| log_candidates(&app, &rpcs3_directory).pop())
+        .ok_or(Error::LogNotFound)?;
+
+    let state = app.state::<LogTailState>();
+    let mut tailing = state.0.lock().await;
+    if tailing.as_ref() == Some(&session.session_id) {
+        return Ok(log_path.display().to_string());
+    }
+    *tailing = Some(session.session_id.clone());
+    drop(tailing);
+
+    let session_start = chrono::DateTime::parse_from_rfc3339(&session.started_at)
+        .map(SystemTime::from)
+        .unwrap_or_else(|_| SystemTime::now());
+    let tail_app = app.clone();
+    let tail_path = log_path.clone();
+    tauri::async_runtime::spawn(async move {
+        let mut offset: u64 = 0;
+        loop {
+            let running = current_session(&tail_app)
+                .await
+                .is_some_and(|current| current.session_id == session.session_id);
+            // One last read after the game exits, the crash is usually at the very end
+            match read_new_lines(&tail_path, &mut offset, session_start).await {
+                Ok(lines) if !lines.is_empty() => {
+                    let _ = tail_app.emit(
+                        LOG_EVENT,
+                        LogTail {
+                            session_id: session.session_id.clone(),
+                            lines,
+                        },
+                    );
+                }
+                _ => {}
+            }
+            if !running {
+                break;
+            }
+            tokio::time::sleep(TAIL_INTERVAL).await;
+        }
+
+        let state = tail_app.state::<LogTailState>();
+        let mut tailing = state.0.lock().await;
+        if tailing.as_ref() == Some(&session.session_id) {
+            *tailing = None;
+        }
+    });
+
+    Ok(log_path.display().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classify_line_finds_crash_causes() {
+        let cases = [
+            (
+                "·F 0:00:12.345678 {PPU[0x1000000] Thread (main_thread) [0x00a0b1c4]} VM: Access violation reading location 0x0",
+                Some(FindingKind::ThreadException),
+            ),
+            (
+                "·E 0:00:01.000000 {PPU[0x1000000] Thread (main_thread) [0x0001f7c8]} sceNp: npDrmIsAvailable(): Failed to verify the RAP file",
+                Some(FindingKind::License),
+            ),
+            (
+                "·E 0:00:00.500000 SYS: Failed to load /dev_flash/sys/external/liblv2.sprx",
+                Some(FindingKind::MissingFirmware),
+            ),
+            (
+                "·E 0:00:03.000000 {PPU[0x1000000] Thread (main_thread)} SYS: Unimplemented syscall sys_fs_fcntl",
+                Some(FindingKind::UnimplementedSyscall),
+            ),
+            (
+                "·F 0:00:04.000000 SYS: Emulator stopped",
+                Some(FindingKind::Fatal),
+            ),
+            // A licence mention that isn't an error is just information
+            ("·! 0:00:00.100000 SYS: Loaded license file", None),
+            ("·S 0:00:00.000000 SYS: RPCS3 v0.0.31-16271-8ef9fa2c Alpha | HEAD", None),
+        ];
+        for (line, kind) in cases {
+            assert_eq!(classify_line(line), kind, "{}", line);
+        }
+    }
+
+    #[test]
+    fn parse_patch_line_reads_applied_and_failed_patches() {
+        let applied = parse_patch_line(
+            "·S 0:00:00.900000 PAT: Applied patch (hash='PPU-a787b532b03b2ebc3970a1d405639b05bec1a506', description='Full Boost', title='EXVS FB', serials='NPJB00512', version='01.00')",
+            10,
+        )
+        .unwrap();
+        assert_eq!(applied.hash, PPU_HASH);
+        assert_eq!(applied.description.as_deref(), Some("Full Boost"));
+        assert!(applied.applied);
+        assert_eq!(applied.line_number, 10);
+
+        let failed = parse_patch_line(
+            "·E 0:00:00.900000 PAT: Failed to apply patch (hash='PPU-1234', location=0x10000)",
+            11,
+        )
+        .unwrap();
+        assert!(!failed.applied);
+        assert_eq!(failed.description, None);
+
+        assert!(parse_patch_line("·S 0:00:00.900000 PAT: Loaded 3 patches", 12).is_none());
+        assert!(parse_patch_line("Applied patch (hash='PPU-1234')", 13).is_none());
+    }
+
+    #[tokio::test]
+    async fn analyze_log_summarizes_a_crash() {
+        let log_path =
+            std::env::temp_dir().join(format!("moddedboost_test_RPCS3_{}.log", std::process::id()));
+        tokio::fs::write(
+            &log_path,
+            format!(
+                "·S 0:00:00.000000 SYS: RPCS3 v0.0.31-16271-8ef9fa2c Alpha | HEAD\n\
+                 ·S 0:00:00.800000 LDR: PPU executable hash: {} (<- 0)\n\
+                 ·E 0:00:00.900000 PAT: Failed to apply patch (hash='{}', description='Full Boost')\n\
+                 ·F 0:00:12.345678 {{PPU[0x1000000] Thread (main_thread)}} VM: Access violation\n",
+                PPU_HASH, PPU_HASH
+            ),
+        )
+        .await
+        .unwrap();
+
+        let summary = analyze_log(&log_path).await.unwrap();
+        tokio::fs::remove_file(&log_path).await.unwrap();
+
+        assert_eq!(
+            summary.rpcs3_version.as_deref(),
+            Some("v0.0.31-16271-8ef9fa2c")
+        );
+        assert_eq!(summary.ppu_hash.as_deref(), Some(PPU_HASH));
+        assert!(summary.fatal);
+        assert_eq!(summary.mod_patch_applied, Some(false));
+        assert_eq!(summary.findings.len(), 1);
+        assert_eq!(summary.findings[0].kind, FindingKind::ThreadException);
+        assert_eq!(summary.findings[0].line_number, 4);
+    }
+}
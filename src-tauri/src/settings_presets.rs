@@ -0,0 +1,355 @@
+use std::path::Path;
+
+use serde::{ser::Serializer, Deserialize, Serialize};
+use tauri::AppHandle;
+
+use crate::metadata::load_metadata;
+use crate::rpcs3_config::{
+    read_settings, write_settings, AudioSettings, ConfigWrite, CoreSettings, NetworkSettings,
+    Rpcs3Settings, VideoSettings,
+};
+use crate::rpcs3_directory::Rpcs3Directory;
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Config(#[from] crate::rpcs3_config::Error),
+    #[error("unknown settings preset {0}")]
+    UnknownPreset(String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+// Only the fields that are set are part of the preset, everything else keeps the user's value
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsPreset {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub settings: Rpcs3Settings,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingDifference {
+    // core, video, audio or network
+    pub section: String,
+    pub field: String,
+    // null when neither config.yml nor the custom config has the key
+    pub current: serde_json::Value,
+    pub preset: serde_json::Value,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PresetDiff {
+    pub preset_id: String,
+    pub matches: bool,
+    pub differences: Vec<SettingDifference>,
+}
+
+fn llvm_core(ppu_threads: u64) -> CoreSettings {
+    CoreSettings {
+        ppu_decoder: Some("Recompiler (LLVM)".to_string()),
+        ppu_threads: Some(ppu_threads),
+        spu_decoder: Some("Recompiler (LLVM)".to_string()),
+        preferred_spu_threads: Some(0),
+        spu_block_size: Some("Safe".to_string()),
+        max_spurs_threads: None,
+    }
+}
+
+// Shipped with the launcher, metadata.json can replace them by id or add more
+fn bundled_presets() -> Vec<SettingsPreset> {
+    vec![
+        SettingsPreset {
+            id: "recommended".to_string(),
+            name: "Recommended".to_string(),
+            description: "LLVM recompilers with Vulkan, what the game is tested with".to_string(),
+            settings: Rpcs3Settings {
+                core: llvm_core(2),
+                video: VideoSettings {
+                    renderer: Some("Vulkan".to_string()),
+                    resolution_scale: Some(100),
+                    frame_limit: Some("Auto".to_string()),
+                    vsync: Some(false),
+                    shader_mode: Some("Async Shader Recompiler".to_string()),
+                    ..Default::default()
+                },
+                audio: AudioSettings {
+                    renderer: Some("Cubeb".to_string()),
+                    enable_buffering: Some(true),
+                    buffer_duration: Some(100),
+                    ..Default::default()
+                },
+                network: NetworkSettings::default(),
+            },
+        },
+        SettingsPreset {
+            id: "lowEnd".to_string(),
+            name: "Low-end PC".to_string(),
+            description: "Lower resolution and fewer SPURS threads for 4 core CPUs and older GPUs"
+                .to_string(),
+            settings: Rpcs3Settings {
+                core: CoreSettings {
+                    max_spurs_threads: Some(4),
+                    ..llvm_core(2)
+                },
+                video: VideoSettings {
+                    renderer: Some("Vulkan".to_string()),
+                    resolution_scale: Some(75),
+                    frame_limit: Some("Auto".to_string()),
+                    vsync: Some(false),
+                    shader_mode: Some("Async Shader Recompiler".to_string()),
+                    ..Default::default()
+                },
+                audio: AudioSettings {
+                    renderer: Some("Cubeb".to_string()),
+                    enable_buffering: Some(true),
+                    buffer_duration: Some(150),
+                    time_stretching: Some(false),
+                    ..Default::default()
+                },
+                network: NetworkSettings::default(),
+            },
+        },
+        SettingsPreset {
+            id: "netplay".to_string(),
+            name: "Netplay/low latency".to_string(),
+            description: "Short audio buffer, no VSync and RPCN for online matches".to_string(),
+            settings: Rpcs3Settings {
+                core: llvm_core(2),
+                video: VideoSettings {
+                    renderer: Some("Vulkan".to_string()),
+                    resolution_scale: Some(100),
+                    frame_limit: Some("Auto".to_string()),
+                    vsync: Some(false),
+                    shader_mode: Some("Async Shader Recompiler".to_string()),
+                    ..Default::default()
+                },
+                audio: AudioSettings {
+                    renderer: Some("Cubeb".to_string()),
+                    enable_buffering: Some(true),
+                    buffer_duration: Some(32),
+                    time_stretching: Some(false),
+                    ..Default::default()
+                },
+                network: NetworkSettings {
+                    internet_enabled: Some("Connected".to_string()),
+                    upnp_enabled: Some(true),
+                    psn_status: Some("RPCN".to_string()),
+                    ..Default::default()
+                },
+            },
+        },
+    ]
+}
+
+pub fn load_presets(app: &AppHandle, beta: bool) -> Vec<SettingsPreset> {
+    let mut presets = bundled_presets();
+    let remote_presets = match load_metadata(app, beta) {
+        Ok(metadata) => metadata.settings_presets,
+        Err(err) => {
+            println!("Failed to load settings presets from metadata: {}", err);
+            Vec::new()
+        }
+    };
+    for preset in remote_presets {
+        match presets.iter_mut().find(|existing| existing.id == preset.id) {
+            Some(existing) => *existing = preset,
+            None => presets.push(preset),
+        }
+    }
+    presets
+}
+
+fn find_preset(app: &AppHandle, beta: bool, preset_id: &str) -> Result<SettingsPreset> {
+    load_presets(app, beta)
+        .into_iter()
+        .find(|preset| preset.id == preset_id)
+        .ok_or(Error::UnknownPreset(preset_id.to_string()))
+}
+
+fn sections(settings: &Rpcs3Settings) -> [(&'static str, serde_json::Value); 4] {
+    let to_json = |value: serde_json::Result<serde_json::Value>| value.unwrap_or_default();
+    [
+        ("core", to_json(serde_json::to_value(&settings.core))),
+        ("video", to_json(serde_json::to_value(&settings.video))),
+        ("audio", to_json(serde_json::to_value(&settings.audio))),
+        ("network", to_json(serde_json::to_value(&settings.network))),
+    ]
+}
+
+pub fn diff_preset(current: &Rpcs3Settings, preset: &SettingsPreset) -> PresetDiff {
+    let mut differences: Vec<SettingDifference> = Vec::new();
+    for ((section, current), (_, wanted)) in sections(current)
+        .into_iter()
+        .zip(sections(&preset.settings))
+    {
+        let serde_json::Value::Object(wanted) = wanted else {
+            continue;
+        };
+        for (field, preset_value) in wanted {
+            if preset_value.is_null() {
+                continue;
+            }
+            let current_value = current.get(&field).cloned().unwrap_or_default();
+            if current_value != preset_value {
+                differences.push(SettingDifference {
+                    section: section.to_string(),
+                    field,
+                    current: current_value,
+                    preset: preset_value,
+                });
+            }
+        }
+    }
+
+    PresetDiff {
+        preset_id: preset.id.clone(),
+        matches: differences.is_empty(),
+        differences,
+    }
+}
+
+#[tauri::command]
+pub async fn get_settings_presets(app: AppHandle, beta: bool) -> Vec<SettingsPreset> {
+    load_presets(&app, beta)
+}
+
+// Presets always go into the game's custom config, config.yml stays as the user set it up
+#[tauri::command]
+pub async fn apply_settings_preset(
+    app: AppHandle,
+    beta: bool,
+    rpcs3_directory: &str,
+    serial: String,
+    preset_id: String,
+) -> Result<ConfigWrite> {
+    let preset = find_preset(&app, beta, &preset_id)?;
+    let rpcs3_directory = Rpcs3Directory::at(Path::new(rpcs3_directory));
+    Ok(write_settings(
+        &rpcs3_directory,
+        Some(&serial),
+        &preset.settings,
+    )?)
+}
+
+// Compares against what rpcs3 would use for the game, config.yml with the custom config on top
+#[tauri::command]
+pub async fn diff_settings_preset(
+    app: AppHandle,
+    beta: bool,
+    rpcs3_directory: &str,
+    serial: String,
+    preset_id: String,
+) -> Result<PresetDiff> {
+    let preset = find_preset(&app, beta, &preset_id)?;
+    let rpcs3_directory = Rpcs3Directory::at(Path::new(rpcs3_directory));
+    let current = read_settings(&rpcs3_directory, Some(&serial))?;
+    Ok(diff_preset(&current.settings, &preset))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn preset(settings: Rpcs3Settings) -> SettingsPreset {
+        SettingsPreset {
+            id: "test".to_string(),
+            name: "Test".to_string(),
+            description: String::new(),
+            settings,
+        }
+    }
+
+    #[test]
+    fn only_set_preset_fields_are_compared() {
+        let current = Rpcs3Settings {
+            video: VideoSettings {
+                renderer: Some("Vulkan".to_string()),
+                resolution_scale: Some(150),
+                vsync: Some(true),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let diff = diff_preset(
+            &current,
+            &preset(Rpcs3Settings {
+                video: VideoSettings {
+                    renderer: Some("Vulkan".to_string()),
+                    resolution_scale: Some(100),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+
+        assert_eq!(diff.preset_id, "test");
+        assert!(!diff.matches);
+        assert_eq!(diff.differences.len(), 1);
+        let difference = &diff.differences[0];
+        assert_eq!(difference.section, "video");
+        assert_eq!(difference.field, "resolutionScale");
+        assert_eq!(difference.current, serde_json::json!(150));
+        assert_eq!(difference.preset, serde_json::json!(100));
+    }
+
+    #[test]
+    fn missing_keys_differ_as_null() {
+        let diff = diff_preset(
+            &Rpcs3Settings::default(),
+            &preset(Rpcs3Settings {
+                audio: AudioSettings {
+                    time_stretching: Some(false),
+                    ..Default::default()
+                },
+                network: NetworkSettings {
+                    psn_status: Some("RPCN".to_string()),
+                    ..Default::default()
+                },
+                ..Default::default()
+            }),
+        );
+
+        let differences: Vec<(&str, &str, &serde_json::Value)> = diff
+            .differences
+            .iter()
+            .map(|difference| {
+                (
+                    difference.section.as_str(),
+                    difference.field.as_str(),
+                    &difference.current,
+                )
+            })
+            .collect();
+        assert_eq!(
+            differences,
+            vec![
+                ("audio", "timeStretching", &serde_json::Value::Null),
+                ("network", "psnStatus", &serde_json::Value::Null),
+            ]
+        );
+    }
+
+    #[test]
+    fn bundled_presets_match_themselves() {
+        for preset in bundled_presets() {
+            let diff = diff_preset(&preset.settings, &preset);
+            assert!(diff.matches, "{} differs from itself", preset.id);
+            assert!(diff.differences.is_empty());
+        }
+    }
+}
@@ -0,0 +1,320 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use serde::{ser::Serializer, Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tauri::path::BaseDirectory;
+use tauri::{AppHandle, Manager};
+use tokio::process::Command;
+use tokio::sync::Mutex;
+
+// Compiled into the binary, the copy under app data is user writable and can't be trusted.
+// Entries without a sha256 fall back to the hash of the bundled resource, which lives in the install directory.
+// When neither exists the tool is never run.
+const MANIFEST: &str = include_str!("../resources/tools/manifest.json");
+
+type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+    #[error("no {0} build for this platform")]
+    UnsupportedPlatform(String),
+    #[error("{0} failed verification and could not be restored: {1}")]
+    Verification(String, String),
+}
+
+impl Serialize for Error {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(self.to_string().as_ref())
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Tool {
+    Rclone,
+    Sfo,
+}
+
+impl Tool {
+    fn name(&self) -> &'static str {
+        match self {
+            Tool::Rclone => "rclone",
+            Tool::Sfo => "sfo",
+        }
+    }
+}
+
+#[derive(Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ToolManifestEntry {
+    name: String,
+    os: String,
+    path: String,
+    version: Option<String>,
+    version_args: Vec<String>,
+    sha256: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ToolManifest {
+    tools: Vec<ToolManifestEntry>,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ToolState {
+    Ok,
+    Missing,
+    // No pinned hash and no bundled copy to take one from, there's nothing to verify against
+    Unverified,
+    HashMismatch,
+    VersionMismatch,
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ToolStatus {
+    pub name: String,
+    pub path: String,
+    pub state: ToolState,
+    // false when the expected hash comes from the bundled resource instead of the manifest
+    pub pinned: bool,
+    pub expected_sha256: Option<String>,
+    pub actual_sha256: Option<String>,
+    pub expected_version: Option<String>,
+    pub installed_version: Option<String>,
+}
+
+// Managed in app state, remembers the modified time and size of every tool that passed verification
+// so rclone (tens of MB) isn't hashed again on every file copy.
+#[derive(Default)]
+pub struct ToolVerificationCache(Mutex<HashMap<PathBuf, (SystemTime, u64)>>);
+
+fn manifest_entry(tool: Tool) -> Result<ToolManifestEntry> {
+    let manifest: ToolManifest = serde_json::from_str(MANIFEST).expect("invalid tools manifest");
+    manifest
+        .tools
+        .into_iter()
+        .find(|entry| entry.name == tool.name() && entry.os == std::env::consts::OS)
+        .ok_or(Error::UnsupportedPlatform(tool.name().to_string()))
+}
+
+fn file_fingerprint(path: &Path) -> Option<(SystemTime, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok()?, metadata.len()))
+}
+
+fn sha256_file(path: &Path) -> std::io::Result<String> {
+    let file = File::open(path)?;
+    let mut reader = BufReader::with_capacity(1_000_000, file);
+    let mut hasher = Sha256::new();
+
+    loop {
+        let part = reader.fill_buf()?;
+        if part.is_empty() {
+            break;
+        }
+        hasher.update(part);
+        let part_len = part.len();
+        reader.consume(part_len);
+    }
+
+    Ok(format!("{:x}", hasher.finalize()))
+}
+
+async fn sha256_file_async(path: &Path) -> Option<String> {
+    let path = path.to_path_buf();
+    tauri::async_runtime::spawn_blocking(move || sha256_file(&path).ok())
+        .await
+        .ok()
+        .flatten()
+}
+
+async fn installed_version(path: &Path, entry: &ToolManifestEntry) -> Option<String> {
+    let output = Command::new(path)
+        .args(&entry.version_args)
+        .output()
+        .await
+        .ok()?;
+
+    // "SFO v1.02 (January 4, 2022)" / "rclone v1.65.2"
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    stdout
+        .lines()
+        .next()?
+        .split_whitespace()
+        .find_map(|word| word.strip_prefix('v'))
+        .map(|version| version.to_string())
+}
+
+fn make_executable(path: &Path) -> std::io::Result<()> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mut permissions = fs::metadata(path)?.permissions();
+        permissions.set_mode(0o755);
+        fs::set_permissions(path, permissions)?;
+    }
+    #[cfg(not(unix))]
+    let _ = path;
+
+    Ok(())
+}
+
+fn resolve_paths(app: &AppHandle, entry: &ToolManifestEntry) -> (PathBuf, PathBuf) {
+    let installed_path = app
+        .path()
+        .resolve(&entry.path, BaseDirectory::AppData)
+        .expect("failed to resolve resource");
+    let bundled_path = app
+        .path()
+        .resolve(format!("resources/{}", entry.path), BaseDirectory::Resource)
+        .expect("failed to resolve resource");
+    (installed_path, bundled_path)
+}
+
+async fn check_tool(app: &AppHandle, entry: &ToolManifestEntry) -> ToolStatus {
+    let (installed_path, bundled_path) = resolve_paths(app, entry);
+
+    let expected_sha256 = match &entry.sha256 {
+        Some(sha256) => Some(sha256.clone()),
+        None => sha256_file_async(&bundled_path).await,
+    };
+    let actual_sha256 = sha256_file_async(&installed_path).await;
+
+    let mut installed_version_value = None;
+    let state = if actual_sha256.is_none() {
+        ToolState::Missing
+    } else if expected_sha256.is_none() {
+        ToolState::Unverified
+    } else if actual_sha256 != expected_sha256 {
+        ToolState::HashMismatch
+    } else {
+        // Hash is fine, the version only needs checking against what the manifest expects.
+        // initialize_resources copies tools without the executable bit, set it before running --version.
+        let _ = make_executable(&installed_path);
+        installed_version_value = installed_version(&installed_path, entry).await;
+        match &entry.version {
+            Some(version) if installed_version_value.as_ref() != Some(version) => {
+                ToolState::VersionMismatch
+            }
+            _ => ToolState::Ok,
+        }
+    };
+
+    ToolStatus {
+        name: entry.name.clone(),
+        path: installed_path.display().to_string(),
+        state,
+        pinned: entry.sha256.is_some(),
+        expected_sha256,
+        actual_sha256,
+        expected_version: entry.version.clone(),
+        installed_version: installed_version_value,
+    }
+}
+
+// Copies the bundled build back over the app data copy, the bundled one is checked against the pinned hash first
+async fn restore_tool(
+    app: &AppHandle,
+    entry: &ToolManifestEntry,
+) -> std::result::Result<(), String> {
+    let (installed_path, bundled_path) = resolve_paths(app, entry);
+
+    if !bundled_path.exists() {
+        return Err("bundled copy is missing".to_string());
+    }
+    if let Some(expected_sha256) = &entry.sha256 {
+        if sha256_file_async(&bundled_path).await.as_ref() != Some(expected_sha256) {
+            return Err("bundled copy does not match the manifest".to_string());
+        }
+    }
+
+    if let Some(parent) = installed_path.parent() {
+        fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+    }
+    fs::copy(&bundled_path, &installed_path).map_err(|err| err.to_string())?;
+    make_executable(&installed_path).map_err(|err| err.to_string())?;
+
+    Ok(())
+}
+
+// Returns the path of a tool that is safe to spawn, restoring it from the bundled resources if needed
+pub async fn ensure_tool(app: &AppHandle, tool: Tool) -> Result<PathBuf> {
+    let entry = manifest_entry(tool)?;
+    let (installed_path, _) = resolve_paths(app, &entry);
+
+    let cache = app.state::<ToolVerificationCache>();
+    let mut cache = cache.0.lock().await;
+    let fingerprint = file_fingerprint(&installed_path);
+    if fingerprint.is_some() && cache.get(&installed_path) == fingerprint.as_ref() {
+        return Ok(installed_path);
+    }
+
+    let mut status = check_tool(app, &entry).await;
+    if !matches!(status.state, ToolState::Ok) {
+        println!("{} failed verification, restoring bundled copy", entry.name);
+        restore_tool(app, &entry)
+            .await
+            .map_err(|err| Error::Verification(entry.name.clone(), err))?;
+
+        status = check_tool(app, &entry).await;
+        if !matches!(status.state, ToolState::Ok) {
+            return Err(Error::Verification(
+                entry.name.clone(),
+                "restored copy still does not match".to_string(),
+            ));
+        }
+    }
+
+    make_executable(&installed_path)?;
+    if let Some(fingerprint) = file_fingerprint(&installed_path) {
+        cache.insert(installed_path.clone(), fingerprint);
+    }
+
+    Ok(installed_path)
+}
+
+#[tauri::command]
+pub async fn get_tool_status(app: AppHandle) -> Result<Vec<ToolStatus>> {
+    let mut statuses: Vec<ToolStatus> = Vec::new();
+    for tool in [Tool::Rclone, Tool::Sfo] {
+        let entry = manifest_entry(tool)?;
+        statuses.push(check_tool(&app, &entry).await);
+    }
+    Ok(statuses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_pins_every_tool_version() {
+        let manifest: ToolManifest = serde_json::from_str(MANIFEST).unwrap();
+        for tool in [Tool::Rclone, Tool::Sfo] {
+            for os in ["linux", "windows"] {
+                let entry = manifest
+                    .tools
+                    .iter()
+                    .find(|entry| entry.name == tool.name() && entry.os == os)
+                    .unwrap_or_else(|| panic!("no {} entry for {}", tool.name(), os));
+                assert!(
+                    entry.version.is_some(),
+                    "{} on {} has no version",
+                    entry.name,
+                    os
+                );
+                assert!(!entry.version_args.is_empty());
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+import {
+  DropdownMenu,
+  DropdownMenuCheckboxItem,
+  DropdownMenuContent,
+  DropdownMenuItem,
+  DropdownMenuSeparator,
+  DropdownMenuTrigger,
+} from "@/components/ui/dropdown-menu.tsx"
+import {useTranslation} from "react-i18next";
+import IconButton from "@/components/common/icon-button.tsx";
+import {RocketIcon} from "@radix-ui/react-icons";
+import {useConfigStore} from "@/lib/store/config.ts";
+import {shallow} from "zustand/shallow";
+import {useEffect, useState} from "react";
+import {VscCheck} from "react-icons/vsc";
+import {platform} from "@tauri-apps/plugin-os";
+import {toast} from "sonner";
+import {DEFAULT_LAUNCH_PROFILE_ID, getLaunchProfiles, LaunchProfile, saveLaunchProfile} from "@/lib/launch-profiles.ts";
+
+type LaunchProfileSelectorProps = {
+  buttonVariant?: "outline" | "link" | "default" | "destructive" | "secondary" | "ghost" | null | undefined,
+  breakpoint?: "sm" | "md" | "lg" | "xl" | "2xl" | "none"
+}
+
+// Wrapper commands the selected profile can be toggled with, they only work on Linux
+const WRAPPERS = [
+  {name: "MangoHud", command: "mangohud"},
+  {name: "GameMode", command: "gamemoderun"},
+]
+
+export function LaunchProfileSelector({buttonVariant = "ghost", breakpoint = "sm"}: LaunchProfileSelectorProps) {
+  const {t} = useTranslation();
+  const [profiles, setProfiles] = useState<LaunchProfile[]>([])
+  const [isLinux, setIsLinux] = useState(false)
+
+  const {launchProfileId, setLaunchProfileId} = useConfigStore(
+    (state) => ({
+      launchProfileId: state.launchProfileId,
+      setLaunchProfileId: state.setLaunchProfileId,
+    }),
+    shallow
+  );
+
+  useEffect(() => {
+    const loadProfiles = async () => {
+      const loadedProfiles = await getLaunchProfiles()
+      setProfiles(loadedProfiles)
+
+      // The stored profile was deleted, the game launches with the default one
+      if (!loadedProfiles.some(profile => profile.id === launchProfileId)) {
+        await setLaunchProfileId(DEFAULT_LAUNCH_PROFILE_ID)
+      }
+    }
+    const loadPlatform = async () => {
+      setIsLinux(await platform() === "linux")
+    }
+    loadProfiles().catch(console.error)
+    loadPlatform().catch(console.error)
+  }, [])
+
+  const selectedProfile = profiles.find(profile => profile.id === launchProfileId)
+
+  const toggleWrapper = async (command: string, enabled: boolean) => {
+    if (!selectedProfile) return
+    const wrappers = enabled
+      ? [...selectedProfile.wrappers, command]
+      : selectedProfile.wrappers.filter(wrapper => wrapper !== command)
+    try {
+      setProfiles(await saveLaunchProfile({...selectedProfile, wrappers}))
+    } catch (err) {
+      toast.error(String(err))
+    }
+  }
+
+  return (
+    <DropdownMenu>
+      <DropdownMenuTrigger asChild>
+        <div>
+          <IconButton
+            buttonVariant={buttonVariant}
+            buttonDescription={t("Launch profile")}
+            tooltipContent={t("Select launch profile")}
+            buttonIcon={<RocketIcon/>}
+            onClick={() => {}}
+            breakpoint={breakpoint}
+          />
+        </div>
+      </DropdownMenuTrigger>
+      <DropdownMenuContent align="end">
+        {
+          profiles.map(profile =>
+            <DropdownMenuItem key={profile.id} className={"flex justify-between"} onClick={() => setLaunchProfileId(profile.id)}>
+              {profile.name}
+              {launchProfileId === profile.id && <VscCheck/>}
+            </DropdownMenuItem>
+          )
+        }
+        {
+          isLinux && selectedProfile && <>
+            <DropdownMenuSeparator/>
+            {
+              WRAPPERS.map(wrapper =>
+                <DropdownMenuCheckboxItem
+                  key={wrapper.command}
+                  checked={selectedProfile.wrappers.includes(wrapper.command)}
+                  onCheckedChange={checked => toggleWrapper(wrapper.command, checked)}
+                >
+                  {wrapper.name}
+                </DropdownMenuCheckboxItem>
+              )
+            }
+          </>
+        }
+      </DropdownMenuContent>
+    </DropdownMenu>
+  )
+}
@@ -5,7 +5,7 @@ import {GlobeIcon} from "@radix-ui/react-icons";
 import {useConfigStore} from "@/lib/store/config.ts";
 import {shallow} from "zustand/shallow";
 import {useEffect, useState} from "react";
-import {loadMirrors, Mirrors} from "@/lib/mirrors.ts";
+import {benchmarkMirrors, loadMirrors, Mirrors} from "@/lib/mirrors.ts";
 import {VscCheck} from "react-icons/vsc";
 import {invoke} from "@tauri-apps/api/core";
 
@@ -39,6 +39,17 @@ export function MirrorGroupSelector({buttonVariant = "ghost", breakpoint = "sm"}
     const autoSelectMirror = async () => {
       // Ignore if mirrors aren't loaded or there's already a selected Mirror Group
       if (!mirrors || selectedMirrorGroup.remotes.length) return;
+
+      // First launch, pick the group that actually downloads fastest from here
+      const benchmark = await benchmarkMirrors().catch(err => {
+        console.error(err)
+        return undefined
+      })
+      const recommendedGroup = mirrors.mirrorGroups.find(mirrorGroup => mirrorGroup.name === benchmark?.recommendedGroup)
+      if (recommendedGroup) {
+        await setSelectedMirrorGroup(recommendedGroup)
+        return
+      }
       
       for (let mirrorGroup of mirrors.mirrorGroups) {
         const isSuccessful = await invoke("get_is_success", {
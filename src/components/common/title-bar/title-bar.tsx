@@ -9,6 +9,7 @@ import {VscChromeClose, VscChromeMaximize, VscChromeMinimize, VscChromeRestore}
 import {getCurrent} from "@tauri-apps/api/window";
 import {useTranslation} from "react-i18next";
 import {MirrorGroupSelector} from "@/components/common/title-bar/mirror-group-selector.tsx";
+import {LaunchProfileSelector} from "@/components/common/title-bar/launch-profile-selector.tsx";
 import {check} from "@tauri-apps/plugin-updater";
 import {useAppStore} from "@/lib/store/app.ts";
 import {toast} from "sonner";
@@ -85,6 +86,7 @@ const TitleBar = () => {
             isLoading={isChecking}
           />
           <MirrorGroupSelector/>
+          <LaunchProfileSelector/>
           <LanguageToggle/>
           <ModeToggle/>
         </div>
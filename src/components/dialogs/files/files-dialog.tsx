@@ -21,10 +21,17 @@ import {useAppStore} from "@/lib/store/app.ts";
 import {toast} from "sonner";
 import i18n from "i18next";
 import {Separator} from "@/components/ui/separator.tsx";
-import {DropdownMenu, DropdownMenuContent, DropdownMenuTrigger} from "@/components/ui/dropdown-menu.tsx";
+import {
+  DropdownMenu,
+  DropdownMenuCheckboxItem,
+  DropdownMenuContent,
+  DropdownMenuTrigger
+} from "@/components/ui/dropdown-menu.tsx";
 import {LuRefreshCcwDot} from "react-icons/lu";
 import {invoke} from "@tauri-apps/api/core";
 import {refreshLocalMetadataList} from "@/lib/refresh.ts";
+import {useConfigStore} from "@/lib/store/config.ts";
+import {shallow} from "zustand/shallow";
 
 interface FileDialogsProps {
   gameId: string;
@@ -38,6 +45,13 @@ const FilesDialog = ({gameId, modVersion, files, triggerContent}: FileDialogsPro
   const [isCheckingUpdates, setIsCheckingUpdates] = useState(false)
   const [isRemovingCache, setIsRemovingCache] = useState(false)
   const { isRefreshing, localMetadata, setLoadedMetadata} = useAppStore()
+  const {autoClearCache, setAutoClearCache} = useConfigStore(
+    (state) => ({
+      autoClearCache: state.autoClearCache,
+      setAutoClearCache: state.setAutoClearCache,
+    }),
+    shallow
+  );
   
   const checkUpdates = async () => {
     setIsCheckingUpdates(true)
@@ -119,6 +133,12 @@ const FilesDialog = ({gameId, modVersion, files, triggerContent}: FileDialogsPro
                       await refresh(true)
                     }}
                   />
+                  <DropdownMenuCheckboxItem
+                    checked={autoClearCache}
+                    onCheckedChange={setAutoClearCache}
+                  >
+                    {t("Clear the PPU and SPU cache when EBOOT.BIN changes")}
+                  </DropdownMenuCheckboxItem>
                 </DropdownMenuContent>
               </DropdownMenu>
             </div>
@@ -5,7 +5,7 @@ import {useTranslation} from "react-i18next";
 import {Dispatch, SetStateAction, useEffect, useState} from "react";
 import {invoke} from "@tauri-apps/api/core";
 import {GameVersions, Metadata, transformPaths} from "@/lib/metadata.ts";
-import {dirname, homeDir, join} from "@tauri-apps/api/path";
+import {join} from "@tauri-apps/api/path";
 import FilesDialog from "@/components/dialogs/files/files-dialog.tsx";
 import IconButton from "@/components/common/icon-button.tsx";
 import {Badge} from "@/components/ui/badge.tsx";
@@ -19,7 +19,17 @@ import i18n from "i18next";
 import {LocalFileMetadata, useAppStore} from "@/lib/store/app.ts";
 import {refreshLocalMetadataList} from "@/lib/refresh.ts";
 import {copyFileCommand} from "@/lib/update.ts";
-import {platform} from "@tauri-apps/plugin-os";
+import {getRpcs3Directory, resolveRpcs3Directory} from "@/lib/rpcs3.ts";
+import {checkOptions, excludeRules, runRclone, syncIgnoreSizeOptions} from "@/lib/rclone.ts";
+import {
+  analyzeRpcs3Log,
+  getGameSession,
+  launchEboot,
+  launchGame,
+  onGameExited,
+  preflightCheck,
+  PreflightItem
+} from "@/lib/game.ts";
 
 type ConfigProps = {
   gameId: GameVersions,
@@ -43,7 +53,7 @@ function GameTabs({gameId, metadata}: ConfigProps) {
   const {t} = useTranslation();
   const {processes, addOrUpdateProcess} = useProcessListStore();
   const {rpcs3Path, mirrorGroup} = useConfigStore.getState()
-  const {isModFilesOutdated, localMetadata, setIsModFilesOutdated, setOpenRunningProcessModal, setOpenInvalidRapFileModal} = useAppStore()
+  const {isModFilesOutdated, localMetadata, setIsModFilesOutdated, setOpenRunningProcessModal, setOpenInvalidRapFileModal, setOpenInitializeModal} = useAppStore()
   const {baseFolderLastChecked, setBaseFolderLastChecked} = useSessionStorage();
   const [baseFolderSyncProcess, setBaseFolderSyncProcess] = useState({...defaultCheckProcess, id: baseFolderSyncProcessId, name: baseFolderSyncProcessId})
   const [baseFolderCheckProcess, setBaseFolderCheckProcess] = useState({...defaultCheckProcess, id: baseFolderCheckProcessId, name: baseFolderCheckProcessId})
@@ -58,69 +68,99 @@ function GameTabs({gameId, metadata}: ConfigProps) {
   const [isLaunchingGame, setIsLaunchingGame] = useState(false)
   const [gameMetadata, setGameMetadata] = useState<Metadata | undefined>()
   
-  const launchGame = async (rpcs3Path: string, gameId: "BLJS10250" | "NPJB00512") => {
-    if (gameId === "NPJB00512") {
-      const rpcs3Directory = platform() === "linux"
-        ? await join(await homeDir(), ".config", "rpcs3")
-        : await dirname(rpcs3Path)
-      const rapFilePath = await join(rpcs3Directory, "dev_hdd0", "home", "00000001", "exdata", "JP0700-NPJB00512_00-FULLBOOST000100A.rap")
-      await invoke<LocalFileMetadata[]>("get_file_metadata_command", {
-        filePaths: [rapFilePath],
-        ignoreModtime: true,
-      }).then(rapFileMetadata => {
-        if (!rapFileMetadata.length || rapFileMetadata[0].checksum != "4c266afc905455958a38a9c5c8590634") {
-          setOpenInvalidRapFileModal(true)
-        }
-      }).catch(err => {
-        console.error(err)
+  // Opens the modal or shows the message for a failed preflight item, returns whether it blocks launching
+  const fixPreflightItem = (item: PreflightItem) => {
+    switch (item.fix) {
+      case "stopRpcs3":
+        setOpenRunningProcessModal(true)
+        return true
+      case "installRap":
         setOpenInvalidRapFileModal(true)
-      })
+        return true
+      case "selectRpcs3":
+        toast.error(i18n.t("The specified file path is not a valid rpcs3 executable!"))
+        return true
+      case "updateRpcs3":
+        toast.error(i18n.t("RPCS3 is too old for the mod: {{detail}}", {detail: item.detail}))
+        return true
+      case "installFirmware":
+        toast.error(i18n.t("The PS3 firmware is not installed in RPCS3"))
+        return true
+      case "installGame":
+        toast.error(i18n.t("The game is not installed in RPCS3"))
+        return true
+      case "initialize":
+        setOpenInitializeModal(true)
+        return true
+      // The game still starts without these, the user can fix them afterwards
+      case "updateModFiles":
+        setIsModFilesOutdated(true)
+        toast.warning(i18n.t("Mod files are outdated: {{files}}", {files: item.detail}))
+        return false
+      case "activatePatch":
+        toast.warning(i18n.t("The patch is not activated"))
+        return false
+      default:
+        return false
     }
-    
-    const isRpcs3Running = await invoke("check_rpcs3_running")
-    if (isRpcs3Running) {
-      setOpenRunningProcessModal(true)
-    } else {
-      setIsLaunchingGame(true)
-      await invoke("launch_game", {
-        fullPath: rpcs3Path,
-        gameType: gameId,
-      });
+  }
+
+  const handleLaunch = async (rpcs3Path: string, gameId: "BLJS10250" | "NPJB00512", exitLauncher = false) => {
+    const report = await preflightCheck(gameId).catch(err => {
+      console.error(err)
+      toast.error(i18n.t("Failed to launch the game: {{error}}", {error: err}))
+      return undefined
+    })
+    if (!report) return
+
+    const blocked = report.items
+      .filter(item => item.status === "failed")
+      .map(fixPreflightItem)
+      .some(blocks => blocks)
+    if (blocked) return
+
+    setIsLaunchingGame(true)
+    // Stays loading until game://exited, launching only waits for rpcs3 to start.
+    // With exitLauncher the EBOOT.BIN is booted directly and the launcher closes itself.
+    const launch = exitLauncher ? launchEboot(gameId, true) : launchGame(rpcs3Path, gameId)
+    await launch.catch(err => {
+      console.error(err)
+      toast.error(i18n.t("Failed to launch the game: {{error}}", {error: err}))
       setIsLaunchingGame(false)
-    }
+    })
   };
 
   const checkBaseFolder = async () => {
     const runCommand = async (remote: string) => {
-      const isSync = await invoke<boolean>("rclone_command", {
+      const isSync = await runRclone({
         command: "check",
         remote: `${remote}`,
         remotePath: `${remote}:/${gameMetadata!.base.remotePath}`,
         targetPath: gameMetadata!.base.path,
-        additionalFlags: "--fast-list",
-        excludeItems: gameMetadata!.base.excludePaths,
+        options: checkOptions,
+        filters: excludeRules(gameMetadata!.base.excludePaths),
         listenerId: "check_base_folder"
       });
 
-      const isDlcSync = await invoke<boolean>("rclone_command", {
+      const isDlcSync = await runRclone({
         command: "check",
         remote: `${remote}`,
         remotePath: `${remote}:/${gameMetadata!.base.dlcRemotePath}`,
         targetPath: gameMetadata!.base.dlcPath,
-        additionalFlags: "--fast-list",
-        excludeItems: [],
+        options: checkOptions,
+        filters: [],
         listenerId: "check_base_folder_dlc"
       });
 
       if (gameId === "BLJS10250") {
         // For BLJS we will sync the base folder of NPJB00512 so that DLC works.
-        const isNPJBDlcSynced = await invoke<boolean>("rclone_command", {
+        const isNPJBDlcSynced = await runRclone({
           command: "check",
           remote: `${remote}`,
           remotePath: `${remote}:/${gameMetadata!.base.dlcNPJBRemoteBasePath}`,
           targetPath: gameMetadata!.base.dlcNPJBBasePath,
-          additionalFlags: "--fast-list",
-          excludeItems: gameMetadata!.base.excludePaths,
+          options: checkOptions,
+          filters: excludeRules(gameMetadata!.base.excludePaths),
           listenerId: "check_base_dlc_npjb_folder"
         });
 
@@ -142,35 +182,35 @@ function GameTabs({gameId, metadata}: ConfigProps) {
   
   const syncBaseFolder = async () => {
     const runCommand = async (remote: string) => {
-      const syncBaseSuccessful = await invoke<boolean>("rclone_command", {
+      const syncBaseSuccessful = await runRclone({
         command: "sync",
         remote: `${remote}`,
         remotePath: `${remote}:/${gameMetadata!.base.remotePath}`,
         targetPath: gameMetadata!.base.path,
-        additionalFlags: "--delete-during --ignore-size --verbose --transfers 4 --checkers 8 --contimeout 60s --timeout 300s --retries 3 --low-level-retries 10 --stats 1s --stats-file-name-length 0 --fast-list",
-        excludeItems: gameMetadata!.base.excludePaths,
+        options: syncIgnoreSizeOptions,
+        filters: excludeRules(gameMetadata!.base.excludePaths),
         listenerId: "sync_base_folder"
       });
 
-      const syncDlcSuccessful = await invoke<boolean>("rclone_command", {
+      const syncDlcSuccessful = await runRclone({
         command: "sync",
         remote: `${remote}`,
         remotePath: `${remote}:/${gameMetadata!.base.dlcRemotePath}`,
         targetPath: gameMetadata!.base.dlcPath,
-        additionalFlags: "--delete-during --ignore-size --verbose --transfers 4 --checkers 8 --contimeout 60s --timeout 300s --retries 3 --low-level-retries 10 --stats 1s --stats-file-name-length 0 --fast-list",
-        excludeItems: gameMetadata!.base.excludePaths,
+        options: syncIgnoreSizeOptions,
+        filters: excludeRules(gameMetadata!.base.excludePaths),
         listenerId: "sync_base_folder"
       });
       
       if (gameId === "BLJS10250") {
         // For BLJS we will sync the base folder of NPJB00512 so that DLC works.
-        const syncNPJBSuccessful = await invoke<boolean>("rclone_command", {
+        const syncNPJBSuccessful = await runRclone({
           command: "sync",
           remote: `${remote}`,
           remotePath: `${remote}:/${gameMetadata!.base.dlcNPJBRemoteBasePath}`,
           targetPath: gameMetadata!.base.dlcNPJBBasePath,
-          additionalFlags: "--delete-during --ignore-size --verbose --transfers 4 --checkers 8 --contimeout 60s --timeout 300s --retries 3 --low-level-retries 10 --stats 1s --stats-file-name-length 0 --fast-list",
-          excludeItems: gameMetadata!.base.excludePaths,
+          options: syncIgnoreSizeOptions,
+          filters: excludeRules(gameMetadata!.base.excludePaths),
           listenerId: "sync_base_dlc_npjb_folder"
         });
         
@@ -199,9 +239,7 @@ function GameTabs({gameId, metadata}: ConfigProps) {
       if (!(patchFileMd5[0]?.checksum ?? "" === gameMetadata!.base.patchMd5))
         return false;
 
-      const rpcs3Directory = platform() === "linux"
-        ? await join(await homeDir(), ".config", "rpcs3")
-        : await dirname(rpcs3Path)
+      const rpcs3Directory = await getRpcs3Directory()
       
       const patchConfigPath = await join(rpcs3Directory, "config", "patch_config.yml");
       const patchActivated = await invoke<boolean>("check_patch_activated", {
@@ -232,9 +270,7 @@ function GameTabs({gameId, metadata}: ConfigProps) {
         if (!copySuccessful) return false;
       }
 
-      const rpcs3Directory = platform() === "linux"
-        ? await join(await homeDir(), ".config", "rpcs3")
-        : await dirname(rpcs3Path)
+      const rpcs3Directory = await getRpcs3Directory()
       
       const patchConfigPath = await join(rpcs3Directory, "config", "patch_config.yml");
       const activationResult = await invoke<boolean>("activate_patch", {
@@ -308,11 +344,31 @@ function GameTabs({gameId, metadata}: ConfigProps) {
     }
   }
   
+  useEffect(() => {
+    getGameSession()
+      .then(session => setIsLaunchingGame(session?.gameId === gameId))
+      .catch(console.error)
+    const unlisten = onGameExited(exited => {
+      if (exited.gameId !== gameId) return
+      setIsLaunchingGame(false)
+      // Point at the likely cause instead of having users send screenshots
+      if (!exited.success) {
+        analyzeRpcs3Log().then(summary => {
+          const finding = summary.findings[0]
+          if (finding) {
+            toast.error(i18n.t("The game crashed: {{line}}", {line: finding.line}))
+          }
+        }).catch(console.error)
+      }
+    })
+    return () => {
+      unlisten.then(unlisten => unlisten())
+    }
+  }, [])
+
   useEffect(() => {
     const convertPaths = async () => {
-      const rpcs3Directory = platform() === "linux" 
-        ? await join(await homeDir(), ".config", "rpcs3")
-        : await dirname(rpcs3Path)
+      const rpcs3Directory = await resolveRpcs3Directory()
         
       // Make a clone of the object as different game versions should have their own copy of their metadata.
       const result = await transformPaths(rpcs3Directory, cloneDeep(metadata), gameId);
@@ -509,7 +565,18 @@ function GameTabs({gameId, metadata}: ConfigProps) {
             buttonDescription={`${t("Launch")} ${gameId}`}
             buttonIcon={<RocketIcon/>}
             onClick={async () => {
-              await launchGame(rpcs3Path, gameId)
+              await handleLaunch(rpcs3Path, gameId)
+            }}/>
+          <IconButton
+            isDisabled={isLaunchingGame}
+            iconPosition={"right"}
+            tooltipContent={t("Boot EBOOT.BIN directly and close the launcher")}
+            buttonVariant={"outline"}
+            buttonClassName={"w-full"}
+            buttonDescription={t("Launch and close launcher")}
+            buttonIcon={<RocketIcon/>}
+            onClick={async () => {
+              await handleLaunch(rpcs3Path, gameId, true)
             }}/>
         </div>
       </Card>
@@ -49,7 +49,10 @@ const Games = () => {
       if (!initialized) {
         navigate(INITIALIZE_ROUTE)
       } else {
-        invoke<DetectedGameVersions>("check_game_versions", {fullPath: rpcs3Path})
+        invoke<DetectedGameVersions>("check_game_versions", {
+          fullPath: rpcs3Path,
+          rpcs3DirectoryOverride: useConfigStore.getState().rpcs3DirectoryOverride || null
+        })
           .then((result) => {
             setGameVersions(result)
           })
@@ -1,4 +1,4 @@
-﻿import {ArrowRightIcon, MagicWandIcon} from "@radix-ui/react-icons";
+﻿import {ArrowRightIcon, MagicWandIcon, MagnifyingGlassIcon} from "@radix-ui/react-icons";
 import {Card, CardContent, CardDescription, CardHeader, CardTitle} from "@/components/ui/card.tsx";
 import {Input} from "@/components/ui/input.tsx";
 import {useTranslation} from "react-i18next";
@@ -11,7 +11,7 @@ import IconButton from "@/components/common/icon-button.tsx";
 import {useAppStore} from "@/lib/store/app.ts";
 import {FileResponse} from "@tauri-apps/plugin-dialog";
 import {useNavigate} from "react-router-dom";
-import {checkRpcs3Initialized, checkRpcs3Validity} from "@/lib/rpcs3.ts";
+import {checkRpcs3Initialized, checkRpcs3Validity, discoverRpcs3Installations} from "@/lib/rpcs3.ts";
 
 export default function Initialize() {
   const {t, i18n} = useTranslation();
@@ -45,6 +45,26 @@ export default function Initialize() {
     await processDirectory(true,  selectedPath.path);
   };
 
+  // Picks the best installation found on the system, so onboarding is a single click
+  const detectRpcs3Path = async (showToast: boolean) => {
+    setIsChecking(true)
+    const installations = await discoverRpcs3Installations().catch(err => {
+      console.error(err)
+      return []
+    })
+    const installation = installations[0]
+    if (!installation) {
+      setIsChecking(false)
+      if (showToast) toast.error(i18n.t("No rpcs3 installation found, please locate it manually."));
+      return
+    }
+
+    inputRef.current!.value = installation.executablePath
+    debouncedHandleChange.cancel()
+    await setRpcs3Path(installation.executablePath)
+    await processDirectory(showToast, installation.executablePath);
+  };
+
   // Handle manual inputs
   const handleRpcs3PathChange = (event: { target: { value: string; }; }) => {
     // Update the ref with the new value
@@ -77,8 +97,12 @@ export default function Initialize() {
   }
 
   useEffect(() => {
-    // Invoke the debounced function for first time loading
-    debouncedHandleChange()
+    // Invoke the debounced function for first time loading, or look for rpcs3 when nothing was chosen yet
+    if (rpcs3Path) {
+      debouncedHandleChange()
+    } else {
+      detectRpcs3Path(false).catch(console.error)
+    }
   }, []);
 
   useEffect(() => {
@@ -124,6 +148,14 @@ export default function Initialize() {
                   isLoading={isChecking}
                   isDisabled={isInitializing}
                 />
+                <IconButton
+                  buttonDescription={t("Detect")}
+                  buttonIcon={<MagnifyingGlassIcon/>}
+                  tooltipContent={t("Find rpcs3 installations automatically")}
+                  onClick={() => detectRpcs3Path(true)}
+                  isLoading={isChecking}
+                  isDisabled={isInitializing}
+                />
               </div>
               {
                 valid ?
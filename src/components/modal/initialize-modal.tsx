@@ -18,14 +18,15 @@ import {invoke} from "@tauri-apps/api/core";
 function InitializeModal() {
   const {t} = useTranslation();
   const {openInitializeModal, setOpenInitializeModal, setIsInitializing} = useAppStore()
-  const {rpcs3Path} = useConfigStore()
+  const {rpcs3Path, rpcs3DirectoryOverride} = useConfigStore()
   
   const initializeCache = async () => {
     setIsInitializing(true)
     const loadingToastId = toast.loading(i18n.t("Starting initialization process..."));
 
     await invoke("initialize", {
-      rpcs3Executable: rpcs3Path
+      rpcs3Executable: rpcs3Path,
+      rpcs3DirectoryOverride: rpcs3DirectoryOverride || null
     })
 
     toast.dismiss(loadingToastId)
@@ -9,14 +9,33 @@ import {
 } from "@/components/ui/alert-dialog.tsx";
 import {useTranslation} from "react-i18next";
 import {useAppStore} from "@/lib/store/app.ts";
+import {useState} from "react";
+import {toast} from "sonner";
+import {stopRpcs3} from "@/lib/rpcs3.ts";
 
 function RunningProcessModal() {
   const {t} = useTranslation();
   const {openRunningProcessModal, setOpenRunningProcessModal} = useAppStore()
 
+  const [isStopping, setIsStopping] = useState(false)
+
   const retry = async () => {
     window.location.reload()
   }
+
+  const stop = async () => {
+    setIsStopping(true)
+    const report = await stopRpcs3().catch(err => {
+      console.error(err)
+      return undefined
+    })
+    setIsStopping(false)
+    if (report?.allStopped) {
+      window.location.reload()
+    } else {
+      toast.error(t("Failed to stop rpcs3, please close it manually"))
+    }
+  }
   
   return (
     <AlertDialog open={openRunningProcessModal} onOpenChange={setOpenRunningProcessModal}>
@@ -31,6 +50,7 @@ function RunningProcessModal() {
           </AlertDialogDescription>
         </AlertDialogHeader>
         <AlertDialogFooter>
+          <AlertDialogAction onClick={stop} disabled={isStopping}>{t("Stop rpcs3")}</AlertDialogAction>
           <AlertDialogAction onClick={retry}>{t("Retry")}</AlertDialogAction>
         </AlertDialogFooter>
       </AlertDialogContent>
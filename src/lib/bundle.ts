@@ -0,0 +1,56 @@
+import {invoke} from "@tauri-apps/api/core";
+import {useConfigStore} from "@/lib/store/config.ts";
+import {GameVersions} from "@/lib/metadata.ts";
+import {getRpcs3Directory} from "@/lib/rpcs3.ts";
+
+export type InstallStep = {
+  name: string,
+  success: boolean,
+  error: string | null
+}
+
+export type InstallReport = {
+  success: boolean,
+  modVersion: string,
+  gameId: GameVersions,
+  steps: InstallStep[],
+  mismatchedFiles: string[]
+}
+
+// Progress of the individual steps is emitted on "install_import_bundle" and "rclone_import_bundle".
+// The bundle has to match the metadata.json of the selected channel.
+export async function importModBundle(archivePath: string, gameId: GameVersions) {
+  const {beta} = useConfigStore.getState()
+
+  return await invoke<InstallReport>("import_mod_bundle", {
+    archivePath,
+    rpcs3Directory: await getRpcs3Directory(),
+    gameId,
+    beta
+  })
+}
+
+export type ExportProgress = {
+  processedBytes: number,
+  totalBytes: number,
+  currentFile: string
+}
+
+export type ExportReport = {
+  outputPath: string,
+  modVersion: string,
+  fileCount: number,
+  totalBytes: number
+}
+
+// outputPath decides the format, .zip or .tar.zst. Progress is emitted on "bundle_export"
+export async function exportModBundle(gameId: GameVersions, outputPath: string) {
+  const {beta} = useConfigStore.getState()
+
+  return await invoke<ExportReport>("export_mod_bundle", {
+    rpcs3Directory: await getRpcs3Directory(),
+    gameId,
+    beta,
+    outputPath
+  })
+}
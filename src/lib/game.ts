@@ -0,0 +1,212 @@
+import {invoke} from "@tauri-apps/api/core";
+import {listen} from "@tauri-apps/api/event";
+import {GameVersions} from "@/lib/metadata.ts";
+import {useConfigStore} from "@/lib/store/config.ts";
+
+export type GameSession = {
+  sessionId: string,
+  gameId: GameVersions,
+  executablePath: string,
+  profileId: string,
+  pid: number | null,
+  startedAt: string
+}
+
+export type GameExited = {
+  sessionId: string,
+  gameId: GameVersions,
+  exitCode: number | null,
+  success: boolean,
+  durationMs: number,
+  error: string | null
+}
+
+export type GameOutput = {
+  sessionId: string,
+  stream: "stdout" | "stderr",
+  line: string
+}
+
+// Resolves once rpcs3 is started, the backend refuses while another session is active
+export async function launchGame(rpcs3Path: string, gameId: GameVersions): Promise<GameSession> {
+  const {launchProfileId} = useConfigStore.getState()
+  await syncEbootCache(gameId)
+  return await invoke<GameSession>("launch_game", {
+    fullPath: rpcs3Path,
+    gameType: gameId,
+    profileId: launchProfileId || null
+  })
+}
+
+// Boots the game's EBOOT.BIN directly, with exitLauncher the launcher closes once rpcs3 is started
+export async function launchEboot(gameId: GameVersions, exitLauncher: boolean): Promise<GameSession> {
+  const {rpcs3Path, rpcs3DirectoryOverride, launchProfileId} = useConfigStore.getState()
+  await syncEbootCache(gameId)
+  return await invoke<GameSession>("launch_eboot", {
+    fullPath: rpcs3Path,
+    gameId,
+    rpcs3DirectoryOverride: rpcs3DirectoryOverride || null,
+    profileId: launchProfileId || null,
+    exitLauncher
+  })
+}
+
+export async function getGameSession(): Promise<GameSession | null> {
+  return await invoke<GameSession | null>("get_game_session")
+}
+
+export async function onGameStarted(handler: (session: GameSession) => void) {
+  return await listen<GameSession>("game://started", event => handler(event.payload))
+}
+
+export async function onGameExited(handler: (exited: GameExited) => void) {
+  return await listen<GameExited>("game://exited", event => handler(event.payload))
+}
+
+export async function onGameOutput(handler: (output: GameOutput) => void) {
+  return await listen<GameOutput>("game://output", event => handler(event.payload))
+}
+
+export type FindingKind = "fatal" | "unimplementedSyscall" | "missingFirmware" | "threadException" | "license"
+
+export type LogFinding = {
+  kind: FindingKind,
+  lineNumber: number,
+  line: string
+}
+
+export type PatchResult = {
+  hash: string,
+  description: string | null,
+  applied: boolean,
+  lineNumber: number
+}
+
+export type CrashSummary = {
+  logPath: string,
+  modifiedAt: string | null,
+  rpcs3Version: string | null,
+  ppuHash: string | null,
+  fatal: boolean,
+  findings: LogFinding[],
+  truncated: boolean,
+  patches: PatchResult[],
+  modPatchApplied: boolean | null
+}
+
+export type LogTail = {
+  sessionId: string,
+  lines: {line: string, kind: FindingKind | null}[]
+}
+
+// Parses RPCS3.log of the configured rpcs3 for the usual crash causes
+export async function analyzeRpcs3Log(): Promise<CrashSummary> {
+  const {rpcs3Path, rpcs3DirectoryOverride} = useConfigStore.getState()
+  return await invoke<CrashSummary>("analyze_rpcs3_log", {
+    executablePath: rpcs3Path,
+    directoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+// Only while a session is running, game://log stops when the game exits
+export async function startLogTail(): Promise<string> {
+  const {rpcs3Path, rpcs3DirectoryOverride} = useConfigStore.getState()
+  return await invoke<string>("start_log_tail", {
+    executablePath: rpcs3Path,
+    directoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+export async function onGameLog(handler: (tail: LogTail) => void) {
+  return await listen<LogTail>("game://log", event => handler(event.payload))
+}
+
+export type PreflightCheck = "rpcs3Valid" | "rpcs3Version" | "firmware" | "gamePresent" | "rapFile" |
+  "modFiles" | "psarcCache" | "patchActivated" | "rpcs3NotRunning"
+
+export type FixAction = "selectRpcs3" | "updateRpcs3" | "installFirmware" | "installGame" | "installRap" |
+  "updateModFiles" | "initialize" | "activatePatch" | "stopRpcs3"
+
+export type PreflightItem = {
+  check: PreflightCheck,
+  status: "passed" | "failed" | "skipped",
+  detail: string | null,
+  fix: FixAction | null
+}
+
+export type PreflightReport = {
+  gameId: GameVersions,
+  ready: boolean,
+  items: PreflightItem[]
+}
+
+// Everything the game needs before launching, failed items carry the fix to offer
+export async function preflightCheck(gameId: GameVersions): Promise<PreflightReport> {
+  const {rpcs3Path, rpcs3DirectoryOverride, beta} = useConfigStore.getState()
+  return await invoke<PreflightReport>("preflight_check", {
+    fullPath: rpcs3Path,
+    gameId,
+    beta,
+    rpcs3DirectoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+export type CacheKind = "ppu" | "spu" | "shaders"
+
+export type CacheUsage = {
+  gameId: GameVersions,
+  directories: string[],
+  ppuBytes: number,
+  spuBytes: number,
+  shaderBytes: number,
+  otherBytes: number,
+  totalBytes: number
+}
+
+export type EbootCacheCheck = {
+  gameId: GameVersions,
+  md5: string,
+  changed: boolean,
+  cleared: CacheUsage | null
+}
+
+// cache/{SERIAL} of both games, across every directory rpcs3 may have put it in
+export async function getCacheUsage(): Promise<CacheUsage[]> {
+  const {rpcs3Path, rpcs3DirectoryOverride} = useConfigStore.getState()
+  return await invoke<CacheUsage[]>("get_cache_usage", {
+    fullPath: rpcs3Path,
+    rpcs3DirectoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+export async function clearGameCache(gameId: GameVersions, kinds: CacheKind[]): Promise<CacheUsage> {
+  const {rpcs3Path, rpcs3DirectoryOverride} = useConfigStore.getState()
+  return await invoke<CacheUsage>("clear_game_cache", {
+    fullPath: rpcs3Path,
+    gameId,
+    kinds,
+    rpcs3DirectoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+export async function checkEbootCache(gameId: GameVersions): Promise<EbootCacheCheck> {
+  const {rpcs3Path, rpcs3DirectoryOverride, autoClearCache} = useConfigStore.getState()
+  return await invoke<EbootCacheCheck>("check_eboot_cache", {
+    fullPath: rpcs3Path,
+    gameId,
+    autoClear: autoClearCache,
+    rpcs3DirectoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+// Runs before every launch so the stored EBOOT md5 follows updates, a failure never blocks the game
+async function syncEbootCache(gameId: GameVersions) {
+  try {
+    const check = await checkEbootCache(gameId)
+    if (check.cleared) {
+      console.log(`EBOOT.BIN of ${gameId} changed, cleared its PPU and SPU caches`)
+    }
+  } catch (e) {
+    console.error("Failed to check the EBOOT cache", e)
+  }
+}
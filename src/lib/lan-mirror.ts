@@ -0,0 +1,32 @@
+import {invoke} from "@tauri-apps/api/core";
+import {useConfigStore} from "@/lib/store/config.ts";
+import {getRpcs3Directory} from "@/lib/rpcs3.ts";
+
+export type LanMirrorStatus = {
+  running: boolean,
+  port: number | null,
+  urls: string[],
+  modVersion: string | null,
+  servedPaths: string[],
+  skippedFiles: string[]
+}
+
+export async function startLanMirror(port?: number) {
+  const {beta} = useConfigStore.getState()
+  const rpcs3Directory = await getRpcs3Directory()
+
+  return await invoke<LanMirrorStatus>("start_lan_mirror", {rpcs3Directory, beta, port})
+}
+
+export async function stopLanMirror() {
+  return await invoke<LanMirrorStatus>("stop_lan_mirror")
+}
+
+export async function getLanMirrorStatus() {
+  return await invoke<LanMirrorStatus>("get_lan_mirror_status")
+}
+
+// Returns the mod version served by the other launcher, its remote is tried first in every mirror group
+export async function addLanMirror(url: string) {
+  return await invoke<string>("add_lan_mirror", {url})
+}
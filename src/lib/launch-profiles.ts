@@ -0,0 +1,34 @@
+import {invoke} from "@tauri-apps/api/core";
+import {useConfigStore} from "@/lib/store/config.ts";
+
+export type LaunchProfile = {
+  id: string,
+  name: string,
+  noGui: boolean,
+  // {GAME_ID} is replaced with the serial, e.g. "--config" "custom_configs/config_{GAME_ID}.yml"
+  args: string[],
+  env: Record<string, string>,
+  // Linux only, e.g. "gamemoderun", "mangohud" or "taskset -c 2-7"
+  wrappers: string[]
+}
+
+export const DEFAULT_LAUNCH_PROFILE_ID = "default"
+
+export async function getLaunchProfiles(): Promise<LaunchProfile[]> {
+  return await invoke<LaunchProfile[]>("get_launch_profiles")
+}
+
+// Adds the profile or replaces the one with the same id, returns all profiles
+export async function saveLaunchProfile(profile: LaunchProfile): Promise<LaunchProfile[]> {
+  return await invoke<LaunchProfile[]>("save_launch_profile", {profile})
+}
+
+// Launching falls back to the default profile once the selected one is gone
+export async function deleteLaunchProfile(profileId: string): Promise<LaunchProfile[]> {
+  const profiles = await invoke<LaunchProfile[]>("delete_launch_profile", {profileId})
+  const {launchProfileId, setLaunchProfileId} = useConfigStore.getState()
+  if (!profiles.some(profile => profile.id === launchProfileId)) {
+    await setLaunchProfileId(DEFAULT_LAUNCH_PROFILE_ID)
+  }
+  return profiles
+}
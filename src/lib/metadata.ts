@@ -3,6 +3,7 @@ import {readTextFile} from "@tauri-apps/plugin-fs";
 import {updateMetadata} from "@/lib/remote.ts";
 import {cloneDeep} from "lodash";
 import {useConfigStore} from "@/lib/store/config.ts";
+import {joinRpcs3Path, Rpcs3Directory, SettingsPreset} from "@/lib/rpcs3.ts";
 
 export async function loadMetadata(getRemote: boolean) {
   await updateMetadata(getRemote);
@@ -16,15 +17,15 @@ export async function loadMetadata(getRemote: boolean) {
   return metadata
 }
 
-export async function transformPaths(rpcs3Directory: string, metadata: Metadata, gameId: string) {
+export async function transformPaths(rpcs3Directory: Rpcs3Directory, metadata: Metadata, gameId: string) {
   const clonedMetadata = cloneDeep(metadata)
-  clonedMetadata.base.path = await join(rpcs3Directory, replaceGameIdInternal(clonedMetadata.base.path, gameId))
+  clonedMetadata.base.path = await joinRpcs3Path(rpcs3Directory, replaceGameIdInternal(clonedMetadata.base.path, gameId))
   clonedMetadata.base.remotePath = replaceGameIdInternal(clonedMetadata.base.remotePath, gameId)
-  clonedMetadata.base.dlcPath = await join(rpcs3Directory, replaceGameIdInternal(clonedMetadata.base.dlcPath, gameId))
-  clonedMetadata.base.dlcNPJBBasePath = await join(rpcs3Directory, clonedMetadata.base.dlcNPJBBasePath)
-  clonedMetadata.base.patchPath = await join(rpcs3Directory, replaceGameIdInternal(clonedMetadata.base.patchPath, gameId))
+  clonedMetadata.base.dlcPath = await joinRpcs3Path(rpcs3Directory, replaceGameIdInternal(clonedMetadata.base.dlcPath, gameId))
+  clonedMetadata.base.dlcNPJBBasePath = await joinRpcs3Path(rpcs3Directory, clonedMetadata.base.dlcNPJBBasePath)
+  clonedMetadata.base.patchPath = await joinRpcs3Path(rpcs3Directory, replaceGameIdInternal(clonedMetadata.base.patchPath, gameId))
   clonedMetadata.mod.files = await Promise.all(clonedMetadata.mod.files.map(async (item) => {
-    item.path = await join(rpcs3Directory, replaceGameIdInternal(item.path, gameId))
+    item.path = await joinRpcs3Path(rpcs3Directory, replaceGameIdInternal(item.path, gameId))
     item.remotePath = replaceGameIdInternal(item.remotePath, gameId)
     return item
   }));
@@ -40,7 +41,9 @@ export type GameVersions = "NPJB00512" | "BLJS10250"
 
 export type Metadata = {
   base: SyncBase,
-  mod: SyncMod
+  mod: SyncMod,
+  minRpcs3Version?: string,
+  settingsPresets?: SettingsPreset[]
 }
 
 export type SyncBase = {
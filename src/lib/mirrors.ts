@@ -1,7 +1,6 @@
 import {updateMirrors} from "@/lib/remote.ts";
-import {resolveResource} from "@tauri-apps/api/path";
-import {readTextFile} from "@tauri-apps/plugin-fs";
 import {useConfigStore} from "@/lib/store/config.ts";
+import {invoke} from "@tauri-apps/api/core";
 
 export type Mirrors = {
   mirrorGroups: MirrorGroup[],
@@ -15,17 +14,86 @@ export type MirrorGroup = {
 
 export type Remotes = {
   name: string,
-  rcloneName: string
+  rcloneName: string,
+  hook?: RemoteHook
+}
+
+export type RemoteHook =
+  | { type: "webDavShareCookie" }
+  | { type: "oauthTokenRefresh", tokenUrl: string }
+  | { type: "signedUrl", endpoint: string }
+
+export type MirrorIssue = {
+  group: string,
+  remote: string | null,
+  message: string
+}
+
+export type MirrorValidation = {
+  valid: boolean,
+  issues: MirrorIssue[]
+}
+
+export type RcloneRemote = {
+  name: string,
+  remoteType: string,
+  source: "central" | "user"
 }
 
 export async function loadMirrors(getRemote: boolean) {
   await updateMirrors(getRemote);
   
   const { beta} = useConfigStore.getState()
-  const mirrorsJson = beta ? "mirrors-beta.json" : "mirrors.json";
+  // Falls back to the bundled copy when the one in app data is missing or broken
+  const mirrors = await invoke<Mirrors>("get_mirrors", {beta})
 
-  const resourcePath = await resolveResource(`resources/${mirrorsJson}`)
-  const mirrors: Mirrors = JSON.parse(await readTextFile(resourcePath))
+  // Remotes the user added in their own rclone.conf are tried first in every mirror group
+  const rcloneRemotes = await invoke<RcloneRemote[]>("list_rclone_remotes").catch(err => {
+    console.error(err)
+    return [] as RcloneRemote[]
+  })
+  const knownRemotes = mirrors.mirrorGroups.flatMap(mirrorGroup => mirrorGroup.remotes.map(remote => remote.rcloneName))
+  const userRemotes: Remotes[] = rcloneRemotes
+    .filter(remote => remote.source === "user" && !knownRemotes.includes(remote.name))
+    .map(remote => ({name: remote.name, rcloneName: remote.name}))
+  mirrors.mirrorGroups = mirrors.mirrorGroups.map(mirrorGroup => ({
+    ...mirrorGroup,
+    remotes: [...userRemotes, ...mirrorGroup.remotes]
+  }))
 
   return { ...mirrors } as Mirrors;
-}
\ No newline at end of file
+}
+export type RemoteBenchmark = {
+  group: string,
+  name: string,
+  rcloneName: string,
+  latencyMs: number | null,
+  throughputBytesPerSec: number | null,
+  downloadedBytes: number,
+  error: string | null
+}
+
+export type GroupBenchmark = {
+  name: string,
+  throughputBytesPerSec: number | null,
+  latencyMs: number | null,
+  reachableRemotes: number
+}
+
+export type MirrorBenchmark = {
+  remotes: RemoteBenchmark[],
+  groups: GroupBenchmark[],
+  recommendedGroup: string | null
+}
+
+// Every rcloneName has to be a remote in the merged rclone.conf
+export async function validateMirrors() {
+  const {beta} = useConfigStore.getState()
+  return await invoke<MirrorValidation>("validate_mirrors", {beta})
+}
+
+// Ranked fastest first, every remote of every group is measured at the same time
+export async function benchmarkMirrors(timeoutSecs?: number) {
+  const {beta} = useConfigStore.getState()
+  return await invoke<MirrorBenchmark>("benchmark_mirrors", {beta, timeoutSecs})
+}
@@ -0,0 +1,116 @@
+import {invoke} from "@tauri-apps/api/core";
+
+export type DeleteMode = "before" | "during" | "after"
+
+export type FilterRule = {
+  kind: "include" | "exclude",
+  pattern: string
+}
+
+export type RcloneOptions = {
+  transfers?: number,
+  checkers?: number,
+  contimeout?: number,
+  timeout?: number,
+  retries?: number,
+  lowLevelRetries?: number,
+  bwlimit?: string,
+  fastList?: boolean,
+  deleteMode?: DeleteMode,
+  dryRun?: boolean,
+  extraArgs?: string[]
+}
+
+export type RcloneErrorReason =
+  "authExpired" | "quotaExceeded" | "notFound" | "networkTimeout" | "diskFull" | "permissionDenied" | "targetNotFound" | "unknown"
+
+export type RcloneResult = {
+  success: boolean,
+  exitCode: number | null,
+  stderrTail: string[],
+  errorReason: RcloneErrorReason | null,
+  shouldFailover: boolean
+}
+
+export type RcloneArgs = {
+  command: string,
+  remote: string,
+  remotePath: string,
+  targetPath: string,
+  options: RcloneOptions,
+  filters: FilterRule[],
+  listenerId: string
+}
+
+// Throws when switching to the next mirror could help, so the mirror loops move on to the next remote
+export async function runRclone(args: RcloneArgs): Promise<boolean> {
+  const result = await invoke<RcloneResult>("rclone_command", args)
+  if (!result.success) {
+    console.error(result.errorReason, result.stderrTail.join("\n"))
+    if (result.shouldFailover) {
+      throw new Error(`rclone failed on ${args.remote}: ${result.errorReason}`)
+    }
+  }
+  return result.success
+}
+
+const progressArgs = ["--verbose", "--stats", "1s", "--stats-file-name-length", "0"]
+
+export const checkOptions: RcloneOptions = {
+  fastList: true
+}
+
+export const copyOptions: RcloneOptions = {
+  contimeout: 60,
+  timeout: 300,
+  retries: 3,
+  lowLevelRetries: 10,
+  fastList: true,
+  extraArgs: ["--no-update-modtime", ...progressArgs]
+}
+
+export const syncOptions: RcloneOptions = {
+  transfers: 4,
+  checkers: 8,
+  contimeout: 60,
+  timeout: 300,
+  retries: 3,
+  lowLevelRetries: 10,
+  fastList: true,
+  deleteMode: "during",
+  extraArgs: progressArgs
+}
+
+export const syncIgnoreSizeOptions: RcloneOptions = {
+  ...syncOptions,
+  extraArgs: ["--ignore-size", ...progressArgs]
+}
+
+export function excludeRules(patterns: string[]): FilterRule[] {
+  return patterns.map(pattern => ({kind: "exclude", pattern}))
+}
+
+export type PreviewMode = {check: {oneWay: boolean}} | "dryRun"
+
+export type PreviewEntry = {
+  path: string,
+  size: number
+}
+
+export type UpdatePreview = {
+  added: PreviewEntry[],
+  changed: PreviewEntry[],
+  deleted: PreviewEntry[],
+  errors: string[],
+  downloadSize: number
+}
+
+export async function previewUpdate(
+  remote: string,
+  remotePath: string,
+  targetPath: string,
+  mode: PreviewMode,
+  filters: FilterRule[]
+): Promise<UpdatePreview> {
+  return await invoke<UpdatePreview>("preview_update", {remote, remotePath, targetPath, mode, filters})
+}
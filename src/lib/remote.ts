@@ -1,91 +1,34 @@
-import {appDataDir, join, resolveResource} from "@tauri-apps/api/path";
-import {readTextFile, writeTextFile} from "@tauri-apps/plugin-fs";
 import {invoke} from "@tauri-apps/api/core";
-import {Mirrors} from "@/lib/mirrors.ts";
-import {Metadata} from "@/lib/metadata.ts";
 import {useConfigStore} from "@/lib/store/config.ts";
 
-type Remote = {
-  metadata: string
-  mirrors: string,
-  rcloneConf: string
-}
-
-export async function updateMetadata(fetchRemote: boolean) {
-  const { beta} = useConfigStore.getState()
-  const remoteJson = beta ? "resources/remote-beta.json" : "resources/remote.json";
-  const metadataJson = beta ? "metadata-beta.json" : "metadata.json";
+export type RemoteFile = "metadata" | "mirrors" | "rcloneConf"
 
-  const resourcePath = await resolveResource(`resources/${metadataJson}`)
-  const metadataPath = await join(await appDataDir(), metadataJson);
-
-  const resourceExist = await invoke("check_path_exist", {
-    fullPath: metadataPath
-  })
+export type FetchResult = {
+  file: RemoteFile,
+  status: "updated" | "notModified" | "cached" | "failed",
+  fetchedAt: string | null,
+  error: string | null
+}
 
-  // At least have a copy if there's no existing resource file
-  if (!resourceExist) {
-    const metadata: Metadata = JSON.parse(await readTextFile(resourcePath))
-    await writeTextFile(metadataPath, JSON.stringify(metadata));
+// The backend only replaces the local copy with a signed, valid one and otherwise keeps the
+// last known good copy, so a failed fetch is logged and the launcher carries on with what it has.
+async function updateRemoteFile(file: RemoteFile, fetchRemote: boolean) {
+  const {beta} = useConfigStore.getState()
+  const result = await invoke<FetchResult>("fetch_remote_file", {file, beta, fetchRemote})
+  if (result.error) {
+    console.error(`Failed to update ${file}: ${result.error}`)
   }
+  return result
+}
 
-  if (fetchRemote) {
-    const remotePath = await resolveResource(remoteJson)
-    const remote: Remote = JSON.parse(await readTextFile(remotePath))
-    const metadataJson = await fetch(remote.metadata, {cache: "no-cache"}).then((res) => res.json()).catch(err => console.error(err))
-    await writeTextFile(metadataPath, JSON.stringify(metadataJson));
-  }
+export async function updateMetadata(fetchRemote: boolean) {
+  return await updateRemoteFile("metadata", fetchRemote)
 }
 
 export async function updateMirrors(fetchRemote: boolean) {
-  const { beta} = useConfigStore.getState()
-  const remoteJson = beta ? "resources/remote-beta.json" : "resources/remote.json";
-  const mirrorsJson = beta ? "mirrors-beta.json" : "mirrors.json";
-  
-  const resourcePath = await resolveResource(`resources/${mirrorsJson}`)
-  const mirrorsPath = await join(await appDataDir(), mirrorsJson);
-
-  const resourceExist = await invoke("check_path_exist", {
-    fullPath: mirrorsPath
-  })
-
-  // At least have a copy if there's no existing resource file
-  if (!resourceExist) {
-    const mirrors: Mirrors = JSON.parse(await readTextFile(resourcePath))
-    await writeTextFile(mirrorsPath, JSON.stringify(mirrors));
-  }
-
-  if (fetchRemote) {
-    const remotePath = await resolveResource(remoteJson)
-    const remote: Remote = JSON.parse(await readTextFile(remotePath))
-    const metadataJson = await fetch(remote.mirrors, {cache: "no-cache"}).then((res) => res.json()).catch(err => console.error(err))
-    await writeTextFile(mirrorsPath, JSON.stringify(metadataJson));
-  }
+  return await updateRemoteFile("mirrors", fetchRemote)
 }
 
 export async function updateRcloneConf(fetchRemote: boolean) {
-  const { beta} = useConfigStore.getState()
-  const remoteJson = beta ? "resources/remote-beta.json" : "resources/remote.json";
-
-  const resourcePath = await resolveResource('resources/tools/rclone/rclone.conf')
-  const rcloneConfPath = await join(await appDataDir(), 'tools/rclone/rclone.conf');
-  
-  const resourceExist = await invoke("check_path_exist", {
-    fullPath: rcloneConfPath
-  })
-  
-  // At least have a copy if there's no existing resource file
-  if (!resourceExist) {
-    const confContent = await readTextFile(resourcePath)
-    await writeTextFile(rcloneConfPath, confContent);
-  }
-
-  if (fetchRemote) {
-    const remotePath = await resolveResource(remoteJson)
-    const remote: Remote = JSON.parse(await readTextFile(remotePath))
-    const confContent = await fetch(remote.rcloneConf, {cache: "no-cache"})
-      .then(res => res.text())
-      .catch(err => console.error(err)) ?? "";
-    await writeTextFile(rcloneConfPath, confContent);
-  }
+  return await updateRemoteFile("rcloneConf", fetchRemote)
 }
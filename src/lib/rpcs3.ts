@@ -1,8 +1,35 @@
 import {invoke} from "@tauri-apps/api/core";
+import {join} from "@tauri-apps/api/path";
+import {toast} from "sonner";
+import i18n from "i18next";
 import {useAppStore} from "@/lib/store/app.ts";
+import {useConfigStore} from "@/lib/store/config.ts";
 
 const REGEX_CHINESE = /[\u3040-\u30ff\u3400-\u4dbf\u4e00-\u9fff\uf900-\ufaff\uff66-\uff9f]/;
 
+export type Rpcs3Version = {
+  major: number,
+  minor: number,
+  patch: number,
+  build: number | null,
+  commit: string | null,
+  raw: string
+}
+
+export type Rpcs3Validation = {
+  valid: boolean,
+  // False when rpcs3 is running or didn't report a version, it's used without checking minVersion
+  verified: boolean,
+  status: "valid" | "notFound" | "launcherExecutable" | "notExecutable" | "running" | "unknownVersion" | "outdated" | "unsupportedOs",
+  version: Rpcs3Version | null,
+  minVersion: Rpcs3Version | null
+}
+
+export async function validateRpcs3Executable(rpcs3Path: string): Promise<Rpcs3Validation> {
+  const {beta} = useConfigStore.getState()
+  return await invoke<Rpcs3Validation>("validate_rpcs3_executable", {fullPath: rpcs3Path, beta})
+}
+
 export async function checkRpcs3Validity(rpcs3Path: string): Promise<boolean> {
   const {setOpenRunningProcessModal, setOpenInvalidPathModal} = useAppStore.getState()
 
@@ -11,10 +38,24 @@ export async function checkRpcs3Validity(rpcs3Path: string): Promise<boolean> {
     setOpenInvalidPathModal(true)
     return false;
   } else {
-    const validExecutable: boolean = await invoke("validate_rpcs3_executable", {fullPath: rpcs3Path})
+    const validation = await validateRpcs3Executable(rpcs3Path)
+
+    // Old builds crash on the modded EBOOT, tell the user which version is needed
+    if (validation.status === "outdated") {
+      toast.error(i18n.t("RPCS3 {{version}} is too old, please update to {{minVersion}} or newer", {
+        version: validation.version?.raw,
+        minVersion: validation.minVersion?.raw
+      }))
+    }
+
+    if (validation.valid && !validation.verified) {
+      toast.warning(validation.status === "running"
+        ? i18n.t("RPCS3 is running, its version could not be checked")
+        : i18n.t("Could not read the RPCS3 version, make sure it is up to date"))
+    }
 
     // If the executable can't be ran to check its validity, it might be caused by another instance of rpcs3 running
-    if (!validExecutable) {
+    if (!validation.valid) {
       const isRunning: boolean = await invoke("check_rpcs3_running")
 
       // On running, show the modal to let user turn off the process and retry again
@@ -23,11 +64,217 @@ export async function checkRpcs3Validity(rpcs3Path: string): Promise<boolean> {
       }
     }
 
-    return validExecutable
+    return validation.valid
   }
 }
 
 export async function checkRpcs3Initialized(rpcs3Path: string): Promise<boolean> {
-  return await invoke("check_initialized", {fullPath: rpcs3Path})
+  const {rpcs3DirectoryOverride} = useConfigStore.getState()
+  return await invoke("check_initialized", {
+    fullPath: rpcs3Path,
+    rpcs3DirectoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+export type VfsPaths = {
+  devHdd0: string,
+  devBdvd: string,
+  games: string,
+  vfsFile: string | null
+}
+
+export type Rpcs3Directory = {
+  path: string,
+  rule: "override" | "executableDirectory" | "flatpak" | "portable" | "xdgConfigHome" | "homeConfig" | "applicationSupport",
+  exists: boolean,
+  vfs: VfsPaths
+}
+
+// The rpcs3 data directory (config/, dev_hdd0/, ...) of the configured executable, resolved by the backend
+export async function resolveRpcs3Directory(): Promise<Rpcs3Directory> {
+  const {rpcs3Path, rpcs3DirectoryOverride} = useConfigStore.getState()
+  return await invoke<Rpcs3Directory>("get_rpcs3_directory", {
+    executablePath: rpcs3Path,
+    directoryOverride: rpcs3DirectoryOverride || null
+  })
+}
+
+export async function getRpcs3Directory(): Promise<string> {
+  return (await resolveRpcs3Directory()).path
+}
+
+// Same as Rpcs3Directory::join, paths starting with a device relocated in vfs.yml land on the real location
+export async function joinRpcs3Path(directory: Rpcs3Directory, relativePath: string): Promise<string> {
+  const trimmedPath = relativePath.replace(/^\/+/, "")
+  const [device, ...rest] = trimmedPath.split("/")
+  const devicePaths: Record<string, string> = {
+    dev_hdd0: directory.vfs.devHdd0,
+    dev_bdvd: directory.vfs.devBdvd,
+    games: directory.vfs.games
+  }
+  const devicePath = devicePaths[device]
+  if (!devicePath) return await join(directory.path, trimmedPath)
+  return rest.length ? await join(devicePath, ...rest) : devicePath
+}
+
+export type FullBoostVersions = {
+  BLJS10250: boolean,
+  NPJB00512: boolean
+}
+
+export type Rpcs3Installation = {
+  executablePath: string,
+  kind: "executable" | "appImage" | "flatpak",
+  source: "applications" | "localBin" | "appImageLauncher" | "flatpak" | "runningProcess" | "searchPath",
+  version: string | null,
+  configDirectory: string,
+  configDirectoryExists: boolean,
+  fullBoost: FullBoostVersions,
+  running: boolean
+}
+
+// Best candidate first, installations that already have Full Boost come before the rest
+export async function discoverRpcs3Installations(): Promise<Rpcs3Installation[]> {
+  const {rpcs3SearchPaths} = useConfigStore.getState()
+  return await invoke<Rpcs3Installation[]>("discover_rpcs3_installations", {searchPaths: rpcs3SearchPaths})
+}
+
+export type GameSerial = keyof FullBoostVersions
+
+export type CoreSettings = {
+  ppuDecoder?: string | null,
+  ppuThreads?: number | null,
+  spuDecoder?: string | null,
+  preferredSpuThreads?: number | null,
+  spuBlockSize?: string | null,
+  maxSpursThreads?: number | null
+}
+
+export type VideoSettings = {
+  renderer?: string | null,
+  resolution?: string | null,
+  resolutionScale?: number | null,
+  frameLimit?: string | null,
+  vsync?: boolean | null,
+  aspectRatio?: string | null,
+  shaderMode?: string | null
 }
 
+export type AudioSettings = {
+  renderer?: string | null,
+  audioFormat?: string | null,
+  masterVolume?: number | null,
+  enableBuffering?: boolean | null,
+  bufferDuration?: number | null,
+  timeStretching?: boolean | null
+}
+
+export type NetworkSettings = {
+  internetEnabled?: string | null,
+  ipAddress?: string | null,
+  bindAddress?: string | null,
+  dnsAddress?: string | null,
+  upnpEnabled?: boolean | null,
+  psnStatus?: string | null
+}
+
+export type Rpcs3Settings = {
+  core?: CoreSettings,
+  video?: VideoSettings,
+  audio?: AudioSettings,
+  network?: NetworkSettings
+}
+
+export type Rpcs3ConfigFile = {
+  path: string,
+  exists: boolean,
+  settings: Rpcs3Settings
+}
+
+export type Rpcs3ConfigWrite = {
+  path: string,
+  backupPath: string | null,
+  settings: Rpcs3Settings
+}
+
+// config.yml without a serial, otherwise the game's custom config on top of it
+export async function getRpcs3Settings(serial?: GameSerial): Promise<Rpcs3ConfigFile> {
+  return await invoke<Rpcs3ConfigFile>("get_rpcs3_settings", {
+    rpcs3Directory: await getRpcs3Directory(),
+    serial: serial ?? null
+  })
+}
+
+// Only the fields that are set are written, the previous file is backed up first
+export async function setRpcs3Settings(settings: Rpcs3Settings, serial?: GameSerial): Promise<Rpcs3ConfigWrite> {
+  return await invoke<Rpcs3ConfigWrite>("set_rpcs3_settings", {
+    rpcs3Directory: await getRpcs3Directory(),
+    serial: serial ?? null,
+    settings
+  })
+}
+
+export type SettingsPreset = {
+  id: string,
+  name: string,
+  description: string,
+  settings: Rpcs3Settings
+}
+
+export type SettingDifference = {
+  section: "core" | "video" | "audio" | "network",
+  field: string,
+  current: string | number | boolean | null,
+  preset: string | number | boolean
+}
+
+export type PresetDiff = {
+  presetId: string,
+  matches: boolean,
+  differences: SettingDifference[]
+}
+
+// The bundled presets with the ones from metadata.json on top
+export async function getSettingsPresets(): Promise<SettingsPreset[]> {
+  const {beta} = useConfigStore.getState()
+  return await invoke<SettingsPreset[]>("get_settings_presets", {beta})
+}
+
+export async function applySettingsPreset(serial: GameSerial, presetId: string): Promise<Rpcs3ConfigWrite> {
+  const {beta} = useConfigStore.getState()
+  return await invoke<Rpcs3ConfigWrite>("apply_settings_preset", {
+    beta,
+    rpcs3Directory: await getRpcs3Directory(),
+    serial,
+    presetId
+  })
+}
+
+export async function diffSettingsPreset(serial: GameSerial, presetId: string): Promise<PresetDiff> {
+  const {beta} = useConfigStore.getState()
+  return await invoke<PresetDiff>("diff_settings_preset", {
+    beta,
+    rpcs3Directory: await getRpcs3Directory(),
+    serial,
+    presetId
+  })
+}
+
+export type StoppedProcess = {
+  pid: number,
+  executablePath: string | null,
+  gracefulRequested: boolean,
+  killed: boolean,
+  stopped: boolean
+}
+
+export type StopReport = {
+  processes: StoppedProcess[],
+  allStopped: boolean
+}
+
+// Closes the configured rpcs3, killing it if it doesn't react within the timeout
+export async function stopRpcs3(timeoutMs?: number): Promise<StopReport> {
+  const {rpcs3Path} = useConfigStore.getState()
+  return await invoke<StopReport>("stop_rpcs3", {fullPath: rpcs3Path, timeoutMs: timeoutMs ?? null})
+}
@@ -16,6 +16,10 @@ export interface ConfigStore {
   beta: boolean;
   language: string;
   rpcs3Path: string;
+  rpcs3SearchPaths: string[];
+  rpcs3DirectoryOverride: string;
+  launchProfileId: string;
+  autoClearCache: boolean;
   mirrorGroup: MirrorGroup;
   filesMetadataCache: {
     path: string,
@@ -27,6 +31,10 @@ export interface ConfigStore {
   setBeta: (beta: boolean) => Promise<void>;
   setLanguage: (language: string) => Promise<void>;
   setRpcs3Path: (rpcs3Path: string) => Promise<void>;
+  setRpcs3SearchPaths: (rpcs3SearchPaths: string[]) => Promise<void>;
+  setRpcs3DirectoryOverride: (rpcs3DirectoryOverride: string) => Promise<void>;
+  setLaunchProfileId: (launchProfileId: string) => Promise<void>;
+  setAutoClearCache: (autoClearCache: boolean) => Promise<void>;
   setFilesMetadataCache: (fileMetadata: { path: string, lastModifiedEpoch: number, md5: string }[]) => Promise<void>;
   setMirrorGroup: (mirrorGroup: MirrorGroup) => Promise<void>;
   setLastSelectedTab: (selectedTab: string) => Promise<void>;
@@ -38,6 +46,10 @@ export const useConfigStore = createWithEqualityFn<ConfigStore>()((set) => ({
   beta: false,
   language: i18n.language || DEFAULT_LANGUAGE,
   rpcs3Path: "",
+  rpcs3SearchPaths: [],
+  rpcs3DirectoryOverride: "",
+  launchProfileId: "default",
+  autoClearCache: false,
   filesMetadataCache: [],
   mirrorGroup: {
     testUrl: "",
@@ -67,6 +79,26 @@ export const useConfigStore = createWithEqualityFn<ConfigStore>()((set) => ({
     await tauriStore.set("rpcs3_path", rpcs3Path);
     await tauriStore.save();
   },
+  setRpcs3SearchPaths: async (rpcs3SearchPaths) => {
+    set({rpcs3SearchPaths: rpcs3SearchPaths});
+    await tauriStore.set("rpcs3_search_paths", rpcs3SearchPaths);
+    await tauriStore.save();
+  },
+  setRpcs3DirectoryOverride: async (rpcs3DirectoryOverride) => {
+    set({rpcs3DirectoryOverride: rpcs3DirectoryOverride});
+    await tauriStore.set("rpcs3_directory_override", rpcs3DirectoryOverride);
+    await tauriStore.save();
+  },
+  setLaunchProfileId: async (launchProfileId) => {
+    set({launchProfileId: launchProfileId});
+    await tauriStore.set("launch_profile_id", launchProfileId);
+    await tauriStore.save();
+  },
+  setAutoClearCache: async (autoClearCache) => {
+    set({autoClearCache: autoClearCache});
+    await tauriStore.set("auto_clear_cache", autoClearCache);
+    await tauriStore.save();
+  },
   setFilesMetadataCache: async (fileMetadata: { path: string, lastModifiedEpoch: number, md5: string }[]) => {
     set({filesMetadataCache: fileMetadata});
     await tauriStore.set("file_metadata", fileMetadata);
@@ -91,6 +123,10 @@ const hydrate = async () => {
   const beta = await tauriStore.get("beta");
   const language = await tauriStore.get("language");
   const rpcs3Path = await tauriStore.get("rpcs3_path");
+  const rpcs3SearchPaths = await tauriStore.get("rpcs3_search_paths");
+  const rpcs3DirectoryOverride = await tauriStore.get("rpcs3_directory_override");
+  const launchProfileId = await tauriStore.get("launch_profile_id");
+  const autoClearCache = await tauriStore.get("auto_clear_cache");
   const fileMetadataPath = await tauriStore.get("file_metadata");
   const mirrorGroup = await tauriStore.get("mirror_group");
   const lastSelectedTab = await tauriStore.get("lastSelectedTab");
@@ -99,6 +135,10 @@ const hydrate = async () => {
   const parsedBeta = z.boolean().safeParse(beta);
   const parsedLanguage = z.string().safeParse(language);
   const parsedRpcs3Path = z.string().safeParse(rpcs3Path);
+  const parsedRpcs3SearchPaths = z.array(z.string()).safeParse(rpcs3SearchPaths);
+  const parsedRpcs3DirectoryOverride = z.string().safeParse(rpcs3DirectoryOverride);
+  const parsedLaunchProfileId = z.string().safeParse(launchProfileId);
+  const parsedAutoClearCache = z.boolean().safeParse(autoClearCache);
   const parsedMirrorGroup = z.custom<MirrorGroup>().safeParse(mirrorGroup);
   const parsedLastSelectedTab = z.string().safeParse(lastSelectedTab);
 
@@ -125,6 +165,22 @@ const hydrate = async () => {
     useConfigStore.setState({rpcs3Path: parsedRpcs3Path.data});
   }
 
+  if (parsedRpcs3SearchPaths.success) {
+    useConfigStore.setState({rpcs3SearchPaths: parsedRpcs3SearchPaths.data});
+  }
+
+  if (parsedRpcs3DirectoryOverride.success) {
+    useConfigStore.setState({rpcs3DirectoryOverride: parsedRpcs3DirectoryOverride.data});
+  }
+
+  if (parsedLaunchProfileId.success) {
+    useConfigStore.setState({launchProfileId: parsedLaunchProfileId.data});
+  }
+
+  if (parsedAutoClearCache.success) {
+    useConfigStore.setState({autoClearCache: parsedAutoClearCache.data});
+  }
+
   if (parsedFileMetadataPath.success) {
     useConfigStore.setState({filesMetadataCache: parsedFileMetadataPath.data});
   }
@@ -1,10 +1,11 @@
 import {invoke} from "@tauri-apps/api/core";
-import {dirname, homeDir, join} from "@tauri-apps/api/path";
+import {dirname, join} from "@tauri-apps/api/path";
 import {useConfigStore} from "@/lib/store/config.ts";
 import {ProcessProps, useProcessListStore} from "@/lib/store/process.ts";
 import {ModFiles} from "@/lib/metadata.ts";
 import {refreshLocalMetadata} from "@/lib/refresh.ts";
-import {platform} from "@tauri-apps/plugin-os";
+import {getRpcs3Directory} from "@/lib/rpcs3.ts";
+import {copyOptions, runRclone, syncOptions} from "@/lib/rclone.ts";
 
 const executeCommand = async (
   file: ModFiles, 
@@ -23,39 +24,34 @@ const executeCommand = async (
 }
 
 export const copyFileCommand = async (path: string, remotePath: string, remote: string) => {
-  return await invoke<boolean>("rclone_command", {
+  return await runRclone({
     command: "copyto",
     remote: `${remote}`,
     remotePath: `${remote}:/${remotePath}`,
     targetPath: path,
-    additionalFlags: "--no-update-modtime --verbose --contimeout 60s --timeout 300s --retries 3 --low-level-retries 10 --stats 1s --stats-file-name-length 0 --fast-list",
-    excludeItems: [],
+    options: copyOptions,
+    filters: [],
     listenerId: "copy_file"
   });
 }
 
 export const syncPsarcCommand = async (path: string, remotePath: string, remote: string) => {
   console.debug(path)
-  const {rpcs3Path} = useConfigStore.getState()
-
-  const rpcs3Directory = platform() === "linux"
-    ? await join(await homeDir(), ".config", "rpcs3")
-    : await dirname(rpcs3Path)
+  const rpcs3Directory = await getRpcs3Directory()
   
   const targetDirectory = await join(rpcs3Directory, ".moddedboost", remotePath)
-  return await invoke<boolean>("rclone_command", {
+  return await runRclone({
     command: "sync",
     remote: `${remote}`,
     remotePath: `${remote}:/${remotePath}`,
     targetPath: targetDirectory,
-    additionalFlags: "--delete-during --verbose --transfers 4 --checkers 8 --contimeout 60s --timeout 300s --retries 3 --low-level-retries 10 --stats 1s --stats-file-name-length 0 --fast-list",
-    excludeItems: [],
+    options: syncOptions,
+    filters: [],
     listenerId: "sync_psarc"
   });
 }
 
 export const updateFiles = async (fileProcess: ProcessProps, file: ModFiles) => {
-  const {rpcs3Path} = useConfigStore.getState()
   const {addOrUpdateProcess} = useProcessListStore.getState();
 
   try {
@@ -65,9 +61,7 @@ export const updateFiles = async (fileProcess: ProcessProps, file: ModFiles) =>
       const executeResult = await executeCommand(file, copyFileCommand)
       if (executeResult) await refreshLocalMetadata(file.path, true, false)
     } else if (file.type === "psarc") {
-      const rpcs3Directory = platform() === "linux"
-        ? await join(await homeDir(), ".config", "rpcs3")
-        : await dirname(rpcs3Path)
+      const rpcs3Directory = await getRpcs3Directory()
       
       const targetDirectory = await join(rpcs3Directory, ".moddedboost", file.remotePath)
       const psarcDestinationDirectory = await dirname(file.path)